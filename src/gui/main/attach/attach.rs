@@ -1,4 +1,8 @@
 use super::ProcessEnum;
+use crate::gui::gui::{parse_raw_base, raw_options};
+use crate::gui::main::DARK_THEME;
+use crate::memory::dump::{open_raw_file, open_target_file};
+use crate::memory::source::source::{share_source, SharedSource};
 use crate::process::Process;
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
@@ -8,6 +12,10 @@ pub struct AttachView {
     selected_process_enum: Option<ProcessEnum>,
     selected_process: Option<Process>,
     search_string: String,
+    file_path: String,
+    raw_base: String,
+    raw_bitness: u32,
+    open_error: Option<String>,
 }
 
 impl AttachView {
@@ -17,6 +25,10 @@ impl AttachView {
             selected_process_enum: None,
             selected_process: None,
             search_string: String::default(),
+            file_path: String::default(),
+            raw_base: String::default(),
+            raw_bitness: 64,
+            open_error: None,
         })
     }
 }
@@ -24,8 +36,9 @@ impl AttachView {
 /// Our attach view, this is the screen the client will initially see when it comes to selecting a
 /// process to debug
 impl AttachView {
-    pub fn show(&mut self, ctx: &egui::Context) -> Option<Process> {
-        // Set our return value, this will be set if a process is double clicked
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<SharedSource> {
+        // Set our return value, this will be set if a process is double clicked or a file is
+        // opened
         let mut return_process = None;
 
         // Create a central panel, this view will be sort of bare bones simply because there's not
@@ -124,7 +137,8 @@ impl AttachView {
                                                         self.selected_process_enum =
                                                             Some(process_enum);
                                                         self.selected_process = Some(proc.clone());
-                                                        return_process = Some(proc.clone());
+                                                        return_process =
+                                                            Some(share_source(proc.clone()));
                                                     } else if label.clicked() {
                                                         // If clicked we just want to update the entry
                                                         // to be this newly clicked one as well as
@@ -140,6 +154,46 @@ impl AttachView {
                                         });
                                     });
                                 });
+
+                            // Underneath the process list we'll let files be opened as targets
+                            // instead, these are analysed read only
+                            ui.add_space(6.0);
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.file_path)
+                                        .desired_width(220.0)
+                                        .hint_text("Path to a binary, dump or snapshot..."),
+                                );
+                                if ui.button("Open").clicked() {
+                                    match open_target_file(self.file_path.trim()) {
+                                        Ok(target) => {
+                                            self.open_error = None;
+                                            return_process = Some(target);
+                                        }
+                                        Err(e) => self.open_error = Some(e.to_string()),
+                                    }
+                                }
+                            });
+                            // Raw files have nothing telling us where they go or how to decode
+                            // them so they're opened separately with those chosen up front
+                            ui.horizontal(|ui| {
+                                raw_options(ui, &mut self.raw_base, &mut self.raw_bitness);
+                                if ui.button("Open raw").clicked() {
+                                    let opened = parse_raw_base(&self.raw_base).and_then(|base| {
+                                        open_raw_file(self.file_path.trim(), base, self.raw_bitness)
+                                    });
+                                    match opened {
+                                        Ok(target) => {
+                                            self.open_error = None;
+                                            return_process = Some(target);
+                                        }
+                                        Err(e) => self.open_error = Some(e.to_string()),
+                                    }
+                                }
+                            });
+                            if let Some(error) = &self.open_error {
+                                ui.label(egui::RichText::new(error).color(DARK_THEME.highlight));
+                            }
                         });
                 });
         });
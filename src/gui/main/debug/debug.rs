@@ -1,16 +1,17 @@
 use crate::gui::gui::WindowType;
-use crate::gui::main::toolbar::toolbar::show_bar;
+use crate::gui::main::toolbar::toolbar::{show_bar, ToolbarState};
 use crate::gui::main::Tab;
 use crate::gui::main::TabViewer;
 use crate::gui::main::Window;
 use crate::gui::windows::ActiveWindows;
-use crate::process::Process;
+use crate::memory::source::source::SharedSource;
 use eframe::egui;
 use egui_dock::{DockArea, DockState, NodeIndex, Style};
 
 pub struct DebugView {
     tree: DockState<Window<Tab>>,
-    process: Process,
+    target: SharedSource,
+    toolbar: ToolbarState,
     windows_manager: ActiveWindows<Tab>,
     _left_index: Option<NodeIndex>,
     bottom_index: Option<NodeIndex>,
@@ -46,6 +47,8 @@ impl DebugView {
             WindowType::DisassemblyView
             | WindowType::ImportsView
             | WindowType::AllocationView
+            | WindowType::ThreadView
+            | WindowType::SectionView
             | WindowType::ScannerView => {
                 self.tree
                     .main_surface_mut()
@@ -74,15 +77,17 @@ impl DebugView {
         }
     }
     /// Basic constructor to just setup our tree, windows manager and that's about it really.
-    pub fn new(process: Process) -> Self {
+    pub fn new(target: SharedSource) -> Self {
         // Setup our initial view of the entire window
         //
         let windows_manager = ActiveWindows::default();
         let tree = DockState::new(vec![]);
+        let toolbar = ToolbarState::new(&target);
 
         Self {
             tree,
-            process,
+            target,
+            toolbar,
             windows_manager,
             _left_index: None,
             bottom_index: None,
@@ -92,12 +97,19 @@ impl DebugView {
 
 impl DebugView {
     pub fn show(&mut self, ctx: &egui::Context) {
+        // A file opened through the menu bar replaces the current target along with every tab
+        // Freezing is stopped before anything gets written into the new target
+        if let Some(target) = self.toolbar.opened_target.take() {
+            self.toolbar.freezer.stop();
+            *self = DebugView::new(target);
+        }
+
         // Implement a frame so that we can grab the ui and send that over to show_bar
         egui::CentralPanel::default().show(ctx, |ui| {
             // We'll need to make it a vertical setup so that our toolbar is above the docking area
             ui.vertical(|ui| {
                 // Show our tool bar
-                if let Some(tab) = show_bar(ui, &mut self.process) {
+                if let Some(tab) = show_bar(ui, &self.target, &mut self.toolbar) {
                     self.add_tab(tab);
                 }
 
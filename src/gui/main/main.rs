@@ -29,8 +29,8 @@ impl eframe::App for MyApp {
         // Show menu bar
         match &mut self.current_view {
             View::Attach(attach_view) => {
-                if let Some(proc) = attach_view.show(ctx) {
-                    self.current_view = View::Debug(DebugView::new(proc));
+                if let Some(target) = attach_view.show(ctx) {
+                    self.current_view = View::Debug(DebugView::new(target));
                 }
             }
             View::Debug(dbg_view) => dbg_view.show(ctx),
@@ -6,12 +6,17 @@ use egui::Color32;
 
 use crate::gui::gui::TabContent;
 use crate::gui::gui::Window;
+use crate::gui::windows::address_list_view::address_list_view::AddressListView;
 use crate::gui::windows::allocation_view::allocation_view::AllocationView;
 use crate::gui::windows::disassembly_view::disassembly_view::DisassemblyView;
+use crate::gui::windows::dissect_view::dissect_view::DissectView;
 use crate::gui::windows::function_view::function_view::FunctionView;
 use crate::gui::windows::imports_view::imports_view::ImportsView;
 use crate::gui::windows::module_view::module_view::ModuleView;
+use crate::gui::windows::pointer_scan_view::pointer_scan_view::PointerScanView;
 use crate::gui::windows::scanner_view::scanner_view::ScannerView;
+use crate::gui::windows::section_view::section_view::SectionView;
+use crate::gui::windows::thread_view::thread_view::ThreadView;
 
 // Our struct used for theme colours
 //
@@ -51,6 +56,11 @@ pub enum Tab {
     Imports(ImportsView),
     Allocations(AllocationView),
     Scanner(ScannerView),
+    Threads(ThreadView),
+    Sections(SectionView),
+    AddressList(AddressListView),
+    PointerScan(PointerScanView),
+    Dissect(DissectView),
 }
 
 // Handle abstract tab system, here we'll just match the enum members to what is currently
@@ -65,6 +75,11 @@ impl TabContent for Tab {
             Tab::Imports(view) => view.ui(ui),
             Tab::Allocations(view) => view.ui(ui),
             Tab::Scanner(view) => view.ui(ui),
+            Tab::Threads(view) => view.ui(ui),
+            Tab::Sections(view) => view.ui(ui),
+            Tab::AddressList(view) => view.ui(ui),
+            Tab::PointerScan(view) => view.ui(ui),
+            Tab::Dissect(view) => view.ui(ui),
         }
     }
     fn title(&self) -> String {
@@ -75,6 +90,11 @@ impl TabContent for Tab {
             Tab::Imports(view) => view.title(),
             Tab::Allocations(view) => view.title(),
             Tab::Scanner(view) => view.title(),
+            Tab::Threads(view) => view.title(),
+            Tab::Sections(view) => view.title(),
+            Tab::AddressList(view) => view.title(),
+            Tab::PointerScan(view) => view.title(),
+            Tab::Dissect(view) => view.title(),
         }
     }
 }
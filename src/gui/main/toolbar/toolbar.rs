@@ -1,141 +1,408 @@
 use crate::gui::gui::Window;
 use crate::gui::gui::WindowType;
+use crate::gui::gui::{parse_raw_base, raw_options};
 use crate::gui::main::Tab;
+use crate::gui::main::DARK_THEME;
+use crate::gui::windows::address_list_view::address_list_view::AddressListView;
 use crate::gui::windows::allocation_view::allocation_view::AllocationView;
 use crate::gui::windows::disassembly_view::disassembly_view::DisassemblyView;
+use crate::gui::windows::dissect_view::dissect_view::DissectView;
 use crate::gui::windows::function_view::function_view::FunctionView;
 use crate::gui::windows::imports_view::imports_view::ImportsView;
 use crate::gui::windows::module_view::module_view::ModuleView;
-use crate::gui::windows::scanner_view::scanner_view::ScanType;
+use crate::gui::windows::pointer_scan_view::pointer_scan_view::PointerScanView;
 use crate::gui::windows::scanner_view::scanner_view::ScannerView;
-use crate::gui::windows::scanner_view::scanner_view::ValueType;
-use crate::iterators::allocation_iter::Allocation;
-use crate::memory::process::process::Process;
-use crate::to_rstr;
+use crate::gui::windows::section_view::section_view::SectionView;
+use crate::gui::windows::thread_view::thread_view::ThreadView;
+use crate::memory::address_list::address_list::SharedAddressList;
+use crate::memory::address_list::freezer::{Freezer, SharedFreezer};
+use crate::memory::ctypes::ctypes::load_c_header;
+use crate::memory::dump::snapshot::{SavedSnapshot, SNAPSHOT_EXTENSION};
+use crate::memory::dump::{open_raw_file, open_target_file};
+use crate::memory::process::types::ModuleInfo;
+use crate::memory::process::ProcessErrors;
+use crate::memory::scan::scan_job::ScanJob;
+use crate::memory::source::source::{lock_source, MemorySource, SharedSource};
+use crate::pe::pe::{get_image_headers, get_pe_from_path, get_pe_from_source, get_sections};
 use eframe::egui;
+use exe::VecPE;
+use std::sync::Arc;
+
+/// The little bit of state the menu bar needs to hold onto between frames, this lives within the
+/// debug view and is handed to show_bar each frame
+///
+pub struct ToolbarState {
+    pub file_path: String,
+    pub raw_base: String,
+    pub raw_bitness: u32,
+    pub status: Option<String>,
+    pub opened_target: Option<SharedSource>,
+    /// Outlives every tab so nothing added to it is lost when they're closed
+    pub address_list: SharedAddressList,
+    /// Holds frozen entries of the address list in place, goes along with the target
+    pub freezer: SharedFreezer,
+    /// Snapshot being saved in the background, if any
+    pub snapshot_job: Option<Arc<ScanJob<SavedSnapshot>>>,
+}
+
+impl ToolbarState {
+    /// Set up the state for a target, freezing starts out with nothing frozen
+    ///
+    pub fn new(target: &SharedSource) -> Self {
+        let address_list = SharedAddressList::default();
+        let freezer = Freezer::start(target.clone(), address_list.clone());
+
+        Self {
+            file_path: String::new(),
+            raw_base: String::new(),
+            raw_bitness: 64,
+            status: None,
+            opened_target: None,
+            address_list,
+            freezer: SharedFreezer::new(freezer),
+            snapshot_job: None,
+        }
+    }
+    /// Pick up the snapshot being saved once it's done
+    ///
+    fn poll_snapshot_job(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.snapshot_job.clone() else {
+            return;
+        };
+
+        // Checked before polling so nothing sent right before the worker stopped is missed
+        let finished = job.is_finished();
+        for saved in job.poll() {
+            self.status = Some(match saved {
+                Ok(saved) if saved.bytes_missing != 0 => format!(
+                    "Saved snapshot to {}, {} KB couldn't be read",
+                    saved.path,
+                    saved.bytes_missing / 1024
+                ),
+                Ok(saved) => format!("Saved snapshot to {}", saved.path),
+                Err(_) if job.progress.is_cancelled() => "Snapshot cancelled".to_string(),
+                Err(e) => format!("Failed to save snapshot: {e}"),
+            });
+        }
+
+        if !finished {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        }
+        self.snapshot_job = None;
+    }
+}
+
+/// The module named after the target, not every target has one as dumps without a module list
+/// don't
+///
+fn main_module(source: &mut dyn MemorySource) -> anyhow::Result<ModuleInfo> {
+    let name = source.name();
+    source
+        .modules()?
+        .into_iter()
+        .find(|module| module.name == name)
+        .ok_or_else(|| ProcessErrors::ModuleNotFound { module_name: name }.into())
+}
+
+/// Find the target's main module, along with its image if it has to be read out of the target.
+/// Dumps usually come from another machine so the image may not exist on disk here, if so we'll
+/// parse it straight out of the target's memory instead
+///
+fn main_module_image(source: &mut dyn MemorySource) -> anyhow::Result<(ModuleInfo, Option<VecPE>)> {
+    let main_module = main_module(source)?;
+
+    let pe_file = if std::path::Path::new(&main_module.path).exists() {
+        None
+    } else {
+        get_pe_from_source(source, &main_module).ok()
+    };
+
+    Ok((main_module, pe_file))
+}
 
 /// Realistically we don't need any function other than show_bar so we won't create a struct but
 /// rather just a standalone function that will be called in gui/main.rs in order to display the
 /// menu bar
 ///
-pub fn show_bar(ui: &mut egui::Ui, process: &mut Process) -> Option<Window<Tab>> {
+pub fn show_bar(
+    ui: &mut egui::Ui,
+    target: &SharedSource,
+    state: &mut ToolbarState,
+) -> Option<Window<Tab>> {
     // Store return vector
     let mut new_window = None;
+    state.poll_snapshot_job(ui.ctx());
     // Create the menu frame
     egui::menu::bar(ui, |ui| {
-        ui.menu_button("File", |_ui| {});
+        ui.menu_button("File", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.file_path)
+                    .desired_width(220.0)
+                    .hint_text("Path..."),
+            );
+            // Opening anything, whether it's an image, dump or snapshot, swaps the target out
+            let open_button = ui.button("Open");
+            if open_button.clicked() {
+                match open_target_file(state.file_path.trim()) {
+                    Ok(target) => {
+                        state.status = None;
+                        state.opened_target = Some(target);
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open: {e}")),
+                }
+            }
+            // Raw files are placed and decoded however they're told to be
+            raw_options(ui, &mut state.raw_base, &mut state.raw_bitness);
+            let open_raw_button = ui.button("Open raw");
+            if open_raw_button.clicked() {
+                let opened = parse_raw_base(&state.raw_base).and_then(|base| {
+                    open_raw_file(state.file_path.trim(), base, state.raw_bitness)
+                });
+                match opened {
+                    Ok(target) => {
+                        state.status = None;
+                        state.opened_target = Some(target);
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open: {e}")),
+                }
+            }
+            // Headers are laid out for the target's pointer size and data model, their structs become
+            // value types
+            let header_button = ui.button("Load C header");
+            if header_button.clicked() {
+                let (pointer_size, windows) = {
+                    let source = lock_source(target);
+                    ((source.bitness() as usize / 8).max(4), source.is_windows())
+                };
+                let path = state.file_path.trim();
+                state.status = Some(match load_c_header(path, pointer_size, windows) {
+                    Ok(import) => match import.skipped.is_empty() {
+                        true => format!("Loaded {} types from {path}", import.types.len()),
+                        false => format!(
+                            "Loaded {} types from {path}, couldn't register {}: {}",
+                            import.types.len(),
+                            import.skipped.len(),
+                            import.skipped.join(", ")
+                        ),
+                    },
+                    Err(e) => format!("Failed to load header: {e}"),
+                });
+            }
+            ui.separator();
+            // Snapshots are saved in the background, only one at a time
+            let snapshot_button = ui.add_enabled(
+                state.snapshot_job.is_none(),
+                egui::Button::new("Save snapshot"),
+            );
+            if snapshot_button.clicked() {
+                // Fall back onto the target's name if we weren't given anywhere to save it
+                let path = match state.file_path.trim() {
+                    "" => format!("{}.{}", lock_source(target).name(), SNAPSHOT_EXTENSION),
+                    path => path.to_string(),
+                };
+
+                match ScanJob::save_snapshot(target.clone(), path) {
+                    Ok(job) => {
+                        state.status = None;
+                        state.snapshot_job = Some(Arc::new(job));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to save snapshot: {e}")),
+                }
+            }
+            if let Some(status) = &state.status {
+                ui.label(egui::RichText::new(status).color(DARK_THEME.highlight));
+            }
+        });
         ui.menu_button("Views", |ui| {
             // Handle all views, if they click one of these buttons then we want to launch that tab
             // (aka just return it)
             let disassembly_button = ui.button("Disassembly");
             if disassembly_button.clicked() {
                 // Create the view and set it to the start of the process
-                let process_start = unsafe { process.base().unwrap() };
-
-                new_window = Some(Window::new(
-                    WindowType::DisassemblyView,
-                    Tab::Disassembly(DisassemblyView {
-                        address_start: process_start,
-                        process: process.clone(),
-                        bytes: Vec::new(),
-                        instructions: Vec::new(),
-                        bytes_read: 0,
-                    }),
-                ));
+                let mut source = lock_source(target);
+                match source.base() {
+                    Ok(process_start) => {
+                        new_window = Some(Window::new(
+                            WindowType::DisassemblyView,
+                            Tab::Disassembly(DisassemblyView {
+                                address_start: process_start,
+                                bitness: source.bitness(),
+                                target: target.clone(),
+                                bytes: Vec::new(),
+                                instructions: Vec::new(),
+                                bytes_read: 0,
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open disassembly: {e}")),
+                }
             }
             let module_button = ui.button("Modules");
             if module_button.clicked() {
                 // Get all loaded modules and send it over instead of sending over the entire
                 // process struct
-                let process_modules = unsafe { process.get_modules().unwrap() };
-                new_window = Some(Window::new(
-                    WindowType::ModuleView,
-                    Tab::Module(ModuleView {
-                        modules: process_modules,
-                        selected_module: None,
-                        selected_module_enum: None,
-                    }),
-                ));
+                match lock_source(target).modules() {
+                    Ok(process_modules) => {
+                        new_window = Some(Window::new(
+                            WindowType::ModuleView,
+                            Tab::Module(ModuleView {
+                                modules: process_modules,
+                                selected_module: None,
+                                selected_module_enum: None,
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to list modules: {e}")),
+                }
             }
             let imports_button = ui.button("Imports");
             if imports_button.clicked() {
-                let process_modules = unsafe { process.get_modules().unwrap() };
-                let process_path = process_modules
-                    .iter()
-                    .find(|module| to_rstr!(module.szModule) == process.name())
-                    .map(|module| to_rstr!(module.szExePath))
-                    .unwrap();
+                match main_module_image(&mut *lock_source(target)) {
+                    Ok((main_module, pe_file)) => {
+                        new_window = Some(Window::new(
+                            WindowType::ImportsView,
+                            Tab::Imports(ImportsView {
+                                selected_module_enum: None,
+                                selected_module: None,
+                                selected_function: None,
+                                selected_function_enum: None,
+                                process_path: Some(main_module.path),
+                                pe_file,
+                                target: target.clone(),
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open imports: {e}")),
+                }
+            }
+            let sections_button = ui.button("Sections");
+            if sections_button.clicked() {
+                match main_module_image(&mut *lock_source(target)) {
+                    Ok((main_module, pe_file)) => {
+                        let pe_file = pe_file.or_else(|| get_pe_from_path(main_module.path).ok());
 
-                new_window = Some(Window::new(
-                    WindowType::ImportsView,
-                    Tab::Imports(ImportsView {
-                        selected_module_enum: None,
-                        selected_module: None,
-                        selected_function: None,
-                        selected_function_enum: None,
-                        process_path: Some(process_path),
-                        pe_file: None,
-                        process: process.clone(),
-                    }),
-                ));
+                        new_window = Some(Window::new(
+                            WindowType::SectionView,
+                            Tab::Sections(SectionView {
+                                headers: pe_file.as_ref().and_then(|pe| get_image_headers(pe).ok()),
+                                sections: pe_file
+                                    .as_ref()
+                                    .and_then(|pe| get_sections(pe).ok())
+                                    .unwrap_or_default(),
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open sections: {e}")),
+                }
             }
             let allocation_button = ui.button("Allocations");
             if allocation_button.clicked() {
                 // get all allocations and send it over instead of sending over the entire process
                 // struct
-                let stored_allocs = unsafe { Allocation::new(process).unwrap().collect() };
-                new_window = Some(Window::new(
-                    WindowType::AllocationView,
-                    Tab::Allocations(AllocationView {
-                        selected_allocation_enum: None,
-                        selected_allocation: None,
-                        allocations: stored_allocs,
-                    }),
-                ));
+                match lock_source(target).regions() {
+                    Ok(stored_allocs) => {
+                        new_window = Some(Window::new(
+                            WindowType::AllocationView,
+                            Tab::Allocations(AllocationView {
+                                selected_allocation_enum: None,
+                                selected_allocation: None,
+                                allocations: stored_allocs,
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to list allocations: {e}")),
+                }
+            }
+            let threads_button = ui.button("Threads");
+            if threads_button.clicked() {
+                match lock_source(target).threads() {
+                    Ok(stored_threads) => {
+                        new_window = Some(Window::new(
+                            WindowType::ThreadView,
+                            Tab::Threads(ThreadView {
+                                threads: stored_threads,
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to list threads: {e}")),
+                }
             }
             let function_button = ui.button("Function");
             if function_button.clicked() {
-                let process_modules = unsafe { process.get_modules().unwrap() };
-                let process_path = process_modules
-                    .iter()
-                    .find(|module| to_rstr!(module.szModule) == process.name())
-                    .map(|module| to_rstr!(module.szExePath))
-                    .unwrap();
+                let mut source = lock_source(target);
+                let opened =
+                    main_module(&mut *source).and_then(|module| Ok((module.path, source.base()?)));
 
-                let process_base = unsafe { process.base().unwrap() };
-
-                new_window = Some(Window::new(
-                    WindowType::FunctionView,
-                    Tab::Function(FunctionView {
-                        fmap: Vec::new(),
-                        selected_fn: None,
-                        selected_fn_enum: None,
-                        process_path: Some(process_path),
-                        process_base,
-                        selected_fn_ex: None,
-                    }),
-                ));
+                match opened {
+                    Ok((process_path, process_base)) => {
+                        new_window = Some(Window::new(
+                            WindowType::FunctionView,
+                            Tab::Function(FunctionView {
+                                fmap: Vec::new(),
+                                selected_fn: None,
+                                selected_fn_enum: None,
+                                process_path: Some(process_path),
+                                process_base,
+                                selected_fn_ex: None,
+                            }),
+                        ));
+                    }
+                    Err(e) => state.status = Some(format!("Failed to open functions: {e}")),
+                }
             }
             let scanner_button = ui.button("Scanner");
             if scanner_button.clicked() {
-                let example_results = Vec::new();
-
                 new_window = Some(Window::new(
                     WindowType::ScannerView,
-                    Tab::Scanner(ScannerView {
-                        process: process.clone(),
-                        is_hex: false,
-                        scan_type: ScanType::default(),
-                        value_type: ValueType::default(),
-                        value: String::new(),
-                        fast_scan: true,
-                        results: example_results,
-                    }),
+                    Tab::Scanner(ScannerView::new(target.clone(), state.address_list.clone())),
+                ));
+            }
+            let pointer_scan_button = ui.button("Pointer scan");
+            if pointer_scan_button.clicked() {
+                new_window = Some(Window::new(
+                    WindowType::PointerScanView,
+                    Tab::PointerScan(PointerScanView::new(target.clone())),
+                ));
+            }
+            let dissect_button = ui.button("Dissect");
+            if dissect_button.clicked() {
+                new_window = Some(Window::new(
+                    WindowType::DissectView,
+                    Tab::Dissect(DissectView::new(target.clone())),
+                ));
+            }
+            let address_list_button = ui.button("Address list");
+            if address_list_button.clicked() {
+                new_window = Some(Window::new(
+                    WindowType::AddressListView,
+                    Tab::AddressList(AddressListView::new(
+                        target.clone(),
+                        state.address_list.clone(),
+                        state.freezer.clone(),
+                    )),
                 ));
             }
             let _ = ui.button("Graph -> TO IMPL");
+            // Views that couldn't be opened say why here as well as under File
+            if let Some(status) = &state.status {
+                ui.label(egui::RichText::new(status).color(DARK_THEME.highlight));
+            }
         });
         ui.menu_button("Settings", |_ui| {});
+        if let Some(job) = state.snapshot_job.clone() {
+            let progress = &job.progress;
+            ui.separator();
+            ui.label("Saving snapshot");
+            ui.add(
+                egui::ProgressBar::new(progress.fraction())
+                    .desired_width(120.0)
+                    .show_percentage(),
+            );
+            if ui.button("Cancel").clicked() {
+                job.cancel();
+            }
+        }
     });
 
     new_window
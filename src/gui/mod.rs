@@ -1,5 +1,10 @@
 pub mod gui {
     use crate::memory::breakpoint::breakpoint::{Breakpoint, BreakpointState};
+    use crate::memory::ctypes::ctypes::{read_integer, CType, TypeLibrary};
+    use crate::memory::dump::raw::SUPPORTED_BITNESS;
+    use crate::memory::expression::expression::evaluate_constant;
+    use crate::memory::scan::types::ValueType;
+    use crate::memory::source::source::MemorySource;
     use egui::{Color32, Response, Sense, Shape, Stroke, Ui};
     // Represent all types of windows as an enum
     //
@@ -13,6 +18,11 @@ pub mod gui {
         ModuleView,
         AllocationView,
         ImportsView,
+        ThreadView,
+        SectionView,
+        AddressListView,
+        PointerScanView,
+        DissectView,
     }
     // Basic struct for defining windows and what type they are
     //
@@ -45,6 +55,188 @@ pub mod gui {
         //
         fn title(&self) -> String;
     }
+    // Define the row of options a raw file is opened with, the address it's placed at and which
+    // bitness it's decoded as. Both the attach screen and the file menu can open raw files
+    //
+    pub fn raw_options(ui: &mut Ui, base: &mut String, bitness: &mut u32) {
+        ui.horizontal(|ui| {
+            ui.label("Base:");
+            ui.add(
+                egui::TextEdit::singleline(base)
+                    .desired_width(140.0)
+                    .hint_text("0x0"),
+            );
+            egui::ComboBox::from_id_salt("raw_bitness")
+                .selected_text(format!("{}-bit", bitness))
+                .show_ui(ui, |ui| {
+                    for option in SUPPORTED_BITNESS {
+                        ui.selectable_value(bitness, option, format!("{}-bit", option));
+                    }
+                });
+        });
+    }
+    // Parse the base a raw file was given, there's no target yet so only numbers and arithmetic
+    // are allowed. Defaults to 0 when left empty
+    //
+    pub fn parse_raw_base(base: &str) -> anyhow::Result<u64> {
+        match base.trim() {
+            "" => Ok(0),
+            expression => evaluate_constant(expression),
+        }
+    }
+    // Arrays longer than this only have their first elements shown within a struct tree
+    //
+    pub const MAX_TREE_ELEMENTS: usize = 256;
+    // Most bytes read for a single node, only values and the start of strings are shown from them
+    //
+    pub const MAX_TREE_READ: usize = 0x1000;
+    // Draw a value of a C type at an address as a tree, structs, unions, arrays and pointers can
+    // be expanded. Memory is only read for whatever's open so pointers are followed as they're
+    // expanded, which keeps lists and other structs that point back at themselves from looping
+    //
+    pub fn c_type_tree(
+        ui: &mut Ui,
+        source: &mut dyn MemorySource,
+        library: &TypeLibrary,
+        name: &str,
+        ctype: &CType,
+        address: u64,
+    ) {
+        c_type_node(ui, source, library, name, name, ctype, address);
+    }
+    // A single node of a C type tree. Ids are salted by the path of names leading to the node
+    // rather than its address so nodes stay open as values change, and two fields of the same
+    // name within different structs don't open and close together
+    //
+    fn c_type_node(
+        ui: &mut Ui,
+        source: &mut dyn MemorySource,
+        library: &TypeLibrary,
+        name: &str,
+        path: &str,
+        ctype: &CType,
+        address: u64,
+    ) {
+        let type_name = library.type_name(ctype);
+        // Only values and the start of strings are shown, whole structs and arrays aren't read
+        let mut read = |ctype: &CType| {
+            let size = library.size_of(ctype).unwrap_or(0).min(MAX_TREE_READ);
+            source.read_bytes(address as usize, size)
+        };
+
+        match ctype {
+            CType::Record(record_name) => {
+                let Ok(record) = library.record(record_name) else {
+                    ui.label(format!("{name}: {type_name} (incomplete)"));
+                    return;
+                };
+                egui::CollapsingHeader::new(format!("{name}: {type_name} @ 0x{address:X}"))
+                    .id_salt(path)
+                    .show(ui, |ui| {
+                        for field in record.fields.iter() {
+                            let field_name = match field.name.as_str() {
+                                "" => format!("(anonymous +0x{:X})", field.offset),
+                                field_name => field_name.to_string(),
+                            };
+                            // Pointers can land anywhere so fields may run off the end of
+                            // the address space
+                            let Some(field_address) = address.checked_add(field.offset as u64)
+                            else {
+                                let field_type = library.type_name(&field.ctype);
+                                ui.label(format!("{field_name}: {field_type} = ??"));
+                                continue;
+                            };
+                            c_type_node(
+                                ui,
+                                source,
+                                library,
+                                &field_name,
+                                &format!("{path}.{field_name}"),
+                                &field.ctype,
+                                field_address,
+                            );
+                        }
+                    });
+            }
+            CType::Array(inner, count) => {
+                // Character arrays are shown as the string they hold as well
+                let text = match inner.as_ref() {
+                    CType::Primitive(ValueType::Byte | ValueType::SignedByte) => {
+                        match read(ctype) {
+                            Ok(bytes) => {
+                                let length = bytes
+                                    .iter()
+                                    .position(|&byte| byte == 0)
+                                    .unwrap_or(bytes.len());
+                                format!(" = \"{}\"", String::from_utf8_lossy(&bytes[..length]))
+                            }
+                            Err(_) => " = ??".to_string(),
+                        }
+                    }
+                    _ => String::new(),
+                };
+                let element_size = library.size_of(inner).unwrap_or(0);
+                egui::CollapsingHeader::new(format!("{name}: {type_name}{text}"))
+                    .id_salt(path)
+                    .show(ui, |ui| {
+                        for idx in 0..(*count).min(MAX_TREE_ELEMENTS) {
+                            let element_address = idx
+                                .checked_mul(element_size)
+                                .and_then(|offset| address.checked_add(offset as u64));
+                            let Some(element_address) = element_address else {
+                                ui.label(format!("[{idx}]: {} = ??", library.type_name(inner)));
+                                break;
+                            };
+                            c_type_node(
+                                ui,
+                                source,
+                                library,
+                                &format!("[{idx}]"),
+                                &format!("{path}[{idx}]"),
+                                inner,
+                                element_address,
+                            );
+                        }
+                        if *count > MAX_TREE_ELEMENTS {
+                            ui.label(format!("... {} more", count - MAX_TREE_ELEMENTS));
+                        }
+                    });
+            }
+            CType::Pointer(inner) => {
+                let (text, pointer) = match read(ctype) {
+                    Ok(bytes) => (library.format_value(ctype, &bytes), read_integer(&bytes)),
+                    Err(_) => ("??".to_string(), 0),
+                };
+                match (inner.as_ref(), pointer) {
+                    (CType::Void, _) | (_, 0) => {
+                        ui.label(format!("{name}: {type_name} = {text}"));
+                    }
+                    _ => {
+                        egui::CollapsingHeader::new(format!("{name}: {type_name} = {text}"))
+                            .id_salt(path)
+                            .show(ui, |ui| {
+                                c_type_node(
+                                    ui,
+                                    source,
+                                    library,
+                                    &format!("*{name}"),
+                                    &format!("{path}*"),
+                                    inner,
+                                    pointer,
+                                );
+                            });
+                    }
+                }
+            }
+            _ => {
+                let value = match read(ctype) {
+                    Ok(bytes) => library.format_value(ctype, &bytes),
+                    Err(_) => "??".to_string(),
+                };
+                ui.label(format!("{name}: {type_name} = {value}"));
+            }
+        }
+    }
     // Define a custom ui widget that we'll use for breakpointing
     //
     pub fn selectable_circle(ui: &mut Ui, colour: Color32) -> Response {
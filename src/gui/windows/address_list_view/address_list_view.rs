@@ -0,0 +1,540 @@
+use crate::gui::gui::{c_type_tree, TabContent};
+use crate::gui::main::DARK_THEME;
+use crate::memory::address_list::address_list::{
+    lock_address_list, AddressEntry, AddressList, FreezeMode, SharedAddressList,
+    ADDRESS_LIST_EXTENSION,
+};
+use crate::memory::address_list::cheat_table::{
+    export_cheat_table, import_cheat_table, CHEAT_TABLE_EXTENSION,
+};
+use crate::memory::address_list::freezer::SharedFreezer;
+use crate::memory::ctypes::ctypes::type_library;
+use crate::memory::expression::expression::evaluate_constant;
+use crate::memory::pointer::pointer::PointerChain;
+use crate::memory::scan::compare::extract_value;
+use crate::memory::scan::types::ValueType;
+use crate::memory::source::source::{lock_source, SharedSource};
+use egui::Ui;
+use egui_extras::{Column, TableBuilder};
+
+/// Create out custom TabContent object for this specific tab, in this case it will be for our
+/// address list. The list itself is shared so the scanner can add to it while this is open
+///
+#[derive(Clone)]
+pub struct AddressListView {
+    pub target: SharedSource,
+    pub list: SharedAddressList,
+    pub freezer: SharedFreezer,
+    pub description: String,
+    pub module: String,
+    pub base_offset: String,
+    pub offsets: String,
+    pub value_type: ValueType,
+    /// Used for arrays of bytes and strings
+    pub length: String,
+    /// Where the list is saved to and loaded from
+    pub path: String,
+    /// Are values typed in hex numbers
+    pub is_hex: bool,
+    /// Row whose value is being edited, along with what's been typed in so far
+    pub editing: Option<(usize, String)>,
+    /// Row whose clamp bounds are being set, along with the bounds typed in so far
+    pub clamping: Option<(usize, String, String)>,
+    /// Row being looked at as a C struct
+    pub tree_entry: Option<usize>,
+    pub status: Option<String>,
+}
+
+impl AddressListView {
+    /// Create a view over an address list
+    ///
+    pub fn new(target: SharedSource, list: SharedAddressList, freezer: SharedFreezer) -> Self {
+        Self {
+            target,
+            list,
+            freezer,
+            description: String::new(),
+            module: String::new(),
+            base_offset: String::new(),
+            offsets: String::new(),
+            value_type: ValueType::default(),
+            length: "10".to_string(),
+            path: String::new(),
+            is_hex: false,
+            editing: None,
+            clamping: None,
+            tree_entry: None,
+            status: None,
+        }
+    }
+    /// Build a chain out of the inputs, offsets are comma separated and can be negative
+    ///
+    fn parse_chain(&self) -> anyhow::Result<PointerChain> {
+        let module = Some(self.module.trim()).filter(|module| !module.is_empty());
+        let base_offset = match self.base_offset.trim() {
+            "" => 0,
+            base_offset => evaluate_constant(base_offset)?,
+        };
+        let offsets = self
+            .offsets
+            .split(',')
+            .filter(|offset| !offset.trim().is_empty())
+            .map(|offset| Ok(evaluate_constant(offset)? as i64))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(PointerChain::new(module, base_offset, offsets))
+    }
+    /// Build an entry out of the inputs
+    ///
+    fn parse_entry(&self) -> anyhow::Result<AddressEntry> {
+        Ok(AddressEntry {
+            description: self.description.clone(),
+            chain: self.parse_chain()?,
+            value_type: self.value_type.clone(),
+            length: evaluate_constant(&self.length)? as usize,
+            frozen: false,
+            freeze: FreezeMode::default(),
+        })
+    }
+    /// Resolve an entry, handing back the address and value or why it couldn't be resolved
+    ///
+    fn resolve_entry(&self, entry: &AddressEntry) -> (String, String) {
+        match entry.read(&mut *lock_source(&self.target)) {
+            Ok((address, bytes)) => (
+                format!("0x{:X}", address),
+                extract_value(&bytes, &entry.value_type),
+            ),
+            Err(e) => (e.to_string(), "??".to_string()),
+        }
+    }
+    /// Start setting the clamp bounds of an entry, filled in with whatever they're set to now
+    ///
+    fn start_clamping(&mut self, idx: usize, entry: &AddressEntry) {
+        let (min, max) = match &entry.freeze {
+            FreezeMode::Clamp { min, max } => (
+                extract_value(min, &entry.value_type),
+                extract_value(max, &entry.value_type),
+            ),
+            FreezeMode::Value(_) => (String::new(), String::new()),
+        };
+        self.clamping = Some((idx, min, max));
+    }
+    /// Where to save to or load from, falling back onto the target's name with the extension
+    /// given
+    ///
+    fn list_path(&self, extension: &str) -> String {
+        match self.path.trim() {
+            "" => format!("{}.{}", lock_source(&self.target).name(), extension),
+            path => path.to_string(),
+        }
+    }
+}
+
+/// Form abstract link to TabContent
+///
+impl TabContent for AddressListView {
+    fn ui(&mut self, ui: &mut Ui) {
+        // Values are live so keep refreshing even when nothing's going on
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(250));
+
+        egui::Frame::none()
+            .fill(DARK_THEME.background_dark)
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                // Saving and loading the whole list
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.path)
+                            .desired_width(220.0)
+                            .hint_text("Path..."),
+                    );
+                    if ui.button("Save").clicked() {
+                        let path = self.list_path(ADDRESS_LIST_EXTENSION);
+                        self.status = Some(match lock_address_list(&self.list).save(&path) {
+                            Ok(()) => format!("Saved address list to {path}"),
+                            Err(e) => format!("Failed to save address list: {e}"),
+                        });
+                    }
+                    if ui.button("Load").clicked() {
+                        let path = self.list_path(ADDRESS_LIST_EXTENSION);
+                        self.status = Some(match AddressList::load(&path) {
+                            Ok(list) => {
+                                *lock_address_list(&self.list) = list;
+                                self.editing = None;
+                                self.clamping = None;
+                                self.tree_entry = None;
+                                format!("Loaded address list from {path}")
+                            }
+                            Err(e) => format!("Failed to load address list: {e}"),
+                        });
+                    }
+                    // Cheat Engine tables are added onto the list rather than replacing it
+                    if ui.button("Import .CT").clicked() {
+                        let path = self.list_path(CHEAT_TABLE_EXTENSION);
+                        self.status = Some(match import_cheat_table(&path) {
+                            Ok(import) => {
+                                let imported = import.entries.len();
+                                lock_address_list(&self.list).entries.extend(import.entries);
+                                match import.skipped.is_empty() {
+                                    true => format!("Imported {imported} entries from {path}"),
+                                    false => format!(
+                                        "Imported {imported} entries from {path}, skipped {}: {}",
+                                        import.skipped.len(),
+                                        import.skipped.join(", ")
+                                    ),
+                                }
+                            }
+                            Err(e) => format!("Failed to import cheat table: {e}"),
+                        });
+                    }
+                    if ui.button("Export .CT").clicked() {
+                        let path = self.list_path(CHEAT_TABLE_EXTENSION);
+                        self.status = Some(
+                            match export_cheat_table(&lock_address_list(&self.list), &path) {
+                                Ok(()) => format!("Exported address list to {path}"),
+                                Err(e) => format!("Failed to export cheat table: {e}"),
+                            },
+                        );
+                    }
+                    ui.checkbox(&mut self.is_hex, "Hex");
+                    ui.separator();
+
+                    // Freezing as a whole, entries are frozen or unfrozen on their own rows
+                    let mut interval_ms = self.freezer.interval_ms();
+                    ui.label("Freeze every");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut interval_ms)
+                                .range(1..=10_000)
+                                .suffix(" ms"),
+                        )
+                        .changed()
+                    {
+                        self.freezer.set_interval_ms(interval_ms);
+                    }
+                    let mut paused = self.freezer.is_paused();
+                    if ui.checkbox(&mut paused, "Pause freezing").changed() {
+                        self.freezer.set_paused(paused);
+                    }
+                    if self.freezer.is_stopped() {
+                        ui.label(
+                            egui::RichText::new("Freezing stopped").color(DARK_THEME.highlight),
+                        );
+                    }
+                });
+
+                // Inputs for adding a new entry
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.description)
+                            .desired_width(120.0)
+                            .hint_text("Description"),
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.module)
+                            .desired_width(120.0)
+                            .hint_text("Module"),
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.base_offset)
+                            .desired_width(100.0)
+                            .hint_text("Base offset"),
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.offsets)
+                            .desired_width(160.0)
+                            .hint_text("Offsets, e.g. 10, 8, -4"),
+                    );
+                    egui::ComboBox::from_id_salt("address_list_value_type")
+                        .selected_text(self.value_type.label())
+                        .show_ui(ui, |ui| {
+                            for value_type in ValueType::all()
+                                .into_iter()
+                                .filter(|value_type| !value_type.is_group())
+                            {
+                                let label = value_type.label().to_string();
+                                ui.selectable_value(&mut self.value_type, value_type, label);
+                            }
+                        });
+                    if self.value_type.is_pattern() {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.length)
+                                .desired_width(50.0)
+                                .hint_text("Length"),
+                        );
+                    }
+                    if ui.button("Add").clicked() {
+                        match self.parse_entry() {
+                            Ok(entry) => {
+                                lock_address_list(&self.list).entries.push(entry);
+                                self.status = None;
+                            }
+                            Err(e) => self.status = Some(e.to_string()),
+                        }
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.label(egui::RichText::new(status).color(DARK_THEME.highlight));
+                }
+
+                // Bounds for whichever entry is being clamped, the value is only written back
+                // when it falls outside of them
+                let mut clamp = None;
+                let mut close_clamp = false;
+                if let Some((idx, min, max)) = &mut self.clamping {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Clamp entry {idx} between"));
+                        ui.add(
+                            egui::TextEdit::singleline(min)
+                                .desired_width(100.0)
+                                .hint_text("Min"),
+                        );
+                        ui.label("and");
+                        ui.add(
+                            egui::TextEdit::singleline(max)
+                                .desired_width(100.0)
+                                .hint_text("Max"),
+                        );
+                        if ui.button("Apply").clicked() {
+                            clamp = Some((*idx, min.clone(), max.clone()));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            close_clamp = true;
+                        }
+                    });
+                }
+                if close_clamp {
+                    self.clamping = None;
+                }
+
+                // Whichever entry is being looked at as a C struct, expanded field by field
+                let mut close_tree = false;
+                if let Some(idx) = self.tree_entry {
+                    let entry = lock_address_list(&self.list).entries.get(idx).cloned();
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Entry {idx} as a struct"));
+                        if ui.button("Close").clicked() {
+                            close_tree = true;
+                        }
+                    });
+                    if let Some(entry) = entry {
+                        let library = type_library();
+                        let mut source = lock_source(&self.target);
+                        let name = match entry.description.as_str() {
+                            "" => entry.value_type.label(),
+                            description => description,
+                        };
+                        match (
+                            library.resolve(entry.value_type.label()),
+                            entry.chain.resolve(&mut *source),
+                        ) {
+                            (Some(ctype), Ok(address)) => {
+                                egui::ScrollArea::vertical()
+                                    .id_salt("address_list_tree")
+                                    .max_height(250.0)
+                                    .show(ui, |ui| {
+                                        c_type_tree(
+                                            ui,
+                                            &mut *source,
+                                            &library,
+                                            name,
+                                            &ctype,
+                                            address,
+                                        );
+                                    });
+                            }
+                            (None, _) => {
+                                ui.label(format!(
+                                    "{} is not a loaded C type",
+                                    entry.value_type.label()
+                                ));
+                            }
+                            (_, Err(e)) => {
+                                ui.label(e.to_string());
+                            }
+                        }
+                    }
+                }
+                if close_tree {
+                    self.tree_entry = None;
+                }
+                ui.separator();
+
+                // Resolve every entry before we start drawing so we aren't holding the target
+                // while the table is built
+                let list = self.list.clone();
+                let mut list = lock_address_list(&list);
+                let resolved: Vec<(String, String)> = list
+                    .entries
+                    .iter()
+                    .map(|entry| self.resolve_entry(entry))
+                    .collect();
+                let mut to_remove = None;
+                let mut to_write = None;
+                let mut to_freeze = None;
+                let mut to_clamp = None;
+                let mut to_tree = None;
+                let library = type_library();
+                let column_width = (ui.available_width() - 280.0) / 5.0;
+
+                TableBuilder::new(ui)
+                    .columns(Column::exact(column_width), 5)
+                    .column(Column::exact(150.0))
+                    .column(Column::exact(130.0))
+                    .header(20.0, |mut header| {
+                        for title in [
+                            "Description",
+                            "Chain",
+                            "Address",
+                            "Type",
+                            "Value",
+                            "Freeze",
+                            "",
+                        ] {
+                            header.col(|ui| {
+                                ui.vertical_centered(|ui| {
+                                    ui.heading(title);
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(30.0, list.entries.len(), |mut row| {
+                            let row_index = row.index();
+                            let (Some(entry), Some((address, value))) =
+                                (list.entries.get_mut(row_index), resolved.get(row_index))
+                            else {
+                                return;
+                            };
+
+                            row.col(|ui| {
+                                ui.text_edit_singleline(&mut entry.description);
+                            });
+                            for column in [
+                                entry.chain.to_string(),
+                                address.clone(),
+                                entry.value_type.label().to_string(),
+                            ] {
+                                row.col(|ui| {
+                                    ui.centered_and_justified(|ui| {
+                                        ui.label(column);
+                                    });
+                                });
+                            }
+                            // Values are edited in place, click to start and enter to write
+                            row.col(|ui| match &mut self.editing {
+                                Some((editing, text)) if *editing == row_index => {
+                                    let response = ui.text_edit_singleline(text);
+                                    if response.lost_focus() {
+                                        if ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                                            to_write = Some((row_index, text.clone()));
+                                        }
+                                        self.editing = None;
+                                    } else {
+                                        response.request_focus();
+                                    }
+                                }
+                                _ => {
+                                    let label = ui.add(
+                                        egui::Label::new(value.as_str())
+                                            .sense(egui::Sense::click()),
+                                    );
+                                    if label.clicked() {
+                                        self.editing = Some((row_index, value.clone()));
+                                    }
+                                }
+                            });
+                            // Numbers can be clamped as well as frozen at a value
+                            row.col(|ui| {
+                                ui.horizontal(|ui| {
+                                    let mut frozen = entry.frozen;
+                                    let label = match entry.freeze {
+                                        FreezeMode::Value(_) => "Value",
+                                        FreezeMode::Clamp { .. } => "Clamped",
+                                    };
+                                    if ui.checkbox(&mut frozen, label).changed() {
+                                        to_freeze = Some((row_index, frozen));
+                                    }
+                                    if entry.value_type.is_ordered()
+                                        && ui.small_button("Clamp").clicked()
+                                    {
+                                        to_clamp = Some(row_index);
+                                    }
+                                });
+                            });
+                            row.col(|ui| {
+                                ui.horizontal(|ui| {
+                                    if ui.button("Remove").clicked() {
+                                        to_remove = Some(row_index);
+                                    }
+                                    // Loaded C types can be expanded field by field
+                                    if library.resolve(entry.value_type.label()).is_some()
+                                        && ui.small_button("Tree").clicked()
+                                    {
+                                        to_tree = Some(row_index);
+                                    }
+                                });
+                            });
+                        });
+                    });
+
+                if let Some((idx, text)) = to_write {
+                    let written = list.entries[idx].write(
+                        &mut *lock_source(&self.target),
+                        &text,
+                        self.is_hex,
+                    );
+                    if let Err(e) = written {
+                        self.status = Some(format!("Failed to write value: {e}"));
+                    }
+                }
+                if let Some((idx, frozen)) = to_freeze {
+                    let entry = &mut list.entries[idx];
+                    // Unfreezing a clamped entry goes back to freezing at a value next time
+                    if !frozen {
+                        entry.freeze = FreezeMode::default();
+                    }
+                    if let Err(e) = entry.set_frozen(&mut *lock_source(&self.target), frozen) {
+                        self.status = Some(format!("Failed to freeze value: {e}"));
+                    }
+                }
+                if let Some(idx) = to_clamp {
+                    let entry = list.entries[idx].clone();
+                    self.start_clamping(idx, &entry);
+                }
+                if let Some((idx, min, max)) = clamp {
+                    if let Some(entry) = list.entries.get_mut(idx) {
+                        match (
+                            entry.parse(&min, self.is_hex),
+                            entry.parse(&max, self.is_hex),
+                        ) {
+                            (Ok(min), Ok(max)) => {
+                                entry.freeze = FreezeMode::Clamp { min, max };
+                                entry.frozen = true;
+                                self.clamping = None;
+                            }
+                            (Err(e), _) | (_, Err(e)) => {
+                                self.status = Some(format!("Invalid clamp bounds: {e}"));
+                            }
+                        }
+                    }
+                }
+                if let Some(idx) = to_tree {
+                    self.tree_entry = Some(idx);
+                }
+                if let Some(idx) = to_remove {
+                    list.entries.remove(idx);
+                    self.editing = None;
+                    self.clamping = None;
+                    self.tree_entry = None;
+                }
+            });
+    }
+
+    // Handle our name of the tab
+    //
+    fn title(&self) -> String {
+        "[>] Address list".to_string()
+    }
+}
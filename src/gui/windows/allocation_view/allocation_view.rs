@@ -2,9 +2,9 @@ use super::AllocationEnum;
 use super::{format_protection, format_state, format_type};
 use crate::gui::gui::TabContent;
 use crate::gui::main::DARK_THEME;
+use crate::memory::process::types::MemoryRegion;
 use egui::Ui;
 use egui_extras::{Column, TableBuilder};
-use windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION;
 
 /// Create out custom TabContent object for this specific tab, in this case it will be for our
 /// allocation view
@@ -12,8 +12,8 @@ use windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION;
 #[derive(Clone)]
 pub struct AllocationView {
     pub selected_allocation_enum: Option<AllocationEnum>,
-    pub selected_allocation: Option<MEMORY_BASIC_INFORMATION>,
-    pub allocations: Vec<MEMORY_BASIC_INFORMATION>,
+    pub selected_allocation: Option<MemoryRegion>,
+    pub allocations: Vec<MemoryRegion>,
 }
 
 /// Form abstract link to TabContent
@@ -88,15 +88,15 @@ impl TabContent for AllocationView {
                                         if let Some(allocation) = self.allocations.get(row_index) {
                                             // Create enum member and check if this is selected
                                             let alloc_enum =
-                                                AllocationEnum::Title(allocation.BaseAddress as _);
+                                                AllocationEnum::Title(allocation.base_address);
                                             let is_selected = Some(&alloc_enum)
                                                 == self.selected_allocation_enum.as_ref();
 
                                             // Format the address, size & protection
                                             let base_address =
-                                                format!("0x{:X}", allocation.BaseAddress as u64);
+                                                format!("0x{:X}", allocation.base_address);
                                             let region_size =
-                                                format!("0x{:X}", allocation.RegionSize as u32);
+                                                format!("0x{:X}", allocation.region_size);
 
                                             // For the address column
                                             row.col(|ui| {
@@ -127,7 +127,7 @@ impl TabContent for AllocationView {
                                                 ui.centered_and_justified(|ui| {
                                                     let label = ui.selectable_label(
                                                         is_selected,
-                                                        format_protection(allocation.Protect.0),
+                                                        format_protection(allocation.protect),
                                                     );
                                                     if label.clicked() {
                                                         selected_alloc_idx = Some(row_index);
@@ -140,7 +140,7 @@ impl TabContent for AllocationView {
                                                 ui.centered_and_justified(|ui| {
                                                     let label = ui.selectable_label(
                                                         is_selected,
-                                                        format_state(allocation.State.0),
+                                                        format_state(allocation.state),
                                                     );
                                                     if label.clicked() {
                                                         selected_alloc_idx = Some(row_index);
@@ -153,7 +153,7 @@ impl TabContent for AllocationView {
                                                 ui.centered_and_justified(|ui| {
                                                     let label = ui.selectable_label(
                                                         is_selected,
-                                                        format_type(allocation.Type.0),
+                                                        format_type(allocation.region_type),
                                                     );
                                                     if label.clicked() {
                                                         selected_alloc_idx = Some(row_index);
@@ -166,7 +166,7 @@ impl TabContent for AllocationView {
                                     if let Some(idx) = selected_alloc_idx {
                                         if let Some(allocation) = self.allocations.get(idx) {
                                             let alloc_enum =
-                                                AllocationEnum::Title(allocation.BaseAddress as _);
+                                                AllocationEnum::Title(allocation.base_address);
                                             self.selected_allocation_enum = Some(alloc_enum);
                                             self.selected_allocation = Some(*allocation);
                                         }
@@ -2,7 +2,8 @@ use super::{format_bytes_to_string, InstructionFormatterOutput};
 use crate::gui::gui::selectable_bp;
 use crate::gui::gui::TabContent;
 use crate::gui::main::DARK_THEME;
-use crate::process::Process;
+use crate::memory::expression::expression::evaluate_address;
+use crate::memory::source::source::{lock_source, SharedSource};
 use egui::{RichText, TextStyle, Ui};
 use iced_x86::IntelFormatter;
 use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction};
@@ -13,7 +14,8 @@ use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction};
 #[derive(Clone)]
 pub struct DisassemblyView {
     pub address_start: u64,
-    pub process: Process,
+    pub bitness: u32,
+    pub target: SharedSource,
     pub bytes: Vec<u8>,
     pub instructions: Vec<(u64, Instruction, String)>,
     pub bytes_read: usize,
@@ -25,14 +27,27 @@ impl DisassemblyView {
     fn refresh_disassembly(&mut self) -> anyhow::Result<()> {
         const READ_SIZE: usize = 512;
 
-        unsafe {
-            self.bytes = self
-                .process
-                .read_bytes_paged(self.address_start as usize, READ_SIZE)?;
-            self.bytes_read = self.bytes.len();
+        let mut source = lock_source(&self.target);
+        let start = self.address_start;
+
+        // Blobs and images refuse reads running past their end, which would zero the whole page,
+        // so stop at the end of the region we're disassembling instead
+        let size = source
+            .regions()
+            .unwrap_or_default()
+            .iter()
+            .find(|region| {
+                start >= region.base_address && start - region.base_address < region.region_size
+            })
+            .map_or(READ_SIZE, |region| {
+                READ_SIZE.min((region.base_address + region.region_size - start) as usize)
+            });
 
-            self.disassemble_bytes();
-        }
+        self.bytes = source.read_bytes_paged(start as usize, size)?;
+        drop(source);
+        self.bytes_read = self.bytes.len();
+
+        self.disassemble_bytes();
 
         Ok(())
     }
@@ -45,13 +60,11 @@ impl DisassemblyView {
     }
 
     fn patch_bytes(&mut self, addr: u64, new_bytes: &[u8]) -> anyhow::Result<()> {
-        // First, write the bytes to the process
-        unsafe {
-            self.process.write_bytes(addr as usize, new_bytes)?;
+        // First, write the bytes to the target
+        lock_source(&self.target).write_bytes(addr as usize, new_bytes)?;
 
-            // Then refresh our view to show the updated bytes
-            self.refresh_disassembly()?;
-        }
+        // Then refresh our view to show the updated bytes
+        self.refresh_disassembly()?;
 
         Ok(())
     }
@@ -65,9 +78,9 @@ impl DisassemblyView {
             return;
         }
 
-        // Create a decoder for 64-bit code
+        // Create a decoder for whatever bitness the target runs as
         let mut decoder = Decoder::with_ip(
-            64,                   // 64-bit code
+            self.bitness,         // 16, 32 or 64-bit code
             &self.bytes,          // Code buffer
             self.address_start,   // IP (instruction pointer)
             DecoderOptions::NONE, // No special options
@@ -128,6 +141,9 @@ impl TabContent for DisassemblyView {
             }
         }
 
+        // Read only targets like dumps can't be patched so we won't offer to edit their bytes
+        let read_only = lock_source(&self.target).is_read_only();
+
         egui::Frame::none()
             .fill(DARK_THEME.background_dark)
             .inner_margin(10.0)
@@ -148,9 +164,10 @@ impl TabContent for DisassemblyView {
                     if ui.text_edit_singleline(addr_input).lost_focus()
                         && ui.input(|i| i.key_pressed(egui::Key::Enter))
                     {
-                        if let Ok(addr) =
-                            u64::from_str_radix(addr_input.trim_start_matches("0x"), 16)
-                        {
+                        // Addresses can be any expression, such as module+offset or pointer
+                        // dereferences, resolved against the target
+                        let address = evaluate_address(addr_input, &mut *lock_source(&self.target));
+                        if let Ok(addr) = address {
                             self.address_start = addr;
                             let _ = self.refresh_disassembly();
                         }
@@ -189,9 +206,13 @@ impl TabContent for DisassemblyView {
 
                             // Display address
                             ui.label(
-                                RichText::new(format!("{:016X}", addr))
-                                    .color(DARK_THEME.primary)
-                                    .text_style(TextStyle::Monospace),
+                                RichText::new(format!(
+                                    "{:0width$X}",
+                                    addr,
+                                    width = if self.bitness == 64 { 16 } else { 8 }
+                                ))
+                                .color(DARK_THEME.primary)
+                                .text_style(TextStyle::Monospace),
                             );
 
                             // Get bytes for this instruction
@@ -239,6 +260,7 @@ impl TabContent for DisassemblyView {
                                             .text_style(TextStyle::Monospace),
                                     ))
                                     .double_clicked()
+                                    && !read_only
                                 {
                                     // If double clicked, we set this one to be the selected object
                                     *selected_addr = Some(*addr);
@@ -265,3 +287,54 @@ impl TabContent for DisassemblyView {
         return format!("[>] Disassembly ({:X})", self.address_start);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::process::types::PAGE_EXECUTE_READ;
+    use crate::memory::source::mock::MockTarget;
+    use crate::memory::source::source::share_source;
+
+    fn view(target: MockTarget, address_start: u64) -> DisassemblyView {
+        DisassemblyView {
+            address_start,
+            bitness: 64,
+            target: share_source(target),
+            bytes: Vec::new(),
+            instructions: Vec::new(),
+            bytes_read: 0,
+        }
+    }
+
+    #[test]
+    fn refresh_stops_at_the_end_of_the_region() {
+        // mov eax, 1 / ret, with nothing mapped after the region
+        let mut code = vec![0x90; 0x20];
+        code[0x10..0x16].copy_from_slice(&[0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3]);
+        let mut view = view(
+            MockTarget::new("game").with_region(0x1000, code.clone(), PAGE_EXECUTE_READ),
+            0x1010,
+        );
+
+        view.refresh_disassembly().unwrap();
+
+        assert_eq!(view.bytes, code[0x10..]);
+        assert_eq!(view.bytes_read, 0x10);
+        assert_eq!(view.instructions[0].0, 0x1010);
+        assert!(view.instructions[0].2.starts_with("mov"));
+        assert!(view.instructions[1].2.starts_with("ret"));
+    }
+
+    #[test]
+    fn refresh_reads_a_full_window_inside_large_regions() {
+        let mut view = view(
+            MockTarget::new("game").with_region(0x1000, vec![0x90; 0x1000], PAGE_EXECUTE_READ),
+            0x1000,
+        );
+
+        view.refresh_disassembly().unwrap();
+
+        assert_eq!(view.bytes_read, 512);
+        assert_eq!(view.instructions.len(), 512);
+    }
+}
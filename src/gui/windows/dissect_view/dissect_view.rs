@@ -0,0 +1,307 @@
+use crate::gui::gui::{c_type_tree, TabContent};
+use crate::gui::main::DARK_THEME;
+use crate::memory::ctypes::ctypes::type_library;
+use crate::memory::dissect::dissect::{Dissection, FieldKind, MemoryMap};
+use crate::memory::expression::expression::{evaluate_address, evaluate_constant};
+use crate::memory::scan::scannable::register_value_type;
+use crate::memory::scan::types::ValueType;
+use crate::memory::source::source::{lock_source, SharedSource};
+use egui::Ui;
+use std::sync::Arc;
+
+/// Something done to a field while the table was being drawn, applied once it's done. Fields are
+/// found by the path of expanded pointers leading to their structure and their index within it
+///
+#[derive(Clone, Debug)]
+enum FieldAction {
+    SetKind(Vec<usize>, usize, FieldKind),
+    Expand(Vec<usize>, usize),
+    Collapse(Vec<usize>, usize),
+}
+
+/// Create out custom TabContent object for this specific tab, in this case it will be for
+/// dissecting arbitrary memory into structures
+///
+#[derive(Clone)]
+pub struct DissectView {
+    pub target: SharedSource,
+    pub address: String,
+    /// Size in hex, expanded pointers are dissected with the same size
+    pub size: String,
+    pub struct_name: String,
+    /// C type picked to lay the address out with
+    pub c_type: String,
+    /// C type being shown along with the address it was resolved to
+    pub typed: Option<(String, u64)>,
+    pub map: MemoryMap,
+    pub dissection: Option<Dissection>,
+    pub status: Option<String>,
+}
+
+impl DissectView {
+    pub fn new(target: SharedSource) -> Self {
+        Self {
+            target,
+            address: String::new(),
+            size: "100".to_string(),
+            struct_name: String::new(),
+            c_type: String::new(),
+            typed: None,
+            map: MemoryMap::default(),
+            dissection: None,
+            status: None,
+        }
+    }
+    fn parse_size(&self) -> anyhow::Result<usize> {
+        Ok(evaluate_constant(&self.size)? as usize)
+    }
+    /// Read the address fresh and guess every field of it, anything overridden before is lost
+    ///
+    fn dissect(&mut self) -> anyhow::Result<()> {
+        let size = self.parse_size()?;
+        let mut source = lock_source(&self.target);
+        let address = evaluate_address(&self.address, &mut *source)?;
+
+        self.map = MemoryMap::read(&mut *source)?;
+        self.dissection = Some(Dissection::read(&mut *source, &self.map, address, size)?);
+        self.status = None;
+        Ok(())
+    }
+    /// Read the values again without touching the layout
+    ///
+    fn refresh(&mut self) -> anyhow::Result<()> {
+        let Some(dissection) = self.dissection.as_mut() else {
+            return Ok(());
+        };
+        let mut source = lock_source(&self.target);
+        self.map = MemoryMap::read(&mut *source)?;
+        dissection.refresh(&mut *source, &self.map)
+    }
+    /// Lay the address out as a loaded C type instead of guessing at it
+    ///
+    fn view_as_c_type(&mut self) -> anyhow::Result<()> {
+        if type_library().resolve(&self.c_type).is_none() {
+            return Err(anyhow::anyhow!(
+                "Pick a C type, load a header through the File menu"
+            ));
+        }
+        let address = evaluate_address(&self.address, &mut *lock_source(&self.target))?;
+        self.typed = Some((self.c_type.clone(), address));
+        Ok(())
+    }
+    /// Register the layout as a struct type, from then on it can be scanned for, added to the
+    /// address list and used as a field here like any other type
+    ///
+    fn save_struct(&self) -> anyhow::Result<String> {
+        let dissection = self
+            .dissection
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Nothing has been dissected yet"))?;
+        let name = self.struct_name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Name the struct first"));
+        }
+        if ValueType::ALL
+            .iter()
+            .any(|value_type| value_type.label() == name)
+        {
+            return Err(anyhow::anyhow!("{name} is already a built in type"));
+        }
+
+        register_value_type(Arc::new(dissection.to_struct_type(name)?));
+        Ok(name.to_string())
+    }
+    fn apply(&mut self, action: FieldAction) -> anyhow::Result<()> {
+        let size = self.parse_size()?;
+        let path = match &action {
+            FieldAction::SetKind(path, _, _)
+            | FieldAction::Expand(path, _)
+            | FieldAction::Collapse(path, _) => path.clone(),
+        };
+        let Some(dissection) = self
+            .dissection
+            .as_mut()
+            .and_then(|dissection| dissection.nested_mut(&path))
+        else {
+            return Ok(());
+        };
+
+        let mut source = lock_source(&self.target);
+        match action {
+            FieldAction::SetKind(_, index, kind) => {
+                dissection.set_kind(&mut *source, &self.map, index, kind)
+            }
+            FieldAction::Expand(_, index) => {
+                dissection.expand(&mut *source, &self.map, index, size)
+            }
+            FieldAction::Collapse(_, index) => {
+                dissection.fields[index].expanded = None;
+                Ok(())
+            }
+        }
+    }
+    /// Rows for every field of a structure, followed by the rows of whatever its pointers were
+    /// expanded into, indented under them
+    ///
+    fn fields_ui(
+        ui: &mut Ui,
+        dissection: &mut Dissection,
+        path: &mut Vec<usize>,
+        actions: &mut Vec<FieldAction>,
+    ) {
+        let indent = "    ".repeat(path.len());
+        let address = dissection.address;
+
+        for (idx, field) in dissection.fields.iter_mut().enumerate() {
+            ui.label(format!("{indent}+0x{:X}", field.offset));
+            ui.label(format!("0x{:X}", address + field.offset as u64));
+            ui.add(
+                egui::TextEdit::singleline(&mut field.name)
+                    .desired_width(120.0)
+                    .hint_text(format!("+0x{:X}", field.offset)),
+            );
+            egui::ComboBox::from_id_salt(("dissect_kind", path.clone(), idx))
+                .selected_text(field.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in FieldKind::all() {
+                        let selected = kind == field.kind;
+                        if ui.selectable_label(selected, kind.label()).clicked() && !selected {
+                            actions.push(FieldAction::SetKind(path.clone(), idx, kind));
+                        }
+                    }
+                });
+            ui.label(&field.value);
+            match (&field.expanded, field.kind.is_pointer()) {
+                (Some(_), _) => {
+                    if ui.button("Collapse").clicked() {
+                        actions.push(FieldAction::Collapse(path.clone(), idx));
+                    }
+                }
+                (None, true) => {
+                    if ui.button("Expand").clicked() {
+                        actions.push(FieldAction::Expand(path.clone(), idx));
+                    }
+                }
+                (None, false) => {
+                    ui.label("");
+                }
+            }
+            ui.end_row();
+
+            if let Some(nested) = field.expanded.as_mut() {
+                path.push(idx);
+                Self::fields_ui(ui, nested, path, actions);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// Form abstract link to TabContent
+///
+impl TabContent for DissectView {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Frame::none()
+            .fill(DARK_THEME.background_dark)
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.address)
+                            .desired_width(180.0)
+                            .hint_text("Address"),
+                    );
+                    ui.label("Size");
+                    ui.add(egui::TextEdit::singleline(&mut self.size).desired_width(60.0));
+                    if ui.button("Dissect").clicked() {
+                        if let Err(e) = self.dissect() {
+                            self.status = Some(e.to_string());
+                        }
+                    }
+                    if ui.button("Refresh").clicked() {
+                        if let Err(e) = self.refresh() {
+                            self.status = Some(e.to_string());
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.struct_name)
+                            .desired_width(180.0)
+                            .hint_text("Struct name"),
+                    );
+                    if ui.button("Save as Struct").clicked() {
+                        self.status = Some(match self.save_struct() {
+                            Ok(name) => format!("Registered {name}, it can now be scanned for"),
+                            Err(e) => format!("Failed to save struct: {e}"),
+                        });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("dissect_c_type")
+                        .selected_text(self.c_type.as_str())
+                        .show_ui(ui, |ui| {
+                            for name in type_library().names() {
+                                ui.selectable_value(&mut self.c_type, name.clone(), name);
+                            }
+                        });
+                    if ui.button("View as C type").clicked() {
+                        if let Err(e) = self.view_as_c_type() {
+                            self.status = Some(e.to_string());
+                        }
+                    }
+                    if self.typed.is_some() && ui.button("Close").clicked() {
+                        self.typed = None;
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.label(egui::RichText::new(status).color(DARK_THEME.text_muted));
+                }
+                ui.separator();
+
+                if let Some((name, address)) = &self.typed {
+                    let library = type_library();
+                    if let Some(ctype) = library.resolve(name) {
+                        egui::ScrollArea::vertical()
+                            .id_salt("dissect_c_tree")
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                let mut source = lock_source(&self.target);
+                                c_type_tree(ui, &mut *source, &library, name, &ctype, *address);
+                            });
+                        ui.separator();
+                    }
+                }
+
+                let Some(dissection) = self.dissection.as_mut() else {
+                    return;
+                };
+                let mut actions = Vec::new();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("dissect_fields")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["Offset", "Address", "Name", "Type", "Value", ""] {
+                                ui.label(title);
+                            }
+                            ui.end_row();
+
+                            Self::fields_ui(ui, dissection, &mut Vec::new(), &mut actions);
+                        });
+                });
+
+                for action in actions {
+                    if let Err(e) = self.apply(action) {
+                        self.status = Some(e.to_string());
+                    }
+                }
+            });
+    }
+
+    // Handle our name of the tab
+    //
+    fn title(&self) -> String {
+        "[>] Dissect".to_string()
+    }
+}
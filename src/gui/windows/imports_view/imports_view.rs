@@ -1,10 +1,10 @@
 use super::{FunctionEnum, ModuleEnum};
 use crate::gui::gui::TabContent;
 use crate::gui::main::DARK_THEME;
-use crate::iterators::module_iter::ModuleIterator;
-use crate::pe::pe::{get_imports, get_imports_descriptor_from_name, get_pe_from_path};
-use crate::process::Process;
-use crate::to_rstr;
+use crate::memory::source::source::{lock_source, SharedSource};
+use crate::pe::pe::{
+    get_iat_entry, get_imports, get_imports_descriptor_from_name, get_pe_from_path,
+};
 use egui::Ui;
 use exe::pe::VecPE;
 use exe::CCharString;
@@ -20,7 +20,7 @@ pub struct ImportsView {
     pub selected_function: Option<String>,
     pub process_path: Option<String>,
     pub pe_file: Option<VecPE>,
-    pub process: Process,
+    pub target: SharedSource,
 }
 
 /// Form abstract link to TabContent
@@ -157,66 +157,21 @@ impl TabContent for ImportsView {
                         bottom: 0.0,
                     }))
                     .show_inside(ui, |ui| {
-                        // This will get messy fast since we're going to have to work with some
-                        // extra parsing and actually reading it from the process itself.
+                        // Show the function and where it resolves to within the target itself
                         if let Some(func_name) = &self.selected_function {
                             ui.label(func_name);
                             ui.add_space(5.0);
 
-                            // Get import descriptor details about the current module and function
-                            // we're dealing with
+                            // Look up where the import lives within the IAT and what it's been
+                            // resolved to within the target
                             if let (Some(pe), Some(module)) = (&self.pe_file, &self.selected_module)
                             {
-                                if let Ok(descriptor) =
-                                    get_imports_descriptor_from_name(pe.clone(), module.clone())
+                                let mut source = lock_source(&self.target);
+                                if let Ok((iat_entry_address, func_address)) =
+                                    get_iat_entry(&mut *source, pe, module, func_name)
                                 {
-                                    // Get the index of the current imported function
-                                    match descriptor.get_imports(pe) {
-                                        Ok(imports) => {
-                                            if let Some(idx) = imports.iter().position(|import| {
-                                                let import_name = match import {
-                                                    exe::ImportData::Ordinal(ord) => {
-                                                        ord.to_string()
-                                                    }
-                                                    exe::ImportData::ImportByName(name) => {
-                                                        name.to_string()
-                                                    }
-                                                };
-                                                import_name == *func_name
-                                            }) {
-                                                // Get thunks
-                                                if let Ok(thunks) = descriptor.get_first_thunk(pe) {
-                                                    if idx < thunks.len() {
-                                                        let process_base =
-                                                            unsafe { self.process.base().unwrap() };
-
-                                                        let iat_entry_address = process_base
-                                                            + descriptor.first_thunk.0 as u64
-                                                            + (idx as u64 * 8);
-
-                                                        match unsafe {
-                                                            self.process.read::<u64>(
-                                                                iat_entry_address as usize,
-                                                            )
-                                                        } {
-                                                            Ok(func_address) => {
-                                                                ui.label(format!(
-                                                                    "IAT Entry: 0x{:X}",
-                                                                    iat_entry_address
-                                                                ));
-                                                                ui.label(format!(
-                                                                    "Function address: 0x{:X}",
-                                                                    func_address
-                                                                ));
-                                                            }
-                                                            _ => {}
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        _ => {}
-                                    }
+                                    ui.label(format!("IAT Entry: 0x{:X}", iat_entry_address));
+                                    ui.label(format!("Function address: 0x{:X}", func_address));
                                 }
                             }
                         }
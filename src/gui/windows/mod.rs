@@ -38,10 +38,15 @@ impl<T: TabContent> ActiveWindows<T> {
     }
 }
 
+pub mod address_list_view;
 pub mod allocation_view;
 pub mod disassembly_view;
+pub mod dissect_view;
 pub mod function_view;
 pub mod graph_view;
 pub mod imports_view;
 pub mod module_view;
+pub mod pointer_scan_view;
 pub mod scanner_view;
+pub mod section_view;
+pub mod thread_view;
@@ -1,18 +1,17 @@
 use super::ModuleEnum;
 use crate::gui::gui::TabContent;
 use crate::gui::main::DARK_THEME;
-use crate::to_rstr;
+use crate::memory::process::types::ModuleInfo;
 use egui::Ui;
-use windows::Win32::System::Diagnostics::ToolHelp::MODULEENTRY32;
 
 // Create out custom TabContent object for this specific tab, in this case it will be for our
 // module view
 //
 #[derive(Clone)]
 pub struct ModuleView {
-    pub modules: Vec<MODULEENTRY32>,
+    pub modules: Vec<ModuleInfo>,
     pub selected_module_enum: Option<ModuleEnum>,
-    pub selected_module: Option<MODULEENTRY32>,
+    pub selected_module: Option<ModuleInfo>,
 }
 
 // Form abstract link to TabContent
@@ -36,7 +35,7 @@ impl TabContent for ModuleView {
                             ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
                                 for module in &self.modules {
                                     // Create an enum member of all modules
-                                    let module_enum = ModuleEnum::Title(to_rstr!(module.szModule));
+                                    let module_enum = ModuleEnum::Title(module.name.clone());
                                     // Check if it was selected
                                     let is_selected =
                                         self.selected_module_enum.as_ref() == Some(&module_enum);
@@ -44,16 +43,13 @@ impl TabContent for ModuleView {
                                     // The actual selectable button in question...
                                     let button = ui.add_sized(
                                         [ui.available_width(), 20.0],
-                                        egui::SelectableLabel::new(
-                                            is_selected,
-                                            to_rstr!(module.szModule),
-                                        ),
+                                        egui::SelectableLabel::new(is_selected, &module.name),
                                     );
 
                                     // If it was clicked then store it
                                     if button.clicked() {
                                         self.selected_module_enum = Some(module_enum);
-                                        self.selected_module = Some(*module);
+                                        self.selected_module = Some(module.clone());
                                     }
                                 }
                             });
@@ -73,10 +69,10 @@ impl TabContent for ModuleView {
                         // module for the right panel to display information about it
                         egui::ScrollArea::vertical().show(ui, |ui| {
                             ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                                if let Some(module) = self.selected_module {
-                                    ui.label(format!("Path : {}", to_rstr!(module.szExePath)));
-                                    ui.label(format!("Size : {:X}", module.modBaseSize));
-                                    ui.label(format!("Base : {:X}", module.modBaseAddr as usize));
+                                if let Some(module) = &self.selected_module {
+                                    ui.label(format!("Path : {}", module.path));
+                                    ui.label(format!("Size : {:X}", module.size));
+                                    ui.label(format!("Base : {:X}", module.base_address));
                                 }
                             });
                         });
@@ -0,0 +1 @@
+pub mod pointer_scan_view;
@@ -0,0 +1,265 @@
+use crate::gui::gui::TabContent;
+use crate::gui::main::DARK_THEME;
+use crate::memory::expression::expression::{evaluate_address, evaluate_constant};
+use crate::memory::pointer::pointer::PointerChain;
+use crate::memory::pointer::pointer_scan::{
+    intersect_chains, PointerMap, PointerScan, PointerScanOptions, POINTER_MAP_EXTENSION,
+};
+use crate::memory::scan::scan_job::ScanJob;
+use crate::memory::source::source::{lock_source, SharedSource};
+use egui::Ui;
+use egui_extras::{Column, TableBuilder};
+use std::sync::Arc;
+
+/// Create out custom TabContent object for this specific tab, in this case it will be for our
+/// pointer scanner
+///
+#[derive(Clone)]
+pub struct PointerScanView {
+    pub target: SharedSource,
+    pub address: String,
+    pub max_depth: String,
+    pub max_offset: String,
+    pub map_path: String,
+    pub map: Option<PointerMap>,
+    pub results: Vec<PointerChain>,
+    /// The scan currently running in the background
+    pub job: Option<Arc<ScanJob<PointerScan>>>,
+    /// Whether the running scan is of a stored map, its chains are intersected with ours
+    pub intersecting: bool,
+    pub status: Option<String>,
+}
+
+impl PointerScanView {
+    /// Create an empty pointer scanner, the limits start off at the scanner's defaults
+    ///
+    pub fn new(target: SharedSource) -> Self {
+        let options = PointerScanOptions::default();
+
+        Self {
+            target,
+            address: String::new(),
+            max_depth: options.max_depth.to_string(),
+            max_offset: format!("{:X}", options.max_offset),
+            map_path: String::new(),
+            map: None,
+            results: Vec::new(),
+            job: None,
+            intersecting: false,
+            status: None,
+        }
+    }
+    /// Pull the limits out of the inputs, depth is decimal while the offset is hex like every other
+    /// address input
+    ///
+    fn parse_options(&self) -> anyhow::Result<PointerScanOptions> {
+        Ok(PointerScanOptions {
+            max_depth: self.max_depth.trim().parse()?,
+            max_offset: evaluate_constant(&self.max_offset)?,
+            ..Default::default()
+        })
+    }
+    /// Build a fresh pointer map of the target and search it for the address, both happen in the
+    /// background
+    ///
+    fn scan(&mut self) -> anyhow::Result<()> {
+        let options = self.parse_options()?;
+        let address = evaluate_address(&self.address, &mut *lock_source(&self.target))?;
+
+        let job = ScanJob::pointer_scan(self.target.clone(), address, options)?;
+        self.job = Some(Arc::new(job));
+        self.intersecting = false;
+        self.status = None;
+        Ok(())
+    }
+    /// Store the map we last built so it can be compared against after a restart
+    ///
+    fn save_map(&mut self) -> anyhow::Result<()> {
+        let map = self
+            .map
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Nothing has been scanned yet"))?;
+        let path = match self.map_path.trim() {
+            "" => format!(
+                "{}.{}",
+                lock_source(&self.target).name(),
+                POINTER_MAP_EXTENSION
+            ),
+            path => path.to_string(),
+        };
+
+        map.save(&path)?;
+        self.status = Some(format!("Saved pointer map to {path}"));
+        Ok(())
+    }
+    /// Search a stored map the same way and only keep chains both scans agree on, a chain that
+    /// survives a restart is one that can be relied on
+    ///
+    fn intersect_map(&mut self) -> anyhow::Result<()> {
+        let options = self.parse_options()?;
+        let path = self.map_path.trim().to_string();
+
+        let job = ScanJob::stored_pointer_scan(self.target.clone(), path, options);
+        self.job = Some(Arc::new(job));
+        self.intersecting = true;
+        self.status = None;
+        Ok(())
+    }
+    /// Pick up the chains a running scan found once it's done
+    ///
+    fn poll_job(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.job.clone() else {
+            return;
+        };
+
+        // Checked before polling so nothing sent right before the worker stopped is missed
+        let finished = job.is_finished();
+        for scan in job.poll() {
+            self.status = Some(match scan {
+                Ok(scan) if self.intersecting => {
+                    let before = self.results.len();
+                    self.results = intersect_chains(&self.results, &scan.chains);
+                    format!("Kept {} of {} chains", self.results.len(), before)
+                }
+                Ok(scan) => {
+                    self.results = scan.chains;
+                    let status = format!(
+                        "Found {} chains within {} pointers",
+                        self.results.len(),
+                        scan.map.pointers.len()
+                    );
+                    self.map = Some(scan.map);
+                    status
+                }
+                Err(_) if job.progress.is_cancelled() => "Pointer scan cancelled".to_string(),
+                Err(e) => format!("Pointer scan failed: {e}"),
+            });
+        }
+
+        if !finished {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        }
+        self.job = None;
+    }
+    /// The target's address along with the limits and the map to save to or intersect with
+    ///
+    fn inputs_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.address)
+                    .desired_width(180.0)
+                    .hint_text("Address"),
+            );
+            ui.label("Depth");
+            ui.add(egui::TextEdit::singleline(&mut self.max_depth).desired_width(40.0));
+            ui.label("Max offset");
+            ui.add(egui::TextEdit::singleline(&mut self.max_offset).desired_width(80.0));
+            if ui.button("Scan").clicked() {
+                if let Err(e) = self.scan() {
+                    self.status = Some(e.to_string());
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.map_path)
+                    .desired_width(300.0)
+                    .hint_text("Pointer map path"),
+            );
+            if ui.button("Save map").clicked() {
+                if let Err(e) = self.save_map() {
+                    self.status = Some(e.to_string());
+                }
+            }
+            if ui.button("Intersect with map").clicked() {
+                if let Err(e) = self.intersect_map() {
+                    self.status = Some(e.to_string());
+                }
+            }
+        });
+    }
+    /// Follow a chain through the live target so it's obvious which still hold up
+    ///
+    fn resolve_chain(&self, chain: &PointerChain) -> String {
+        chain
+            .resolve(&mut *lock_source(&self.target))
+            .map(|address| format!("0x{:X}", address))
+            .unwrap_or_else(|e| e.to_string())
+    }
+}
+
+/// Form abstract link to TabContent
+///
+impl TabContent for PointerScanView {
+    fn ui(&mut self, ui: &mut Ui) {
+        self.poll_job(ui.ctx());
+
+        egui::Frame::none()
+            .fill(DARK_THEME.background_dark)
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                // while a scan is running show how far along it is, the inputs wait until it's
+                // done
+                if let Some(job) = self.job.clone() {
+                    let progress = &job.progress;
+                    ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} / {} MB, {} chains",
+                            progress.bytes_done() / (1024 * 1024),
+                            progress.total_bytes / (1024 * 1024),
+                            progress.hits()
+                        ));
+                        if ui.button("Cancel").clicked() {
+                            job.cancel();
+                        }
+                    });
+                }
+                ui.add_enabled_ui(self.job.is_none(), |ui| {
+                    self.inputs_ui(ui);
+                });
+                if let Some(status) = &self.status {
+                    ui.label(egui::RichText::new(status).color(DARK_THEME.text_muted));
+                }
+                ui.separator();
+
+                let column_width = ui.available_width() / 2.0;
+
+                TableBuilder::new(ui)
+                    .columns(Column::exact(column_width), 2)
+                    .header(20.0, |mut header| {
+                        for title in ["Chain", "Resolves to"] {
+                            header.col(|ui| {
+                                ui.vertical_centered(|ui| {
+                                    ui.heading(title);
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(30.0, self.results.len(), |mut row| {
+                            if let Some(chain) = self.results.get(row.index()) {
+                                let resolved = self.resolve_chain(chain);
+                                row.col(|ui| {
+                                    ui.centered_and_justified(|ui| {
+                                        ui.label(chain.to_string());
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.centered_and_justified(|ui| {
+                                        ui.label(resolved);
+                                    });
+                                });
+                            }
+                        });
+                    });
+            });
+    }
+
+    // Handle our name of the tab
+    //
+    fn title(&self) -> String {
+        "[>] Pointer scan".to_string()
+    }
+}
@@ -1,37 +1,48 @@
 use crate::gui::gui::TabContent;
 use crate::gui::main::DARK_THEME;
-use crate::process::Process;
+use crate::memory::address_list::address_list::{
+    lock_address_list, AddressEntry, SharedAddressList,
+};
+use crate::memory::expression::expression::{evaluate_address, evaluate_constant};
+use crate::memory::process::types::ModuleInfo;
+use crate::memory::scan::compare::extract_value;
+use crate::memory::scan::group::{GroupMember, ScanGroup};
+use crate::memory::scan::history::{
+    load_session, save_session, ScanParams, ScanStep, SCAN_SESSION_EXTENSION,
+};
+use crate::memory::scan::matcher::ScanMatcher;
+use crate::memory::scan::pattern::BytePattern;
+use crate::memory::scan::region_filter::{FlagFilter, RegionFilter};
+use crate::memory::scan::region_store::RegionStore;
+use crate::memory::scan::results::ScanResults;
+use crate::memory::scan::scan_job::{ScanJob, ScanProgress};
+use crate::memory::scan::scannable::{register_value_type, StructField, StructType};
+use crate::memory::scan::types::{FloatMatch, FloatRounding, ScanType, ValueType};
+use crate::memory::source::source::{lock_source, SharedSource};
 use egui::Ui;
 use egui_extras::{Column, TableBuilder};
+use std::sync::Arc;
 
-#[derive(Clone, Default, Debug, PartialEq, Eq)]
-pub enum ScanType {
-    #[default]
-    Exact,
-    BiggerThan,
-    SmallerThan,
-}
-
-// NOTE: could add a sub enum for int or float variants.
-#[derive(Clone, Default, Debug, PartialEq, Eq)]
-pub enum ValueType {
-    Byte,
-    TwoBytes,
-    #[default]
-    FourBytes,
-    EightBytes,
-}
+/// Once a stored scan has been narrowed down to this many values it's turned into a result list
+///
+pub const STORED_RESULT_LIMIT: usize = 100_000;
 
-#[derive(Clone, Default, Debug)]
-pub struct ScanResult {
-    pub address: u64,
+/// A value within a group as it's being typed in
+///
+#[derive(Clone, Debug)]
+pub struct GroupRow {
+    pub offset: String,
+    pub value_type: ValueType,
     pub value: String,
-    // could add other stuff here, like previous and first.
 }
 
-impl ScanResult {
-    pub fn new(address: u64, value: String) -> Self {
-        Self { address, value }
+impl Default for GroupRow {
+    fn default() -> Self {
+        Self {
+            offset: "0".to_string(),
+            value_type: ValueType::default(),
+            value: String::new(),
+        }
     }
 }
 
@@ -40,16 +51,90 @@ impl ScanResult {
 ///
 #[derive(Clone)]
 pub struct ScannerView {
-    pub process: Process,
+    pub target: SharedSource,
+    /// Results double clicked on are added here
+    pub address_list: SharedAddressList,
     /// Is search value a hex number.
     pub is_hex: bool,
     pub scan_type: ScanType,
     pub value_type: ValueType,
+    pub float_rounding: FloatRounding,
+    /// Used when floats are matched within an epsilon
+    pub epsilon: String,
+    /// Used when matching strings
+    pub case_insensitive: bool,
     /// Used to determine if it uses alignment or not.
     pub fast_scan: bool,
+    /// Which regions a first scan covers, the address range is kept as typed until a scan
+    pub region_filter: RegionFilter,
+    pub range_start: String,
+    pub range_end: String,
+    /// Modules the scan can be restricted to
+    pub modules: Vec<ModuleInfo>,
     pub value: String,
+    /// Upper bound of a between scan
+    pub value_upper: String,
+    /// Values making up a group scan
+    pub group_rows: Vec<GroupRow>,
+    /// Name the group's layout is registered as a type under
+    pub type_name: String,
+    /// Group the current results were found with, used to show them
+    pub group: Option<ScanGroup>,
     // NOTE: could also do some pubsub type deal.
-    pub results: Vec<ScanResult>,
+    pub results: ScanResults,
+    /// Held instead of results while an unknown initial value scan is still too broad to list
+    pub store: Option<Arc<RegionStore>>,
+    /// The scan currently running in the background
+    pub job: Option<Arc<ScanJob>>,
+    /// Same again for unknown initial value scans, these build up a store instead of results
+    pub store_job: Option<Arc<ScanJob<RegionStore>>>,
+    /// Whether the running scan is a next scan, the step before it is put back if it's cancelled
+    pub rollback: bool,
+    /// How the results being shown were found
+    pub params: Option<ScanParams>,
+    /// Every step before the one being shown, most recent last
+    pub history: Vec<ScanStep>,
+    /// Where scan sessions are saved to and loaded from
+    pub session_path: String,
+    pub status: Option<String>,
+}
+
+impl ScannerView {
+    /// Create a scanner with nothing scanned yet
+    ///
+    pub fn new(target: SharedSource, address_list: SharedAddressList) -> Self {
+        let modules = lock_source(&target).modules().unwrap_or_default();
+
+        Self {
+            target,
+            address_list,
+            is_hex: false,
+            scan_type: ScanType::default(),
+            value_type: ValueType::default(),
+            float_rounding: FloatRounding::default(),
+            epsilon: "0.01".to_string(),
+            case_insensitive: false,
+            fast_scan: true,
+            region_filter: RegionFilter::default(),
+            range_start: String::new(),
+            range_end: String::new(),
+            modules,
+            value: String::new(),
+            value_upper: String::new(),
+            group_rows: vec![GroupRow::default()],
+            type_name: String::new(),
+            group: None,
+            results: ScanResults::default(),
+            store: None,
+            job: None,
+            store_job: None,
+            rollback: false,
+            params: None,
+            history: Vec::new(),
+            session_path: String::new(),
+            status: None,
+        }
+    }
 }
 
 /// Form abstract link to TabContent
@@ -57,6 +142,9 @@ pub struct ScannerView {
 impl TabContent for ScannerView {
     /// Cursed ass code incoming not even gonna lie.
     fn ui(&mut self, ui: &mut Ui) {
+        self.poll_job(ui.ctx());
+        self.poll_store_job(ui.ctx());
+
         egui::Frame::none()
             .fill(DARK_THEME.background_dark)
             .inner_margin(10.0)
@@ -67,7 +155,7 @@ impl TabContent for ScannerView {
                 let total_width = ui.available_width();
                 let panel_width = total_width / 2.7;
                 // Left panel is going for be for actual found values, you can double click and it
-                // will be sent to scanner results.
+                // will be sent to the address list.
                 egui::SidePanel::left("scanner_panel_left")
                     .resizable(false)
                     .exact_width(panel_width)
@@ -75,19 +163,28 @@ impl TabContent for ScannerView {
                         egui::ScrollArea::vertical()
                             .id_salt("scanner_scroll")
                             .show(ui, |ui| {
+                                if let Some(store) = &self.store {
+                                    ui.label(format!(
+                                        "{} values stored, narrow them down with a next scan",
+                                        store.match_count()
+                                    ));
+                                }
+                                let mut to_add = None;
                                 ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                                    // Labels would otherwise swallow the clicks meant for the row
+                                    ui.style_mut().interaction.selectable_labels = false;
                                     TableBuilder::new(ui)
+                                        .sense(egui::Sense::click())
                                         .column(Column::exact(120.0).clip(false))
-                                        .column(Column::remainder().clip(true))
+                                        .columns(Column::remainder().clip(true), 3)
                                         .auto_shrink([false; 2])
                                         //.striped(true)
                                         .header(20.0, |mut header| {
-                                            header.col(|ui| {
-                                                ui.heading("Address");
-                                            });
-                                            header.col(|ui| {
-                                                ui.heading("Value");
-                                            });
+                                            for title in ["Address", "Value", "Previous", "First"] {
+                                                header.col(|ui| {
+                                                    ui.heading(title);
+                                                });
+                                            }
                                         })
                                         .body(|body| {
                                             // Since we're dealing with possibly a large amount of
@@ -96,17 +193,30 @@ impl TabContent for ScannerView {
                                             let row_height = 30.0;
                                             body.rows(row_height, self.results.len(), |mut row| {
                                                 let row_index = row.index();
+                                                // Only the rows being shown are ever formatted
                                                 if let Some(result) = self.results.get(row_index) {
                                                     row.col(|ui| {
                                                         ui.label(format!("0x{:X}", result.address));
                                                     });
-                                                    row.col(|ui| {
-                                                        ui.label(format!("{}", result.value));
-                                                    });
+                                                    for bytes in [
+                                                        result.bytes,
+                                                        result.previous,
+                                                        result.first,
+                                                    ] {
+                                                        row.col(|ui| {
+                                                            ui.label(self.format_value(bytes));
+                                                        });
+                                                    }
+                                                    if row.response().double_clicked() {
+                                                        to_add = Some(row_index);
+                                                    }
                                                 }
                                             });
                                         });
                                 });
+                                if let Some(idx) = to_add {
+                                    self.add_to_address_list(idx);
+                                }
                             });
                     });
                 // The last frame is going to just be for all the scan options and inputs etc..
@@ -119,70 +229,183 @@ impl TabContent for ScannerView {
                     }))
                     .show_inside(ui, |ui| {
                         ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                            // show the value text box and checkbox right next to it.
-                            ui.with_layout(egui::Layout::left_to_right(egui::Align::LEFT), |ui| {
-                                ui.text_edit_singleline(&mut self.value);
+                            // while a scan is running show how far along it is, everything else
+                            // waits until it's done
+                            if let Some(progress) = self.running() {
+                                ui.add(
+                                    egui::ProgressBar::new(progress.fraction()).show_percentage(),
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.label(format!(
+                                        "{} / {} MB, {} of {} chunks, {} hits",
+                                        progress.bytes_done() / (1024 * 1024),
+                                        progress.total_bytes / (1024 * 1024),
+                                        progress.chunks_done(),
+                                        progress.total_chunks,
+                                        progress.hits()
+                                    ));
+                                    if ui.button("Cancel").clicked() {
+                                        progress.cancel();
+                                    }
+                                });
+                                ui.add_space(7.0);
+                                ui.disable();
+                            }
+
+                            // show the value text box and checkbox right next to it, groups are
+                            // typed in a value at a time instead
+                            if self.value_type.is_group() {
+                                self.group_ui(ui);
                                 ui.checkbox(&mut self.is_hex, "Hex");
-                            });
+                            } else {
+                                ui.with_layout(
+                                    egui::Layout::left_to_right(egui::Align::LEFT),
+                                    |ui| {
+                                        if self.scan_type == ScanType::Between {
+                                            ui.add(
+                                                egui::TextEdit::singleline(&mut self.value)
+                                                    .desired_width(120.0),
+                                            );
+                                            ui.label("and");
+                                            ui.add(
+                                                egui::TextEdit::singleline(&mut self.value_upper)
+                                                    .desired_width(120.0),
+                                            );
+                                        } else {
+                                            ui.text_edit_singleline(&mut self.value);
+                                        }
+                                        ui.checkbox(&mut self.is_hex, "Hex");
+                                    },
+                                );
+                            }
 
                             ui.add_space(7.0);
 
                             // combo box for the user to decide the type of the scan.
+                            let scan_types = self.available_scan_types();
+                            if !scan_types.contains(&self.scan_type) {
+                                self.scan_type = ScanType::default();
+                            }
                             egui::ComboBox::from_label("Scan Type")
-                                .selected_text(format!("{:?}", self.scan_type))
+                                .selected_text(self.scan_type.label())
                                 .show_ui(ui, |ui| {
-                                    ui.selectable_value(
-                                        &mut self.scan_type,
-                                        ScanType::Exact,
-                                        "Exact",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.scan_type,
-                                        ScanType::BiggerThan,
-                                        "Bigger Than",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.scan_type,
-                                        ScanType::SmallerThan,
-                                        "Smaller Than",
-                                    );
+                                    for scan_type in scan_types {
+                                        let label = scan_type.label();
+                                        ui.selectable_value(&mut self.scan_type, scan_type, label);
+                                    }
                                 });
 
                             ui.add_space(3.0);
 
-                            // combo box for the user to decide the type of the value alignment.
-                            egui::ComboBox::from_label("Value Type")
-                                .selected_text(format!("{:?}", self.value_type))
-                                .show_ui(ui, |ui| {
-                                    ui.selectable_value(
-                                        &mut self.value_type,
-                                        ValueType::Byte,
-                                        "Byte",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.value_type,
-                                        ValueType::TwoBytes,
-                                        "Two Bytes",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.value_type,
-                                        ValueType::FourBytes,
-                                        "Four Bytes",
-                                    );
-                                    ui.selectable_value(
-                                        &mut self.value_type,
-                                        ValueType::EightBytes,
-                                        "Eight Bytes",
-                                    );
+                            // combo box for the user to decide the type of the value alignment, this
+                            // is fixed once there are results since they're compared as that type
+                            ui.add_enabled_ui(!self.has_scan(), |ui| {
+                                egui::ComboBox::from_label("Value Type")
+                                    .selected_text(self.value_type.label())
+                                    .show_ui(ui, |ui| {
+                                        for value_type in ValueType::all() {
+                                            let label = value_type.label().to_string();
+                                            ui.selectable_value(
+                                                &mut self.value_type,
+                                                value_type,
+                                                label,
+                                            );
+                                        }
+                                    });
+                            });
+
+                            // strings can be matched regardless of case
+                            if matches!(self.value_type, ValueType::Ascii | ValueType::Utf16) {
+                                ui.add_space(3.0);
+                                ui.checkbox(&mut self.case_insensitive, "Case insensitive");
+                            }
+
+                            // floats are rarely stored exactly as they're shown so let the user
+                            // pick how loosely they're matched
+                            if self.value_type.is_float() {
+                                ui.add_space(3.0);
+                                ui.horizontal(|ui| {
+                                    egui::ComboBox::from_label("Rounding")
+                                        .selected_text(format!("{:?}", self.float_rounding))
+                                        .show_ui(ui, |ui| {
+                                            for (float_rounding, label) in [
+                                                (FloatRounding::Rounded, "Rounded"),
+                                                (FloatRounding::Truncated, "Truncated"),
+                                                (FloatRounding::Epsilon, "Epsilon"),
+                                                (FloatRounding::Exact, "Exact"),
+                                            ] {
+                                                ui.selectable_value(
+                                                    &mut self.float_rounding,
+                                                    float_rounding,
+                                                    label,
+                                                );
+                                            }
+                                        });
+                                    if self.float_rounding == FloatRounding::Epsilon {
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut self.epsilon)
+                                                .desired_width(80.0),
+                                        );
+                                    }
                                 });
+                            }
 
                             ui.add_space(3.0);
                             ui.checkbox(&mut self.fast_scan, "Fast");
+                            ui.add_space(3.0);
+
+                            // narrow down which parts of the target a first scan covers
+                            ui.collapsing("Memory Regions", |ui| {
+                                self.region_filter_ui(ui);
+                            });
+                            ui.add_space(7.0);
+
+                            if !self.has_scan() {
+                                let scan_button = ui.button("First Scan");
+                                if scan_button.clicked() {
+                                    if let Err(e) = self.perform_scan() {
+                                        self.status = Some(e.to_string());
+                                    }
+                                }
+                            } else {
+                                ui.horizontal(|ui| {
+                                    let new_scan_button = ui.button("New Scan");
+                                    if new_scan_button.clicked() {
+                                        self.results = ScanResults::default();
+                                        self.store = None;
+                                        self.job = None;
+                                        self.store_job = None;
+                                        self.rollback = false;
+                                        self.params = None;
+                                        self.history.clear();
+                                        if self.scan_type.needs_previous() {
+                                            self.scan_type = ScanType::default();
+                                        }
+                                    }
+                                    let next_scan_button = ui.button("Next Scan");
+                                    if next_scan_button.clicked() {
+                                        if let Err(e) = self.perform_next_scan() {
+                                            self.status = Some(e.to_string());
+                                        }
+                                    }
+                                    let undo_button = ui.add_enabled(
+                                        !self.history.is_empty(),
+                                        egui::Button::new("Undo"),
+                                    );
+                                    if undo_button.clicked() {
+                                        self.undo();
+                                    }
+                                });
+                            }
                             ui.add_space(7.0);
 
-                            let scan_button = ui.button("Scan");
-                            if scan_button.clicked() {
-                                self.perform_scan()
+                            // every step taken so far, sessions can be saved and picked back up
+                            // after a restart
+                            ui.collapsing("History", |ui| {
+                                self.history_ui(ui);
+                            });
+                            if let Some(status) = &self.status {
+                                ui.label(egui::RichText::new(status).color(DARK_THEME.highlight));
                             }
                         });
                     });
@@ -197,75 +420,649 @@ impl TabContent for ScannerView {
 }
 
 impl ScannerView {
-    /// Performs a scan based on the current settings
-    fn perform_scan(&mut self) {
-        // Parse the input value based on whether it's hex or decimal
-        let parse_result = if self.is_hex {
-            // Remove '0x' prefix if present
-            let value_str = self.value.trim_start_matches("0x");
-            match self.value_type {
-                ValueType::Byte => u8::from_str_radix(value_str, 16).map(|v| v as u64),
-                ValueType::TwoBytes => u16::from_str_radix(value_str, 16).map(|v| v as u64),
-                ValueType::FourBytes => u32::from_str_radix(value_str, 16).map(|v| v as u64),
-                ValueType::EightBytes => u64::from_str_radix(value_str, 16),
+    /// Controls for which regions get scanned
+    ///
+    fn region_filter_ui(&mut self, ui: &mut Ui) {
+        let filter = &mut self.region_filter;
+
+        egui::Grid::new("scanner_region_flags").show(ui, |ui| {
+            for (label, flag) in [
+                ("Writable", &mut filter.writable),
+                ("Executable", &mut filter.executable),
+                ("Copy on write", &mut filter.copy_on_write),
+            ] {
+                ui.label(label);
+                egui::ComboBox::from_id_salt(label)
+                    .selected_text(flag.label())
+                    .show_ui(ui, |ui| {
+                        for option in FlagFilter::ALL {
+                            ui.selectable_value(flag, option, option.label());
+                        }
+                    });
+                ui.end_row();
             }
-        } else {
-            self.value.parse::<u64>()
-        };
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut filter.image, "Image");
+            ui.checkbox(&mut filter.private, "Private");
+            ui.checkbox(&mut filter.mapped, "Mapped");
+        });
+
+        let selected = filter
+            .module
+            .as_ref()
+            .map_or("Any module".to_string(), |module| module.name.clone());
+        egui::ComboBox::from_label("Module")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.module, None, "Any module");
+                for module in self.modules.iter() {
+                    ui.selectable_value(&mut filter.module, Some(module.clone()), &module.name);
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.label("Start");
+            ui.add(egui::TextEdit::singleline(&mut self.range_start).desired_width(120.0));
+            ui.label("End");
+            ui.add(egui::TextEdit::singleline(&mut self.range_end).desired_width(120.0));
+        });
+    }
+    /// Resolve the address range typed in and hand back the filter to scan with, either end of
+    /// the range can be left empty
+    ///
+    fn build_region_filter(&self) -> anyhow::Result<RegionFilter> {
+        let mut filter = self.region_filter.clone();
 
-        let value = match parse_result {
-            Ok(v) => v,
-            Err(_) => {
-                // Handle parse error - maybe show in UI
-                return;
+        for (text, bound) in [
+            (&self.range_start, &mut filter.start),
+            (&self.range_end, &mut filter.end),
+        ] {
+            if text.trim().is_empty() {
+                continue;
             }
+            *bound = evaluate_address(text, &mut *lock_source(&self.target))
+                .map_err(|e| anyhow::anyhow!("Invalid address '{text}': {e}"))?;
+        }
+
+        Ok(filter)
+    }
+    /// Add a result to the address list so it's kept around after the scan's gone
+    ///
+    fn add_to_address_list(&self, idx: usize) {
+        let Some(result) = self.results.get(idx) else {
+            return;
         };
 
-        // Perform the scan based on value type
-        let results = match self.value_type {
-            ValueType::Byte => unsafe {
-                self.process.find_data(
-                    self.scan_type.clone(),
-                    self.value_type.clone(),
-                    value as u8,
-                    self.fast_scan,
-                )
-            },
-            ValueType::TwoBytes => unsafe {
-                self.process.find_data(
-                    self.scan_type.clone(),
-                    self.value_type.clone(),
-                    value as u16,
-                    self.fast_scan,
-                )
-            },
-            ValueType::FourBytes => unsafe {
-                self.process.find_data(
-                    self.scan_type.clone(),
-                    self.value_type.clone(),
-                    value as u32,
-                    self.fast_scan,
-                )
-            },
-            ValueType::EightBytes => unsafe {
-                self.process.find_data(
-                    self.scan_type.clone(),
-                    self.value_type.clone(),
-                    value,
-                    self.fast_scan,
-                )
+        lock_address_list(&self.address_list)
+            .entries
+            .push(AddressEntry::at_address(
+                "No description",
+                result.address,
+                self.value_type.clone(),
+                self.results.width(),
+            ));
+    }
+    /// Controls for typing in the values of a group
+    ///
+    fn group_ui(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+
+        egui::Grid::new("scanner_group").show(ui, |ui| {
+            for title in ["Offset", "Type", "Value"] {
+                ui.label(title);
+            }
+            ui.end_row();
+
+            for (idx, row) in self.group_rows.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut row.offset).desired_width(60.0));
+                egui::ComboBox::from_id_salt(("scanner_group_type", idx))
+                    .selected_text(row.value_type.label())
+                    .show_ui(ui, |ui| {
+                        for value_type in ValueType::all()
+                            .into_iter()
+                            .filter(|value_type| value_type.scannable().is_some())
+                        {
+                            let label = value_type.label().to_string();
+                            ui.selectable_value(&mut row.value_type, value_type, label);
+                        }
+                    });
+                ui.add(egui::TextEdit::singleline(&mut row.value).desired_width(120.0));
+                if ui.button("Remove").clicked() {
+                    removed = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+
+        if let Some(idx) = removed {
+            self.group_rows.remove(idx);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Add Value").clicked() {
+                self.group_rows.push(GroupRow::default());
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut self.type_name)
+                    .desired_width(120.0)
+                    .hint_text("Type name"),
+            );
+            if ui.button("Save as Type").clicked() {
+                self.status = Some(match self.register_group_type() {
+                    Ok(name) => format!("Registered {name}, values are typed in comma separated"),
+                    Err(e) => format!("Failed to register type: {e}"),
+                });
+            }
+        });
+    }
+    /// Register the group's layout as a struct type, from then on it can be scanned for and added
+    /// to the address list like any other type. The values typed in are left out
+    ///
+    fn register_group_type(&self) -> anyhow::Result<String> {
+        let name = self.type_name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Name the type first"));
+        }
+        if ValueType::ALL
+            .iter()
+            .any(|value_type| value_type.label() == name)
+        {
+            return Err(anyhow::anyhow!("{name} is already a built in type"));
+        }
+
+        let fields = self
+            .group_rows
+            .iter()
+            .map(|row| {
+                let offset = evaluate_constant(row.offset.trim().trim_start_matches('+'))? as usize;
+                Ok(StructField {
+                    name: format!("+0x{:X}", offset),
+                    offset,
+                    value_type: row.value_type.clone(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        register_value_type(Arc::new(StructType::new(name, 0, fields)?));
+        Ok(name.to_string())
+    }
+    /// Turn a value into text the way its type should be shown, groups show each of their values
+    ///
+    fn format_value(&self, bytes: &[u8]) -> String {
+        match &self.group {
+            Some(group) if self.value_type.is_group() => group.format(bytes),
+            _ => extract_value(bytes, &self.value_type),
+        }
+    }
+    /// Parse a value typed in based on whether it's hex or decimal
+    ///
+    fn parse_value(&self, value_type: &ValueType, text: &str) -> anyhow::Result<Vec<u8>> {
+        value_type
+            .parse(text, self.is_hex)
+            .map_err(|e| anyhow::anyhow!("Invalid value '{text}': {e}"))
+    }
+    /// Parse the values of a group, offsets are hex like addresses are
+    ///
+    fn build_group(&self) -> anyhow::Result<ScanGroup> {
+        let mut members = Vec::new();
+
+        for row in self.group_rows.iter() {
+            let offset = evaluate_constant(row.offset.trim().trim_start_matches('+'))
+                .map_err(|e| anyhow::anyhow!("Invalid offset '{}': {e}", row.offset))?
+                as usize;
+            members.push(GroupMember {
+                offset,
+                value_type: row.value_type.clone(),
+                bytes: self.parse_value(&row.value_type, &row.value)?,
+                float_match: self.float_match(&row.value)?,
+            });
+        }
+
+        ScanGroup::new(members).map_err(|e| anyhow::anyhow!("Invalid group: {e}"))
+    }
+    /// Build up how floats are matched, rounding goes to as many decimal places as were typed in
+    ///
+    fn float_match(&self, text: &str) -> anyhow::Result<FloatMatch> {
+        let decimals = text
+            .trim()
+            .split_once('.')
+            .map_or(0, |(_, fraction)| fraction.len() as i32);
+
+        match self.float_rounding {
+            FloatRounding::Exact => Ok(FloatMatch::Exact),
+            FloatRounding::Rounded => Ok(FloatMatch::Rounded { decimals }),
+            FloatRounding::Truncated => Ok(FloatMatch::Truncated { decimals }),
+            FloatRounding::Epsilon => match self.epsilon.trim().parse::<f64>() {
+                Ok(epsilon) => Ok(FloatMatch::Epsilon(epsilon.abs())),
+                Err(e) => Err(anyhow::anyhow!("Invalid epsilon '{}': {e}", self.epsilon)),
             },
+        }
+    }
+    /// Whether there's an earlier scan to narrow down
+    ///
+    fn has_scan(&self) -> bool {
+        !self.results.is_empty() || self.store.is_some()
+    }
+    /// Scan types that make sense for the current value type, anything comparing against the last
+    /// scan only shows up once there's something to narrow down
+    ///
+    fn available_scan_types(&self) -> Vec<ScanType> {
+        match (self.value_type.is_ordered(), self.has_scan()) {
+            (false, false) => vec![ScanType::Exact],
+            (false, true) => vec![ScanType::Exact, ScanType::Changed, ScanType::Unchanged],
+            (true, false) => vec![
+                ScanType::Exact,
+                ScanType::BiggerThan,
+                ScanType::SmallerThan,
+                ScanType::Between,
+                ScanType::UnknownInitial,
+            ],
+            (true, true) => vec![
+                ScanType::Exact,
+                ScanType::BiggerThan,
+                ScanType::SmallerThan,
+                ScanType::Between,
+                ScanType::Changed,
+                ScanType::Unchanged,
+                ScanType::Increased,
+                ScanType::Decreased,
+                ScanType::IncreasedBy,
+                ScanType::DecreasedBy,
+            ],
+        }
+    }
+    /// Parse the input as a pattern, strings are taken exactly as typed
+    ///
+    fn parse_pattern(&self) -> anyhow::Result<BytePattern> {
+        BytePattern::parse(&self.value_type, &self.value, self.case_insensitive)
+            .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {e}", self.value))
+    }
+    /// Bundle the current settings up into what the workers compare memory against
+    ///
+    fn build_matcher(&self) -> anyhow::Result<ScanMatcher> {
+        // Patterns are only needed when matching exactly, changed and unchanged compare bytes
+        let pattern = if self.value_type.is_pattern() && self.scan_type == ScanType::Exact {
+            Some(self.parse_pattern()?)
+        } else {
+            None
+        };
+        let group = if self.value_type.is_group() && self.scan_type == ScanType::Exact {
+            Some(self.build_group()?)
+        } else {
+            None
+        };
+        // Changed, unchanged and the like don't need a value at all, between needs two
+        let value_bytes = match self.scan_type {
+            _ if self.value_type.is_pattern() || self.value_type.is_group() => {
+                vec![0u8; self.value_type.size()]
+            }
+            ScanType::Between => [
+                self.parse_value(&self.value_type, &self.value)?,
+                self.parse_value(&self.value_type, &self.value_upper)?,
+            ]
+            .concat(),
+            _ if self.scan_type.needs_value() => self.parse_value(&self.value_type, &self.value)?,
+            _ => vec![0u8; self.value_type.size()],
+        };
+
+        Ok(ScanMatcher {
+            scan_type: self.scan_type.clone(),
+            value_type: self.value_type.clone(),
+            value_bytes,
+            float_match: self.float_match(&self.value)?,
+            pattern,
+            group,
+        })
+    }
+    /// Performs a scan based on the current settings, the scan runs in the background and results
+    /// show up as they're found
+    fn perform_scan(&mut self) -> anyhow::Result<()> {
+        if self.scan_type == ScanType::UnknownInitial {
+            return self.perform_unknown_scan();
+        }
+
+        let matcher = self.build_matcher()?;
+        let filter = self.build_region_filter()?;
+
+        let group = matcher.group.clone();
+        let job = ScanJob::first_scan(self.target.clone(), matcher, self.fast_scan, &filter)
+            .map_err(|e| anyhow::anyhow!("Scan failed: {e}"))?;
+
+        self.group = group;
+        self.results = ScanResults::default();
+        self.store = None;
+        self.rollback = false;
+        self.params = Some(self.scan_params());
+        self.history.clear();
+        self.job = Some(Arc::new(job));
+        self.status = None;
+        Ok(())
+    }
+    /// Pick up whatever a running scan has found since last frame and tidy up once it's done
+    ///
+    fn poll_job(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.job.clone() else {
+            return;
+        };
+
+        // Checked before polling so nothing sent right before the workers stopped is missed
+        let finished = job.is_finished();
+        for batch in job.poll() {
+            match batch {
+                Ok(results) => self.results.append(results),
+                Err(e) => self.status = Some(format!("Scan failed: {e}")),
+            }
+        }
+
+        if !finished {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        }
+
+        self.job = None;
+        // A cancelled next scan would otherwise lose everything it hadn't got to yet
+        match std::mem::take(&mut self.rollback) && job.progress.is_cancelled() {
+            true => self.undo(),
+            false => self.results.sort(),
+        }
+        // Nothing left to narrow down, the next scan will be a first scan again
+        if self.results.is_empty() && self.scan_type.needs_previous() {
+            self.scan_type = ScanType::default();
+        }
+    }
+    /// Take a copy of the target to compare later scans against, nothing about the value is known
+    /// so there's no result list yet. The copy is taken in the background like any other scan
+    ///
+    fn perform_unknown_scan(&mut self) -> anyhow::Result<()> {
+        let filter = self.build_region_filter()?;
+        let job = ScanJob::capture(
+            self.target.clone(),
+            self.value_type.clone(),
+            self.fast_scan,
+            &filter,
+        )
+        .map_err(|e| anyhow::anyhow!("Unknown initial value scan failed: {e}"))?;
+
+        self.results = ScanResults::default();
+        self.store = None;
+        self.group = None;
+        self.rollback = false;
+        self.params = Some(self.scan_params());
+        self.history.clear();
+        // The only thing worth doing next is seeing how it's changed
+        self.scan_type = ScanType::Changed;
+        self.store_job = Some(Arc::new(job));
+        self.status = None;
+        Ok(())
+    }
+    /// Narrow a stored scan down in the background, the step before it is put back if it's
+    /// cancelled
+    ///
+    fn perform_stored_next_scan(
+        &mut self,
+        store: Arc<RegionStore>,
+        value_bytes: Vec<u8>,
+        float_match: FloatMatch,
+    ) {
+        self.push_step();
+        let job = ScanJob::stored_next_scan(
+            self.target.clone(),
+            store,
+            self.scan_type.clone(),
+            value_bytes,
+            float_match,
+        );
+        self.store_job = Some(Arc::new(job));
+        self.rollback = true;
+    }
+    /// Pick up the store a running unknown value scan built once it's done
+    ///
+    fn poll_store_job(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.store_job.clone() else {
+            return;
         };
 
-        // Update results
-        match results {
-            Ok(scan_results) => {
-                self.results = scan_results;
+        // Checked before polling so nothing sent right before the worker stopped is missed
+        let finished = job.is_finished();
+        let mut failed = false;
+        for store in job.poll() {
+            match store {
+                Ok(store) => self.show_store(store),
+                Err(e) => {
+                    failed = true;
+                    if !job.progress.is_cancelled() {
+                        self.status = Some(format!("Scan failed: {e}"));
+                    }
+                }
             }
+        }
+
+        if !finished {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        }
+
+        self.store_job = None;
+        if std::mem::take(&mut self.rollback) && failed {
+            self.undo();
+        }
+        // Nothing left to narrow down, the next scan will be a first scan again
+        if !self.has_scan() && self.scan_type.needs_previous() {
+            self.scan_type = ScanType::default();
+        }
+    }
+    /// Show a stored scan, once few enough values are left they're listed as results instead
+    ///
+    fn show_store(&mut self, store: RegionStore) {
+        if store.match_count() > STORED_RESULT_LIMIT {
+            self.store = Some(Arc::new(store));
+            return;
+        }
+
+        match store.results() {
+            Ok(results) => self.results = results,
             Err(e) => {
-                // Handle error - maybe show in UI
-                eprintln!("Scan failed: {}", e);
+                self.status = Some(format!("Scan failed: {e}"));
+                self.undo();
+            }
+        }
+    }
+    /// Progress of whichever scan is running in the background
+    ///
+    fn running(&self) -> Option<Arc<ScanProgress>> {
+        match (&self.job, &self.store_job) {
+            (Some(job), _) => Some(job.progress.clone()),
+            (_, Some(job)) => Some(job.progress.clone()),
+            _ => None,
+        }
+    }
+    /// Re-reads only the addresses already found and filters them by the current settings
+    ///
+    fn perform_next_scan(&mut self) -> anyhow::Result<()> {
+        let matcher = self.build_matcher()?;
+        self.status = None;
+
+        if let Some(store) = self.store.clone() {
+            self.perform_stored_next_scan(store, matcher.value_bytes, matcher.float_match);
+            return Ok(());
+        }
+
+        self.push_step();
+        if matcher.group.is_some() {
+            self.group = matcher.group.clone();
+        }
+        let previous = &self.history[self.history.len() - 1].results;
+        let job = ScanJob::next_scan(self.target.clone(), matcher, previous);
+        self.job = Some(Arc::new(job));
+        self.rollback = true;
+        Ok(())
+    }
+    /// What the inputs are set to right now, recorded alongside each step
+    ///
+    fn scan_params(&self) -> ScanParams {
+        ScanParams {
+            scan_type: self.scan_type.clone(),
+            value_type: self.value_type.clone(),
+            value: self.value.clone(),
+            value_upper: self.value_upper.clone(),
+            is_hex: self.is_hex,
+        }
+    }
+    /// Move whatever's being shown onto the history since a next scan is about to replace it
+    ///
+    fn push_step(&mut self) {
+        let params = self.params.take().unwrap_or_else(|| self.scan_params());
+        self.history.push(ScanStep {
+            params,
+            group: self.group.clone(),
+            results: std::mem::take(&mut self.results),
+            store: self.store.take(),
+        });
+        self.params = Some(self.scan_params());
+    }
+    /// Show a step's results, the value type is fixed to whatever they were found as
+    ///
+    fn restore(&mut self, step: ScanStep) {
+        self.value_type = step.params.value_type.clone();
+        self.results = step.results;
+        self.store = step.store;
+        self.group = step.group;
+        self.params = Some(step.params);
+    }
+    /// Throw away the results being shown and go back to the step before them
+    ///
+    fn undo(&mut self) {
+        if let Some(step) = self.history.pop() {
+            self.restore(step);
+        }
+    }
+    /// Where to save sessions to or load them from, falling back onto the target's name
+    ///
+    fn session_path(&self) -> String {
+        match self.session_path.trim() {
+            "" => format!(
+                "{}.{}",
+                lock_source(&self.target).name(),
+                SCAN_SESSION_EXTENSION
+            ),
+            path => path.to_string(),
+        }
+    }
+    /// List every step so far along with how many values each left, and save or load sessions
+    ///
+    fn history_ui(&mut self, ui: &mut Ui) {
+        for (idx, step) in self.history.iter().enumerate() {
+            ui.label(format!(
+                "{}. {}, {} left",
+                idx + 1,
+                step.params.describe(),
+                step.match_count()
+            ));
+        }
+        if let Some(params) = &self.params {
+            let match_count = match &self.store {
+                Some(store) => store.match_count(),
+                None => self.results.len(),
+            };
+            ui.label(
+                egui::RichText::new(format!(
+                    "{}. {}, {} left",
+                    self.history.len() + 1,
+                    params.describe(),
+                    match_count
+                ))
+                .color(DARK_THEME.highlight),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.session_path)
+                    .desired_width(160.0)
+                    .hint_text("Path..."),
+            );
+            if ui
+                .add_enabled(self.has_scan(), egui::Button::new("Save Session"))
+                .clicked()
+            {
+                let path = self.session_path();
+                let current = ScanStep {
+                    params: self.params.clone().unwrap_or_else(|| self.scan_params()),
+                    group: self.group.clone(),
+                    results: self.results.clone(),
+                    store: self.store.clone(),
+                };
+                self.status = Some(match save_session(&path, &self.history, &current) {
+                    Ok(()) => format!("Saved scan session to {path}"),
+                    Err(e) => format!("Failed to save scan session: {e}"),
+                });
+            }
+            if ui.button("Load Session").clicked() {
+                let path = self.session_path();
+                self.status = Some(match load_session(&path) {
+                    Ok(mut steps) => match steps.pop() {
+                        Some(current) => {
+                            self.job = None;
+                            self.store_job = None;
+                            self.rollback = false;
+                            self.history = steps;
+                            self.restore(current);
+                            format!("Loaded scan session from {path}")
+                        }
+                        None => format!("{path} has no scan steps in it"),
+                    },
+                    Err(e) => format!("Failed to load scan session: {e}"),
+                });
             }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::source::mock::MockTarget;
+    use crate::memory::source::source::share_source;
+
+    fn results(hits: &[u64]) -> ScanResults {
+        let mut results = ScanResults::new(4);
+        for address in hits {
+            results.push(*address, &100u32.to_le_bytes());
         }
+        results
+    }
+
+    #[test]
+    fn undo_goes_back_a_step_at_a_time() {
+        let mut view = ScannerView::new(
+            share_source(MockTarget::new("game")),
+            SharedAddressList::default(),
+        );
+        view.value = "100".to_string();
+        view.results = results(&[0x1000, 0x2000, 0x3000]);
+        view.params = Some(view.scan_params());
+
+        view.push_step();
+        view.scan_type = ScanType::Unchanged;
+        view.value_type = ValueType::Byte;
+        view.results = results(&[0x2000]);
+        view.push_step();
+        view.results = results(&[]);
+
+        view.undo();
+        assert_eq!(view.results.addresses(), [0x2000]);
+        assert_eq!(view.history.len(), 1);
+
+        // The value type goes back to whatever the results were found as
+        view.undo();
+        assert_eq!(view.results.addresses(), [0x1000, 0x2000, 0x3000]);
+        assert_eq!(view.value_type, ValueType::FourBytes);
+        assert_eq!(view.params.as_ref().unwrap().scan_type, ScanType::Exact);
+
+        // Nothing left to go back to
+        view.undo();
+        assert_eq!(view.results.addresses(), [0x1000, 0x2000, 0x3000]);
+        assert!(view.history.is_empty());
     }
 }
@@ -0,0 +1,128 @@
+use crate::gui::gui::TabContent;
+use crate::gui::main::DARK_THEME;
+use crate::gui::windows::allocation_view::format_protection;
+use crate::pe::pe::{ImageHeaders, SectionInfo};
+use egui::Ui;
+use egui_extras::{Column, TableBuilder};
+
+/// Create out custom TabContent object for this specific tab, in this case it will be for our
+/// sections and headers view
+///
+#[derive(Clone)]
+pub struct SectionView {
+    pub headers: Option<ImageHeaders>,
+    pub sections: Vec<SectionInfo>,
+}
+
+/// Give the machine field a readable name for the architectures we're likely to come across
+///
+fn format_machine(machine: u16) -> String {
+    match machine {
+        0x014C => "x86".to_string(),
+        0x8664 => "x64".to_string(),
+        0x01C4 => "ARM".to_string(),
+        0xAA64 => "ARM64".to_string(),
+        _ => format!("UNKNOWN (0x{:X})", machine),
+    }
+}
+
+/// Form abstract link to TabContent
+///
+impl TabContent for SectionView {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Frame::none()
+            .fill(DARK_THEME.background_dark)
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                // Headers go up top as a simple two column grid
+                if let Some(headers) = &self.headers {
+                    egui::Grid::new("section_view_headers")
+                        .num_columns(2)
+                        .spacing([40.0, 4.0])
+                        .show(ui, |ui| {
+                            let fields = [
+                                ("Machine", format_machine(headers.machine)),
+                                (
+                                    "Format",
+                                    (if headers.is_64 { "PE32+" } else { "PE32" }).to_string(),
+                                ),
+                                ("Image Base", format!("0x{:X}", headers.image_base)),
+                                (
+                                    "Entry Point",
+                                    format!(
+                                        "0x{:X}",
+                                        headers.image_base + headers.entry_point as u64
+                                    ),
+                                ),
+                                ("Size Of Image", format!("0x{:X}", headers.size_of_image)),
+                                (
+                                    "Size Of Headers",
+                                    format!("0x{:X}", headers.size_of_headers),
+                                ),
+                                (
+                                    "Section Alignment",
+                                    format!("0x{:X}", headers.section_alignment),
+                                ),
+                                ("Sections", format!("{}", headers.number_of_sections)),
+                                ("Timestamp", format!("0x{:X}", headers.time_date_stamp)),
+                            ];
+                            for (field, value) in fields {
+                                ui.label(egui::RichText::new(field).color(DARK_THEME.text_muted));
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                    ui.add_space(10.0);
+                }
+
+                let column_width = ui.available_width() / 6.0;
+
+                // Then every section underneath
+                TableBuilder::new(ui)
+                    .columns(Column::exact(column_width), 6)
+                    .header(20.0, |mut header| {
+                        for title in [
+                            "Name",
+                            "Virtual Address",
+                            "Virtual Size",
+                            "Raw Offset",
+                            "Raw Size",
+                            "Protection",
+                        ] {
+                            header.col(|ui| {
+                                ui.vertical_centered(|ui| {
+                                    ui.heading(title);
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(30.0, self.sections.len(), |mut row| {
+                            if let Some(section) = self.sections.get(row.index()) {
+                                let columns = [
+                                    section.name.clone(),
+                                    format!("0x{:X}", section.virtual_address),
+                                    format!("0x{:X}", section.virtual_size),
+                                    format!("0x{:X}", section.raw_offset),
+                                    format!("0x{:X}", section.raw_size),
+                                    format_protection(section.protection()),
+                                ];
+                                for column in columns {
+                                    row.col(|ui| {
+                                        ui.centered_and_justified(|ui| {
+                                            ui.label(column);
+                                        });
+                                    });
+                                }
+                            }
+                        });
+                    });
+            });
+    }
+
+    // Handle our name of the tab
+    //
+    fn title(&self) -> String {
+        "[>] Sections".to_string()
+    }
+}
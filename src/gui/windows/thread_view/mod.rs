@@ -0,0 +1 @@
+pub mod thread_view;
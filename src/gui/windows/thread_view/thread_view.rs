@@ -0,0 +1,71 @@
+use crate::gui::gui::TabContent;
+use crate::gui::main::DARK_THEME;
+use crate::memory::process::types::ThreadInfo;
+use egui::Ui;
+use egui_extras::{Column, TableBuilder};
+
+/// Create out custom TabContent object for this specific tab, in this case it will be for our
+/// thread view
+///
+#[derive(Clone)]
+pub struct ThreadView {
+    pub threads: Vec<ThreadInfo>,
+}
+
+/// Format an optional register, dumps don't always carry a context we understand
+///
+fn format_register(register: Option<u64>) -> String {
+    register.map_or_else(|| "??".to_string(), |value| format!("0x{:X}", value))
+}
+
+/// Form abstract link to TabContent
+///
+impl TabContent for ThreadView {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Frame::none()
+            .fill(DARK_THEME.background_dark)
+            .inner_margin(10.0)
+            .show(ui, |ui| {
+                let column_width = ui.available_width() / 6.0;
+
+                TableBuilder::new(ui)
+                    .columns(Column::exact(column_width), 6)
+                    .header(20.0, |mut header| {
+                        for title in ["Thread ID", "TEB", "Stack", "Stack Size", "IP", "SP"] {
+                            header.col(|ui| {
+                                ui.vertical_centered(|ui| {
+                                    ui.heading(title);
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(30.0, self.threads.len(), |mut row| {
+                            if let Some(thread) = self.threads.get(row.index()) {
+                                let columns = [
+                                    format!("{}", thread.thread_id),
+                                    format!("0x{:X}", thread.teb),
+                                    format!("0x{:X}", thread.stack_start),
+                                    format!("0x{:X}", thread.stack_size),
+                                    format_register(thread.instruction_pointer),
+                                    format_register(thread.stack_pointer),
+                                ];
+                                for column in columns {
+                                    row.col(|ui| {
+                                        ui.centered_and_justified(|ui| {
+                                            ui.label(column);
+                                        });
+                                    });
+                                }
+                            }
+                        });
+                    });
+            });
+    }
+
+    // Handle our name of the tab
+    //
+    fn title(&self) -> String {
+        "[>] Threads".to_string()
+    }
+}
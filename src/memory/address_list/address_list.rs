@@ -0,0 +1,401 @@
+use crate::memory::dump::snapshot::{
+    take_blob, take_string, take_u32, take_u64, take_u8, write_blob, write_string, write_u32,
+    write_u64,
+};
+use crate::memory::pointer::pointer::PointerChain;
+use crate::memory::scan::types::ValueType;
+use crate::memory::source::source::MemorySource;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// "PVAL" as it appears at the very start of every saved address list
+///
+pub const ADDRESS_LIST_MAGIC: u32 = 0x4C41_5650;
+
+/// Bumped whenever the layout changes, older lists are refused rather than misread
+///
+const ADDRESS_LIST_VERSION: u32 = 1;
+
+/// Extension address lists are saved with
+///
+pub const ADDRESS_LIST_EXTENSION: &str = "pvtable";
+
+/// How a frozen entry is held in place
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum FreezeMode {
+    /// Keep writing the same value back
+    Value(Vec<u8>),
+    /// Leave the value be unless it strays outside of these, both ends are inclusive
+    Clamp { min: Vec<u8>, max: Vec<u8> },
+}
+
+impl Default for FreezeMode {
+    fn default() -> Self {
+        FreezeMode::Value(Vec::new())
+    }
+}
+
+/// A single value being kept track of. Plain addresses are chains without any offsets so
+/// everything is resolved the same way, chains are resolved again every time they're read so
+/// they keep following the value around
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressEntry {
+    pub description: String,
+    pub chain: PointerChain,
+    pub value_type: ValueType,
+    /// Number of bytes shown for arrays of bytes and strings, numbers always use their own size
+    pub length: usize,
+    /// Whether the freezer is holding this entry in place
+    pub frozen: bool,
+    pub freeze: FreezeMode,
+}
+
+impl AddressEntry {
+    /// Create an entry at a plain address
+    ///
+    pub fn at_address(
+        description: &str,
+        address: u64,
+        value_type: ValueType,
+        length: usize,
+    ) -> Self {
+        Self {
+            description: description.to_string(),
+            chain: PointerChain::new(None, address, Vec::new()),
+            value_type,
+            length,
+            frozen: false,
+            freeze: FreezeMode::default(),
+        }
+    }
+    /// Number of bytes the value takes up
+    ///
+    pub fn size(&self) -> usize {
+        match self.value_type.is_pattern() || self.value_type.is_group() {
+            true => self.length.max(1),
+            false => self.value_type.size(),
+        }
+    }
+    /// Resolve the entry and read its value, handing back where it was found too
+    ///
+    pub fn read(&self, source: &mut dyn MemorySource) -> anyhow::Result<(u64, Vec<u8>)> {
+        let address = self.chain.resolve(source)?;
+        let bytes = source.read_bytes(address as usize, self.size())?;
+        Ok((address, bytes))
+    }
+    /// Parse a value typed in into the bytes it'd have within the target
+    ///
+    pub fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>> {
+        // Groups are kept as plain bytes once they're in the list
+        match self.value_type.is_group() {
+            true => ValueType::ArrayOfBytes.parse(text, is_hex),
+            false => self.value_type.parse(text, is_hex),
+        }
+    }
+    /// Write a value typed in to wherever the entry currently resolves to, a frozen value is held
+    /// at the new value from then on
+    ///
+    pub fn write(
+        &mut self,
+        source: &mut dyn MemorySource,
+        text: &str,
+        is_hex: bool,
+    ) -> anyhow::Result<()> {
+        let bytes = self.parse(text, is_hex)?;
+        let address = self.chain.resolve(source)?;
+        source.write_bytes(address as usize, &bytes)?;
+
+        if let FreezeMode::Value(frozen) = &mut self.freeze {
+            *frozen = bytes;
+        }
+        Ok(())
+    }
+    /// Start or stop holding the entry in place, freezing at a value takes whatever it's at now
+    ///
+    pub fn set_frozen(
+        &mut self,
+        source: &mut dyn MemorySource,
+        frozen: bool,
+    ) -> anyhow::Result<()> {
+        if frozen && matches!(self.freeze, FreezeMode::Value(_)) {
+            self.freeze = FreezeMode::Value(self.read(source)?.1);
+        }
+        self.frozen = frozen;
+        Ok(())
+    }
+    /// Put a frozen entry back in place, clamped values are only written when they're out of
+    /// bounds
+    ///
+    pub fn apply_freeze(&self, source: &mut dyn MemorySource) -> anyhow::Result<()> {
+        if !self.frozen {
+            return Ok(());
+        }
+
+        match &self.freeze {
+            FreezeMode::Value(value) if value.is_empty() => Ok(()),
+            FreezeMode::Value(value) => {
+                let address = self.chain.resolve(source)?;
+                source.write_bytes(address as usize, value)
+            }
+            FreezeMode::Clamp { min, max } => {
+                let (address, bytes) = self.read(source)?;
+                let value = self.value_type.decode(&bytes);
+
+                if value < self.value_type.decode(min) {
+                    source.write_bytes(address as usize, min)
+                } else if value > self.value_type.decode(max) {
+                    source.write_bytes(address as usize, max)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Every entry the user is keeping track of, this outlives any one tab so it's shared between
+/// the scanner and the address list view
+///
+#[derive(Clone, Debug, Default)]
+pub struct AddressList {
+    pub entries: Vec<AddressEntry>,
+}
+
+impl AddressList {
+    /// Write the list out to disk
+    ///
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        write_u32(&mut writer, ADDRESS_LIST_MAGIC)?;
+        write_u32(&mut writer, ADDRESS_LIST_VERSION)?;
+
+        write_u32(&mut writer, self.entries.len() as u32)?;
+        for entry in self.entries.iter() {
+            write_string(&mut writer, &entry.description)?;
+            // Types are stored by name so registered types can be found again
+            write_string(&mut writer, entry.value_type.label())?;
+            write_u32(&mut writer, entry.length as u32)?;
+
+            writer.write_all(&[entry.chain.module.is_some() as u8])?;
+            write_string(
+                &mut writer,
+                entry.chain.module.as_deref().unwrap_or_default(),
+            )?;
+            write_u64(&mut writer, entry.chain.base_offset)?;
+            write_u32(&mut writer, entry.chain.offsets.len() as u32)?;
+            for offset in entry.chain.offsets.iter() {
+                write_u64(&mut writer, *offset as u64)?;
+            }
+
+            writer.write_all(&[entry.frozen as u8])?;
+            match &entry.freeze {
+                FreezeMode::Value(value) => {
+                    writer.write_all(&[0])?;
+                    write_blob(&mut writer, value)?;
+                }
+                FreezeMode::Clamp { min, max } => {
+                    writer.write_all(&[1])?;
+                    write_blob(&mut writer, min)?;
+                    write_blob(&mut writer, max)?;
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+    /// Load a list back in from disk
+    ///
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        if take_u32(&mut reader)? != ADDRESS_LIST_MAGIC {
+            return Err(anyhow::anyhow!("{path} is not an address list"));
+        }
+        let version = take_u32(&mut reader)?;
+        if version != ADDRESS_LIST_VERSION {
+            return Err(anyhow::anyhow!(
+                "Address list version {version} is not supported, expected {ADDRESS_LIST_VERSION}"
+            ));
+        }
+
+        // Counts aren't trusted to size anything up front, a bad one runs out of file instead
+        let entry_count = take_u32(&mut reader)?;
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let description = take_string(&mut reader)?;
+            let value_type = take_string(&mut reader)?;
+            let value_type = ValueType::from_label(&value_type)
+                .ok_or_else(|| anyhow::anyhow!("Unknown value type {value_type}"))?;
+            let length = take_u32(&mut reader)? as usize;
+
+            let has_module = take_u8(&mut reader)? != 0;
+            let module = take_string(&mut reader)?;
+            let base_offset = take_u64(&mut reader)?;
+            let offset_count = take_u32(&mut reader)?;
+            let offsets = (0..offset_count)
+                .map(|_| Ok(take_u64(&mut reader)? as i64))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let frozen = take_u8(&mut reader)? != 0;
+            let freeze = match take_u8(&mut reader)? {
+                0 => FreezeMode::Value(take_blob(&mut reader)?),
+                1 => FreezeMode::Clamp {
+                    min: take_blob(&mut reader)?,
+                    max: take_blob(&mut reader)?,
+                },
+                mode => return Err(anyhow::anyhow!("Unknown freeze mode {mode}")),
+            };
+
+            entries.push(AddressEntry {
+                description,
+                chain: PointerChain::new(
+                    has_module.then_some(module.as_str()),
+                    base_offset,
+                    offsets,
+                ),
+                value_type,
+                length,
+                frozen,
+                freeze,
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+/// An address list shared between every view that adds to or shows it
+///
+pub type SharedAddressList = Arc<Mutex<AddressList>>;
+
+/// Lock a shared address list, a poisoned lock only means a view panicked while holding it
+///
+pub fn lock_address_list(list: &SharedAddressList) -> MutexGuard<'_, AddressList> {
+    list.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::process::types::PAGE_READWRITE;
+    use crate::memory::source::mock::MockTarget;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("procvamp-{}-{name}", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// A target holding a single i32 at 0x1000
+    ///
+    fn target(value: i32) -> MockTarget {
+        let mut bytes = vec![0u8; 0x100];
+        bytes[..4].copy_from_slice(&value.to_le_bytes());
+        MockTarget::new("game").with_region(0x1000, bytes, PAGE_READWRITE)
+    }
+
+    fn value_at(target: &mut MockTarget) -> i32 {
+        i32::from_le_bytes(target.read_bytes(0x1000, 4).unwrap().try_into().unwrap())
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let mut health = AddressEntry::at_address("health", 0x1000, ValueType::FourBytes, 0);
+        health.frozen = true;
+        health.freeze = FreezeMode::Value(100u32.to_le_bytes().to_vec());
+
+        let mut ammo = AddressEntry::at_address("ammo", 0, ValueType::SignedTwoBytes, 0);
+        ammo.chain = PointerChain::new(Some("game.exe"), 0x1234, vec![0x18, -0x8]);
+        ammo.freeze = FreezeMode::Clamp {
+            min: 1i16.to_le_bytes().to_vec(),
+            max: 30i16.to_le_bytes().to_vec(),
+        };
+
+        let name = AddressEntry::at_address("name", 0x2000, ValueType::Ascii, 16);
+
+        let list = AddressList {
+            entries: vec![health, ammo, name],
+        };
+        let path = temp_path(&format!("list.{ADDRESS_LIST_EXTENSION}"));
+        list.save(&path).unwrap();
+        let loaded = AddressList::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().entries, list.entries);
+    }
+
+    #[test]
+    fn other_files_are_refused() {
+        let path = temp_path("not-a-list");
+
+        std::fs::write(&path, b"not an address list").unwrap();
+        assert!(AddressList::load(&path).is_err());
+
+        // A count running past the end of the file is an error rather than a huge allocation
+        let mut bytes = Vec::new();
+        bytes.extend(ADDRESS_LIST_MAGIC.to_le_bytes());
+        bytes.extend(ADDRESS_LIST_VERSION.to_le_bytes());
+        bytes.extend(u32::MAX.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+        let loaded = AddressList::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn frozen_values_are_written_back() {
+        let mut target = target(100);
+        let mut entry = AddressEntry::at_address("health", 0x1000, ValueType::SignedFourBytes, 0);
+
+        // Nothing is written until the entry is frozen
+        target.write_bytes(0x1000, &50i32.to_le_bytes()).unwrap();
+        entry.apply_freeze(&mut target).unwrap();
+        assert_eq!(value_at(&mut target), 50);
+
+        // Freezing takes whatever the value is at right then
+        entry.set_frozen(&mut target, true).unwrap();
+        target.write_bytes(0x1000, &10i32.to_le_bytes()).unwrap();
+        entry.apply_freeze(&mut target).unwrap();
+        assert_eq!(value_at(&mut target), 50);
+
+        // Writing a new value moves where it's held at
+        entry.write(&mut target, "75", false).unwrap();
+        target.write_bytes(0x1000, &10i32.to_le_bytes()).unwrap();
+        entry.apply_freeze(&mut target).unwrap();
+        assert_eq!(value_at(&mut target), 75);
+
+        entry.set_frozen(&mut target, false).unwrap();
+        target.write_bytes(0x1000, &10i32.to_le_bytes()).unwrap();
+        entry.apply_freeze(&mut target).unwrap();
+        assert_eq!(value_at(&mut target), 10);
+    }
+
+    #[test]
+    fn clamped_values_are_only_written_out_of_bounds() {
+        let mut target = target(0);
+        let mut entry = AddressEntry::at_address("speed", 0x1000, ValueType::SignedFourBytes, 0);
+        entry.freeze = FreezeMode::Clamp {
+            min: (-10i32).to_le_bytes().to_vec(),
+            max: 20i32.to_le_bytes().to_vec(),
+        };
+        entry.set_frozen(&mut target, true).unwrap();
+
+        // Negative values compare as negative rather than as huge unsigned ones
+        for (value, clamped) in [(-50, -10), (-10, -10), (5, 5), (20, 20), (21, 20)] {
+            target
+                .write_bytes(0x1000, &i32::to_le_bytes(value))
+                .unwrap();
+            entry.apply_freeze(&mut target).unwrap();
+            assert_eq!(value_at(&mut target), clamped, "{value}");
+        }
+        // Setting up a clamp doesn't swap it out for the current value
+        assert!(matches!(entry.freeze, FreezeMode::Clamp { .. }));
+    }
+}
@@ -0,0 +1,457 @@
+use super::address_list::{AddressEntry, AddressList, FreezeMode};
+use crate::memory::expression::expression::evaluate_constant;
+use crate::memory::pointer::pointer::PointerChain;
+use crate::memory::scan::types::ValueType;
+use roxmltree::{Document, Node};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Extension Cheat Engine saves its tables with
+///
+pub const CHEAT_TABLE_EXTENSION: &str = "CT";
+
+/// Table version written out on export, old enough that any recent Cheat Engine will open it
+///
+const CHEAT_TABLE_VERSION: u32 = 45;
+
+/// Errors that can come out of reading a Cheat Engine table
+///
+#[derive(Error, Debug)]
+pub enum CheatTableErrors {
+    #[error("{path} is not a Cheat Engine table")]
+    NotACheatTable { path: String },
+    #[error("'{description}' has a type we can't show ({variable_type})")]
+    UnsupportedType {
+        description: String,
+        variable_type: String,
+    },
+    #[error("'{description}' has an address we can't follow ({address})")]
+    UnsupportedAddress {
+        description: String,
+        address: String,
+    },
+}
+
+/// Entries read out of a table, along with why any that couldn't be brought in were left out.
+/// Scripts, headers and symbolic addresses have no equivalent here so they're skipped
+///
+#[derive(Debug, Default)]
+pub struct CheatTableImport {
+    pub entries: Vec<AddressEntry>,
+    pub skipped: Vec<String>,
+}
+
+/// Text of a child element, e.g. the 4 Bytes of <VariableType>4 Bytes</VariableType>
+///
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+/// Cheat Engine types come as a name with a couple of flags alongside, strings and arrays of
+/// bytes carry their own length
+///
+fn parse_variable_type(entry: Node) -> Option<(ValueType, usize)> {
+    let signed = child_text(entry, "ShowAsSigned") == Some("1");
+    let length = |name| {
+        child_text(entry, name)
+            .and_then(|length| length.parse::<usize>().ok())
+            .unwrap_or(10)
+    };
+
+    let value_type = match (child_text(entry, "VariableType")?, signed) {
+        ("Byte", false) => ValueType::Byte,
+        ("Byte", true) => ValueType::SignedByte,
+        ("2 Bytes", false) => ValueType::TwoBytes,
+        ("2 Bytes", true) => ValueType::SignedTwoBytes,
+        ("4 Bytes", false) => ValueType::FourBytes,
+        ("4 Bytes", true) => ValueType::SignedFourBytes,
+        ("8 Bytes", false) => ValueType::EightBytes,
+        ("8 Bytes", true) => ValueType::SignedEightBytes,
+        ("Float", _) => ValueType::Float,
+        ("Double", _) => ValueType::Double,
+        ("Array of byte", _) => return Some((ValueType::ArrayOfBytes, length("ByteLength"))),
+        ("String", _) => {
+            // Lengths are in characters so UTF-16 strings take up twice as many bytes
+            return Some(match child_text(entry, "Unicode") == Some("1") {
+                true => (ValueType::Utf16, length("Length") * 2),
+                false => (ValueType::Ascii, length("Length")),
+            });
+        }
+        _ => return None,
+    };
+
+    Some((value_type, 10))
+}
+
+/// Addresses are hex, either absolute or relative to a module which may or may not be quoted,
+/// e.g. 0040F000 or "game.exe"+1A2B
+///
+fn parse_address(address: &str) -> Option<(Option<String>, u64)> {
+    if let Ok(address) = evaluate_constant(address) {
+        return Some((None, address));
+    }
+
+    let (module, offset) = match address.strip_prefix('"') {
+        Some(quoted) => {
+            let (module, rest) = quoted.split_once('"')?;
+            (module, rest.trim())
+        }
+        None => match address.find(['+', '-']) {
+            Some(split) => (address[..split].trim(), address[split..].trim()),
+            None => (address, ""),
+        },
+    };
+
+    let offset = match offset {
+        "" => 0,
+        offset => evaluate_constant(&format!("0{offset}")).ok()?,
+    };
+    Some((Some(module.to_string()), offset))
+}
+
+/// Turn a single CheatEntry into one of ours
+///
+fn parse_entry(entry: Node) -> anyhow::Result<AddressEntry> {
+    // Descriptions are stored quoted, only that one pair of quotes is taken off so any at either
+    // end of the description itself are kept
+    let description = child_text(entry, "Description").unwrap_or_default();
+    let description = description
+        .strip_prefix('"')
+        .and_then(|description| description.strip_suffix('"'))
+        .unwrap_or(description)
+        .to_string();
+
+    let (value_type, length) =
+        parse_variable_type(entry).ok_or_else(|| CheatTableErrors::UnsupportedType {
+            description: description.clone(),
+            variable_type: child_text(entry, "VariableType")
+                .unwrap_or_default()
+                .to_string(),
+        })?;
+
+    let address = child_text(entry, "Address").unwrap_or_default();
+    let (module, base_offset) =
+        parse_address(address).ok_or_else(|| CheatTableErrors::UnsupportedAddress {
+            description: description.clone(),
+            address: address.to_string(),
+        })?;
+
+    // Offsets are listed last to first
+    let mut offsets = Vec::new();
+    if let Some(list) = entry.children().find(|child| child.has_tag_name("Offsets")) {
+        for offset in list.children().filter(|child| child.has_tag_name("Offset")) {
+            let text = offset.text().unwrap_or_default().trim();
+            let offset =
+                evaluate_constant(text).map_err(|_| CheatTableErrors::UnsupportedAddress {
+                    description: description.clone(),
+                    address: format!("{address} with offset {text}"),
+                })?;
+            offsets.push(offset as i64);
+        }
+    }
+    offsets.reverse();
+
+    Ok(AddressEntry {
+        description,
+        chain: PointerChain::new(module.as_deref(), base_offset, offsets),
+        value_type,
+        length,
+        frozen: false,
+        freeze: FreezeMode::default(),
+    })
+}
+
+/// Walk through a CheatEntries element, entries nested under headers are flattened out
+///
+fn collect_entries(entries: Node, import: &mut CheatTableImport) {
+    for entry in entries
+        .children()
+        .filter(|child| child.has_tag_name("CheatEntry"))
+    {
+        let is_header = child_text(entry, "GroupHeader") == Some("1");
+        let is_script = child_text(entry, "VariableType") == Some("Auto Assembler Script");
+
+        if !is_header && !is_script {
+            match parse_entry(entry) {
+                Ok(parsed) => import.entries.push(parsed),
+                Err(e) => import.skipped.push(e.to_string()),
+            }
+        }
+
+        if let Some(children) = entry
+            .children()
+            .find(|child| child.has_tag_name("CheatEntries"))
+        {
+            collect_entries(children, import);
+        }
+    }
+}
+
+/// Read the address entries out of a Cheat Engine table
+///
+pub fn import_cheat_table(path: &str) -> anyhow::Result<CheatTableImport> {
+    let text = std::fs::read_to_string(path)?;
+    let document = Document::parse(&text)?;
+
+    let root = document.root_element();
+    if !root.has_tag_name("CheatTable") {
+        return Err(CheatTableErrors::NotACheatTable {
+            path: path.to_string(),
+        }
+        .into());
+    }
+
+    let mut import = CheatTableImport::default();
+    if let Some(entries) = root
+        .children()
+        .find(|child| child.has_tag_name("CheatEntries"))
+    {
+        collect_entries(entries, &mut import);
+    }
+    Ok(import)
+}
+
+/// Escape text so it can sit within an element
+///
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Cheat Engine's name for a type and whether it's shown signed, groups go out as plain bytes
+///
+fn variable_type(value_type: &ValueType) -> (&'static str, bool) {
+    match value_type {
+        ValueType::Byte => ("Byte", false),
+        ValueType::SignedByte => ("Byte", true),
+        ValueType::TwoBytes => ("2 Bytes", false),
+        ValueType::SignedTwoBytes => ("2 Bytes", true),
+        ValueType::FourBytes => ("4 Bytes", false),
+        ValueType::SignedFourBytes => ("4 Bytes", true),
+        ValueType::EightBytes => ("8 Bytes", false),
+        ValueType::SignedEightBytes => ("8 Bytes", true),
+        ValueType::Float => ("Float", false),
+        ValueType::Double => ("Double", false),
+        ValueType::Ascii | ValueType::Utf16 => ("String", false),
+        ValueType::ArrayOfBytes | ValueType::Group | ValueType::Custom(_) => {
+            ("Array of byte", false)
+        }
+    }
+}
+
+/// Hex the way Cheat Engine writes it, without a prefix and with the sign up front
+///
+fn format_offset(offset: i64) -> String {
+    match offset < 0 {
+        true => format!("-{:X}", offset.unsigned_abs()),
+        false => format!("{:X}", offset),
+    }
+}
+
+/// Write an address list out as a Cheat Engine table
+///
+pub fn export_cheat_table(list: &AddressList, path: &str) -> anyhow::Result<()> {
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(
+        xml,
+        r#"<CheatTable CheatEngineTableVersion="{CHEAT_TABLE_VERSION}">"#
+    )?;
+    writeln!(xml, "  <CheatEntries>")?;
+
+    for (id, entry) in list.entries.iter().enumerate() {
+        let (name, signed) = variable_type(&entry.value_type);
+
+        writeln!(xml, "    <CheatEntry>")?;
+        writeln!(xml, "      <ID>{id}</ID>")?;
+        writeln!(
+            xml,
+            "      <Description>\"{}\"</Description>",
+            escape(&entry.description)
+        )?;
+        if signed {
+            writeln!(xml, "      <ShowAsSigned>1</ShowAsSigned>")?;
+        }
+        writeln!(xml, "      <VariableType>{name}</VariableType>")?;
+        match entry.value_type {
+            ValueType::Ascii | ValueType::Utf16 => {
+                let unicode = entry.value_type == ValueType::Utf16;
+                let length = match unicode {
+                    true => entry.size() / 2,
+                    false => entry.size(),
+                };
+                writeln!(xml, "      <Length>{length}</Length>")?;
+                writeln!(xml, "      <Unicode>{}</Unicode>", unicode as u8)?;
+                writeln!(xml, "      <CodePage>0</CodePage>")?;
+                writeln!(xml, "      <ZeroTerminate>1</ZeroTerminate>")?;
+            }
+            ValueType::ArrayOfBytes | ValueType::Group | ValueType::Custom(_) => {
+                writeln!(xml, "      <ByteLength>{}</ByteLength>", entry.size())?;
+            }
+            _ => {}
+        }
+
+        let address = match &entry.chain.module {
+            Some(module) => format!("\"{}\"+{:X}", escape(module), entry.chain.base_offset),
+            None => format!("{:X}", entry.chain.base_offset),
+        };
+        writeln!(xml, "      <Address>{address}</Address>")?;
+
+        if !entry.chain.offsets.is_empty() {
+            writeln!(xml, "      <Offsets>")?;
+            for offset in entry.chain.offsets.iter().rev() {
+                writeln!(xml, "        <Offset>{}</Offset>", format_offset(*offset))?;
+            }
+            writeln!(xml, "      </Offsets>")?;
+        }
+        writeln!(xml, "    </CheatEntry>")?;
+    }
+
+    writeln!(xml, "  </CheatEntries>")?;
+    writeln!(xml, "</CheatTable>")?;
+
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A table the way Cheat Engine 7.x saves them, a header holding a nested header and a script
+    /// alongside a pointer and a UTF-16 string
+    ///
+    const CHEAT_ENGINE_TABLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<CheatTable CheatEngineTableVersion="45">
+  <CheatEntries>
+    <CheatEntry>
+      <ID>0</ID>
+      <Description>"Player"</Description>
+      <Options moHideChildren="1"/>
+      <GroupHeader>1</GroupHeader>
+      <CheatEntries>
+        <CheatEntry>
+          <ID>1</ID>
+          <Description>"Health"</Description>
+          <ShowAsSigned>1</ShowAsSigned>
+          <VariableType>4 Bytes</VariableType>
+          <Address>"Game-Win64.exe"+01A2B3C0</Address>
+          <Offsets>
+            <Offset>-8</Offset>
+            <Offset>1C</Offset>
+            <Offset>10</Offset>
+          </Offsets>
+        </CheatEntry>
+        <CheatEntry>
+          <ID>2</ID>
+          <Description>"Stats"</Description>
+          <GroupHeader>1</GroupHeader>
+          <CheatEntries>
+            <CheatEntry>
+              <ID>3</ID>
+              <Description>"Name"</Description>
+              <VariableType>String</VariableType>
+              <Length>16</Length>
+              <Unicode>1</Unicode>
+              <CodePage>0</CodePage>
+              <ZeroTerminate>1</ZeroTerminate>
+              <Address>7FF6A0010000</Address>
+            </CheatEntry>
+          </CheatEntries>
+        </CheatEntry>
+      </CheatEntries>
+    </CheatEntry>
+    <CheatEntry>
+      <ID>4</ID>
+      <Description>"Infinite ammo"</Description>
+      <VariableType>Auto Assembler Script</VariableType>
+      <AssemblerScript>[ENABLE]
+aobscanmodule(ammo,Game-Win64.exe,FF 48 10)
+[DISABLE]
+</AssemblerScript>
+    </CheatEntry>
+    <CheatEntry>
+      <ID>5</ID>
+      <Description>"Speed"</Description>
+      <VariableType>Custom</VariableType>
+      <CustomType>Float Big Endian</CustomType>
+      <Address>1000</Address>
+    </CheatEntry>
+  </CheatEntries>
+</CheatTable>
+"#;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("procvamp-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn cheat_engine_tables_are_imported() {
+        let path = temp_path("import.CT");
+        std::fs::write(&path, CHEAT_ENGINE_TABLE).unwrap();
+        let import = import_cheat_table(&path);
+        let _ = std::fs::remove_file(&path);
+        let import = import.unwrap();
+
+        // Headers and scripts are skipped over without complaint, nested entries are flattened
+        assert_eq!(import.entries.len(), 2);
+        assert_eq!(import.skipped.len(), 1);
+        assert!(import.skipped[0].contains("Speed"));
+
+        let health = &import.entries[0];
+        assert_eq!(health.description, "Health");
+        assert_eq!(health.value_type, ValueType::SignedFourBytes);
+        assert_eq!(
+            health.chain,
+            PointerChain::new(Some("Game-Win64.exe"), 0x01A2B3C0, vec![0x10, 0x1C, -8])
+        );
+
+        let name = &import.entries[1];
+        assert_eq!(name.description, "Name");
+        assert_eq!(name.value_type, ValueType::Utf16);
+        assert_eq!(name.size(), 32);
+        assert_eq!(
+            name.chain,
+            PointerChain::new(None, 0x7FF6A0010000, Vec::new())
+        );
+    }
+
+    #[test]
+    fn exported_tables_import_the_same() {
+        let list = AddressList {
+            entries: vec![
+                AddressEntry {
+                    chain: PointerChain::new(Some("game.exe"), 0x1A2B, vec![0x10, -0x20]),
+                    ..AddressEntry::at_address("Height 5'11\"", 0, ValueType::Double, 10)
+                },
+                AddressEntry::at_address("\"Quoted\" & <escaped>", 0x40_0000, ValueType::Ascii, 12),
+                AddressEntry::at_address("Name", 0x40_1000, ValueType::Utf16, 24),
+                AddressEntry::at_address("Code", 0x40_2000, ValueType::ArrayOfBytes, 6),
+                AddressEntry::at_address("Lives", 0x40_3000, ValueType::SignedByte, 10),
+            ],
+        };
+
+        let path = temp_path("round-trip.CT");
+        export_cheat_table(&list, &path).unwrap();
+        let import = import_cheat_table(&path);
+        let _ = std::fs::remove_file(&path);
+        let import = import.unwrap();
+
+        assert!(import.skipped.is_empty(), "{:?}", import.skipped);
+        assert_eq!(import.entries.len(), list.entries.len());
+        for (imported, entry) in import.entries.iter().zip(list.entries.iter()) {
+            assert_eq!(imported.description, entry.description);
+            assert_eq!(imported.chain, entry.chain);
+            assert_eq!(imported.value_type, entry.value_type);
+            assert_eq!(imported.size(), entry.size());
+        }
+    }
+}
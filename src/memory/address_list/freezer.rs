@@ -0,0 +1,105 @@
+use super::address_list::{lock_address_list, AddressEntry, SharedAddressList};
+use crate::memory::source::source::{lock_source, SharedSource};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often frozen entries are written back unless told otherwise
+///
+pub const DEFAULT_FREEZE_INTERVAL_MS: u64 = 100;
+
+/// Settings shared between the writer thread and whichever views are showing them
+///
+#[derive(Debug)]
+pub struct FreezeControl {
+    interval_ms: AtomicU64,
+    paused: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// Keeps every frozen entry of an address list in place by writing them back on a background
+/// thread. Belongs to a single target, dropping it stops the thread for good
+///
+pub struct Freezer {
+    control: Arc<FreezeControl>,
+}
+
+impl Freezer {
+    /// Start writing frozen entries of the list back into the target
+    ///
+    pub fn start(target: SharedSource, list: SharedAddressList) -> Self {
+        let control = Arc::new(FreezeControl {
+            interval_ms: AtomicU64::new(DEFAULT_FREEZE_INTERVAL_MS),
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+        });
+
+        let worker_control = control.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_millis(
+                worker_control.interval_ms.load(Ordering::Relaxed),
+            ));
+            if worker_control.stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            if worker_control.paused.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            // Copy the entries out so the list isn't held while we're writing
+            let frozen: Vec<AddressEntry> = lock_address_list(&list)
+                .entries
+                .iter()
+                .filter(|entry| entry.frozen)
+                .cloned()
+                .collect();
+            if frozen.is_empty() {
+                continue;
+            }
+
+            // Entries that can't be resolved right now are tried again next time round
+            let mut source = lock_source(&target);
+            for entry in frozen.iter() {
+                let _ = entry.apply_freeze(&mut *source);
+            }
+        });
+
+        Self { control }
+    }
+    pub fn interval_ms(&self) -> u64 {
+        self.control.interval_ms.load(Ordering::Relaxed)
+    }
+    /// Change how often entries are written back, anything under a millisecond is bumped up
+    ///
+    pub fn set_interval_ms(&self, interval_ms: u64) {
+        self.control
+            .interval_ms
+            .store(interval_ms.max(1), Ordering::Relaxed);
+    }
+    pub fn is_paused(&self) -> bool {
+        self.control.paused.load(Ordering::Relaxed)
+    }
+    /// Stop writing anything back without forgetting which entries are frozen
+    ///
+    pub fn set_paused(&self, paused: bool) {
+        self.control.paused.store(paused, Ordering::Relaxed);
+    }
+    pub fn is_stopped(&self) -> bool {
+        self.control.stopped.load(Ordering::Relaxed)
+    }
+    /// Stop the writer thread, it finishes up at its next wake and can't be started again
+    ///
+    pub fn stop(&self) {
+        self.control.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Freezer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A freezer shared between the debug view that owns it and any address list views
+///
+pub type SharedFreezer = Arc<Freezer>;
@@ -0,0 +1,3 @@
+pub mod address_list;
+pub mod cheat_table;
+pub mod freezer;
@@ -0,0 +1,545 @@
+use super::parser::parse_declarations;
+use crate::memory::scan::compare::extract_value;
+use crate::memory::scan::scannable::{
+    register_value_type, ByteArray, CustomType, StructField, StructType,
+};
+use crate::memory::scan::types::ValueType;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use thiserror::Error;
+
+/// Errors that can come out of parsing C declarations or laying them out
+///
+#[derive(Error, Debug)]
+pub enum CTypeErrors {
+    #[error("Line {line}: expected {expected}, found {found}")]
+    Unexpected {
+        line: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("Declarations end early, expected {expected}")]
+    UnexpectedEnd { expected: String },
+    #[error("Line {line}: unexpected character '{character}'")]
+    UnexpectedCharacter { line: usize, character: char },
+    #[error("Line {line}: unknown type {name}")]
+    UnknownType { line: usize, name: String },
+    #[error("Line {line}: {name} is not a constant")]
+    UnknownConstant { line: usize, name: String },
+    #[error("Line {line}: constant is out of range")]
+    InvalidConstant { line: usize },
+    #[error("Line {line}: bitfields aren't supported ({name})")]
+    Bitfield { line: usize, name: String },
+    #[error("{name} is used before it's defined")]
+    IncompleteType { name: String },
+    #[error("{name} is not a struct or union")]
+    NotARecord { name: String },
+    #[error("{name} is too large to lay out")]
+    TooLarge { name: String },
+}
+
+/// A type as C sees it. Structs, unions and enums are referred to by name and looked up within
+/// the library, typedefs are resolved as they're parsed
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum CType {
+    Void,
+    /// Fixed width integers, floats and doubles
+    Primitive(ValueType),
+    Pointer(Box<CType>),
+    Array(Box<CType>, usize),
+    /// A struct or union
+    Record(String),
+    Enum(String),
+}
+
+/// A field of a struct or union, anonymous structs and unions within another have no name
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CField {
+    pub name: String,
+    pub ctype: CType,
+    pub offset: usize,
+}
+
+/// A laid out struct or union
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CRecord {
+    pub name: String,
+    pub is_union: bool,
+    pub size: usize,
+    pub alignment: usize,
+    pub fields: Vec<CField>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CEnum {
+    pub name: String,
+    pub size: usize,
+    pub values: Vec<(String, i64)>,
+}
+
+/// Everything parsed out of C declarations so far. Layouts are worked out with the pointer size
+/// of the target they were loaded against
+///
+#[derive(Clone, Debug)]
+pub struct TypeLibrary {
+    pub pointer_size: usize,
+    /// Longs are 4 bytes on Windows and 32-bit targets but 8 bytes elsewhere (LP64)
+    pub long_size: usize,
+    pub records: BTreeMap<String, CRecord>,
+    pub enums: BTreeMap<String, CEnum>,
+    pub typedefs: BTreeMap<String, CType>,
+    /// Enumerators and defines, usable within array lengths
+    pub constants: BTreeMap<String, i64>,
+    anonymous_count: usize,
+}
+
+/// Most fields a struct is flattened into, past this it's better off being looked at through
+/// the struct tree
+///
+pub const MAX_STRUCT_FIELDS: usize = 0x1000;
+
+/// Integer type of a given width
+///
+pub fn integer_type(size: usize, signed: bool) -> ValueType {
+    match (size, signed) {
+        (1, false) => ValueType::Byte,
+        (1, true) => ValueType::SignedByte,
+        (2, false) => ValueType::TwoBytes,
+        (2, true) => ValueType::SignedTwoBytes,
+        (8, false) => ValueType::EightBytes,
+        (8, true) => ValueType::SignedEightBytes,
+        (_, false) => ValueType::FourBytes,
+        (_, true) => ValueType::SignedFourBytes,
+    }
+}
+
+/// Little endian integer out of however many bytes are given, pointers are read with this
+///
+pub fn read_integer(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |value, &byte| (value << 8) | byte as u64)
+}
+
+/// Structs, unions and enums without a name are given one C can't spell
+///
+pub fn is_anonymous(name: &str) -> bool {
+    name.starts_with('<')
+}
+
+impl TypeLibrary {
+    pub const fn new() -> Self {
+        Self {
+            pointer_size: 8,
+            long_size: 4,
+            records: BTreeMap::new(),
+            enums: BTreeMap::new(),
+            typedefs: BTreeMap::new(),
+            constants: BTreeMap::new(),
+            anonymous_count: 0,
+        }
+    }
+    /// Name for a struct, union or enum that wasn't given one
+    ///
+    pub fn anonymous_name(&mut self) -> String {
+        self.anonymous_count += 1;
+        format!("<anonymous {}>", self.anonymous_count)
+    }
+    pub fn record(&self, name: &str) -> anyhow::Result<&CRecord> {
+        self.records.get(name).ok_or_else(|| {
+            CTypeErrors::IncompleteType {
+                name: name.to_string(),
+            }
+            .into()
+        })
+    }
+    pub fn enumeration(&self, name: &str) -> anyhow::Result<&CEnum> {
+        self.enums.get(name).ok_or_else(|| {
+            CTypeErrors::IncompleteType {
+                name: name.to_string(),
+            }
+            .into()
+        })
+    }
+    /// Find a type by name, whether it's a struct, union, enum or typedef
+    ///
+    pub fn resolve(&self, name: &str) -> Option<CType> {
+        if let Some(ctype) = self.typedefs.get(name) {
+            return Some(ctype.clone());
+        }
+        if self.records.contains_key(name) {
+            return Some(CType::Record(name.to_string()));
+        }
+        if self.enums.contains_key(name) {
+            return Some(CType::Enum(name.to_string()));
+        }
+        None
+    }
+    /// Every named type, in name order
+    ///
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .records
+            .keys()
+            .chain(self.enums.keys())
+            .chain(self.typedefs.keys())
+            .filter(|name| !is_anonymous(name))
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+    pub fn size_of(&self, ctype: &CType) -> anyhow::Result<usize> {
+        Ok(match ctype {
+            CType::Void => 0,
+            CType::Primitive(value_type) => value_type.size(),
+            CType::Pointer(_) => self.pointer_size,
+            CType::Array(inner, count) => {
+                self.size_of(inner)?
+                    .checked_mul(*count)
+                    .ok_or_else(|| CTypeErrors::TooLarge {
+                        name: self.type_name(ctype),
+                    })?
+            }
+            CType::Record(name) => self.record(name)?.size,
+            CType::Enum(name) => self.enumeration(name)?.size,
+        })
+    }
+    pub fn align_of(&self, ctype: &CType) -> anyhow::Result<usize> {
+        Ok(match ctype {
+            CType::Void => 1,
+            CType::Primitive(value_type) => value_type.size(),
+            CType::Pointer(_) => self.pointer_size,
+            CType::Array(inner, _) => self.align_of(inner)?,
+            CType::Record(name) => self.record(name)?.alignment,
+            CType::Enum(name) => self.enumeration(name)?.size,
+        })
+    }
+    /// Lay fields out the way a C compiler would. Each is aligned to its own alignment, capped by
+    /// the packing in effect, and the whole is padded out to its most aligned field
+    ///
+    pub fn layout(
+        &self,
+        name: &str,
+        is_union: bool,
+        members: Vec<(String, CType)>,
+        pack: Option<usize>,
+    ) -> anyhow::Result<CRecord> {
+        let mut fields = Vec::with_capacity(members.len());
+        let mut end = 0usize;
+        let mut alignment = 1;
+
+        let too_large = || CTypeErrors::TooLarge {
+            name: name.to_string(),
+        };
+
+        for (field_name, ctype) in members {
+            let size = self.size_of(&ctype)?;
+            let field_alignment = self
+                .align_of(&ctype)?
+                .min(pack.unwrap_or(usize::MAX))
+                .max(1);
+            alignment = alignment.max(field_alignment);
+
+            let offset = match is_union {
+                true => 0,
+                false => end
+                    .checked_next_multiple_of(field_alignment)
+                    .ok_or_else(too_large)?,
+            };
+            end = end.max(offset.checked_add(size).ok_or_else(too_large)?);
+            fields.push(CField {
+                name: field_name,
+                ctype,
+                offset,
+            });
+        }
+
+        Ok(CRecord {
+            name: name.to_string(),
+            is_union,
+            size: end
+                .checked_next_multiple_of(alignment)
+                .ok_or_else(too_large)?,
+            alignment,
+            fields,
+        })
+    }
+    /// Give an anonymous struct, union or enum the name a typedef gave it
+    ///
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if let Some(mut record) = self.records.remove(from) {
+            record.name = to.to_string();
+            self.records.insert(to.to_string(), record);
+        }
+        if let Some(mut enumeration) = self.enums.remove(from) {
+            enumeration.name = to.to_string();
+            self.enums.insert(to.to_string(), enumeration);
+        }
+    }
+    /// How a type is written in C, e.g. struct Player* or uint32_t[4]
+    ///
+    pub fn type_name(&self, ctype: &CType) -> String {
+        match ctype {
+            CType::Void => "void".to_string(),
+            CType::Primitive(value_type) => match value_type {
+                ValueType::Byte => "uint8_t",
+                ValueType::SignedByte => "int8_t",
+                ValueType::TwoBytes => "uint16_t",
+                ValueType::SignedTwoBytes => "int16_t",
+                ValueType::FourBytes => "uint32_t",
+                ValueType::SignedFourBytes => "int32_t",
+                ValueType::EightBytes => "uint64_t",
+                ValueType::SignedEightBytes => "int64_t",
+                ValueType::Float => "float",
+                ValueType::Double => "double",
+                value_type => value_type.label(),
+            }
+            .to_string(),
+            CType::Pointer(inner) => format!("{}*", self.type_name(inner)),
+            CType::Array(inner, count) => format!("{}[{}]", self.type_name(inner), count),
+            CType::Record(name) => match self.records.get(name) {
+                Some(record) if record.is_union => format!("union {name}"),
+                _ => format!("struct {name}"),
+            },
+            CType::Enum(name) => format!("enum {name}"),
+        }
+    }
+    /// Turn the bytes of a value into text, structs and arrays are shown through their fields so
+    /// have nothing of their own. Enums show the name of their value where there is one
+    ///
+    pub fn format_value(&self, ctype: &CType, bytes: &[u8]) -> String {
+        match ctype {
+            CType::Primitive(value_type) => extract_value(bytes, value_type),
+            CType::Pointer(_) => format!("0x{:X}", read_integer(bytes)),
+            CType::Enum(name) => {
+                // Sign extend so negative values are found
+                let shift = 64 - bytes.len().clamp(1, 8) * 8;
+                let value = ((read_integer(bytes) << shift) as i64) >> shift;
+                let named = self.enums.get(name).and_then(|enumeration| {
+                    enumeration.values.iter().find(|(_, other)| *other == value)
+                });
+                match named {
+                    Some((value_name, _)) => format!("{value_name} ({value})"),
+                    None => value.to_string(),
+                }
+            }
+            CType::Void | CType::Array(..) | CType::Record(_) => String::new(),
+        }
+    }
+    /// Flatten a type into the fields of a struct, nested structs and arrays are named by the
+    /// path to them. Only the first member of a union is kept since they all overlap. Byte and
+    /// character arrays are buffers more often than not so they stay whole as a single field
+    ///
+    fn flatten(
+        &self,
+        prefix: &str,
+        ctype: &CType,
+        offset: usize,
+        fields: &mut Vec<StructField>,
+    ) -> anyhow::Result<()> {
+        let mut push = |value_type| {
+            fields.push(StructField {
+                name: prefix.to_string(),
+                offset,
+                value_type,
+            })
+        };
+        match ctype {
+            CType::Void => {}
+            CType::Primitive(value_type) => push(value_type.clone()),
+            CType::Pointer(_) => push(integer_type(self.pointer_size, false)),
+            CType::Enum(name) => push(integer_type(self.enumeration(name)?.size, true)),
+            CType::Array(inner, count)
+                if matches!(
+                    inner.as_ref(),
+                    CType::Primitive(ValueType::Byte | ValueType::SignedByte)
+                ) =>
+            {
+                let text = **inner == CType::Primitive(ValueType::SignedByte);
+                push(ValueType::Custom(CustomType(Arc::new(ByteArray::new(
+                    &self.type_name(ctype),
+                    *count,
+                    text,
+                )))))
+            }
+            CType::Array(inner, count) => {
+                let size = self.size_of(inner)?;
+                if fields.len().saturating_add(*count) > MAX_STRUCT_FIELDS {
+                    return Err(CTypeErrors::TooLarge {
+                        name: self.type_name(ctype),
+                    }
+                    .into());
+                }
+                for idx in 0..*count {
+                    let name = format!("{prefix}[{idx}]");
+                    self.flatten(&name, inner, offset + idx * size, fields)?;
+                }
+            }
+            CType::Record(name) => {
+                let record = self.record(name)?;
+                let members = match record.is_union {
+                    true => &record.fields[..record.fields.len().min(1)],
+                    false => &record.fields[..],
+                };
+                for field in members {
+                    let name = match (prefix, field.name.as_str()) {
+                        ("", name) | (name, "") => name.to_string(),
+                        (prefix, name) => format!("{prefix}.{name}"),
+                    };
+                    self.flatten(&name, &field.ctype, offset + field.offset, fields)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Turn a struct or union into a struct type so it can be scanned for and added to the
+    /// address list, pointers are kept as integers as wide as they are
+    ///
+    pub fn to_struct_type(&self, name: &str) -> anyhow::Result<StructType> {
+        let ctype = self.resolve(name).ok_or_else(|| CTypeErrors::NotARecord {
+            name: name.to_string(),
+        })?;
+        let CType::Record(record_name) = &ctype else {
+            return Err(CTypeErrors::NotARecord {
+                name: name.to_string(),
+            }
+            .into());
+        };
+
+        let mut fields = Vec::new();
+        self.flatten("", &ctype, 0, &mut fields)?;
+        StructType::new(name, self.record(record_name)?.size, fields)
+    }
+}
+
+impl Default for TypeLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Types loaded from every header so far, shared between every view like registered types are
+///
+static LIBRARY: RwLock<TypeLibrary> = RwLock::new(TypeLibrary::new());
+
+pub fn type_library() -> RwLockReadGuard<'static, TypeLibrary> {
+    LIBRARY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Names of the types a header brought in, along with any structs that couldn't be registered as
+/// value types and why
+///
+#[derive(Debug, Default)]
+pub struct CHeaderImport {
+    pub types: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// Parse C declarations into the library, nothing is added if any of them fail to parse. Every
+/// struct and union is registered as a value type on top
+///
+pub fn load_c_declarations(
+    text: &str,
+    pointer_size: usize,
+    windows: bool,
+) -> anyhow::Result<CHeaderImport> {
+    let mut library = type_library().clone();
+    library.pointer_size = pointer_size;
+    library.long_size = match pointer_size == 8 && !windows {
+        true => 8,
+        false => 4,
+    };
+
+    let mut import = CHeaderImport {
+        types: parse_declarations(text, &mut library)?,
+        skipped: Vec::new(),
+    };
+
+    for name in import.types.iter() {
+        if !matches!(library.resolve(name), Some(CType::Record(_))) {
+            continue;
+        }
+        if ValueType::ALL
+            .iter()
+            .any(|value_type| value_type.label() == name)
+        {
+            import
+                .skipped
+                .push(format!("{name} is already a built in type"));
+            continue;
+        }
+        match library.to_struct_type(name) {
+            Ok(struct_type) => {
+                register_value_type(Arc::new(struct_type));
+            }
+            Err(e) => import.skipped.push(format!("{name}: {e}")),
+        }
+    }
+
+    *LIBRARY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = library;
+    Ok(import)
+}
+
+/// Parse a header file into the library
+///
+pub fn load_c_header(
+    path: &str,
+    pointer_size: usize,
+    windows: bool,
+) -> anyhow::Result<CHeaderImport> {
+    let text = std::fs::read_to_string(path)?;
+    load_c_declarations(&text, pointer_size, windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(text: &str) -> anyhow::Result<TypeLibrary> {
+        let mut library = TypeLibrary::new();
+        parse_declarations(text, &mut library)?;
+        Ok(library)
+    }
+
+    #[test]
+    fn byte_arrays_flatten_into_a_single_field() {
+        let library =
+            library("struct Packet { int id; char name[16]; uint8_t data[0x10000000]; };").unwrap();
+        let struct_type = library.to_struct_type("Packet").unwrap();
+
+        let fields: Vec<(&str, usize, usize)> = struct_type
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.offset, field.value_type.size()))
+            .collect();
+        assert_eq!(
+            fields,
+            [("id", 0, 4), ("name", 4, 16), ("data", 20, 0x10000000)]
+        );
+
+        let name = &struct_type.fields[1].value_type;
+        let mut bytes = b"player".to_vec();
+        bytes.resize(16, 0);
+        assert_eq!(name.parse("player", false).unwrap(), bytes);
+        assert_eq!(extract_value(&bytes, name), "\"player\"");
+    }
+
+    #[test]
+    fn oversized_arrays_are_errors() {
+        assert!(library("struct Huge { uint64_t values[0x4000000000000000]; };").is_err());
+        assert!(library("struct Many { int values[0x100000]; };")
+            .unwrap()
+            .to_struct_type("Many")
+            .is_err());
+    }
+}
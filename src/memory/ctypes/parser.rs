@@ -0,0 +1,835 @@
+use super::ctypes::{integer_type, is_anonymous, CEnum, CType, CTypeErrors, TypeLibrary};
+use crate::memory::scan::types::ValueType;
+
+/// Symbols made up of two characters are listed first so they're matched before their first
+/// character is
+///
+const SYMBOLS: [&str; 20] = [
+    "<<", ">>", "{", "}", "(", ")", "[", "]", ";", ",", "*", "&", "=", ":", "+", "-", "/", "%",
+    "|", "~",
+];
+
+/// Words that make up the built in types, they can come in any combination and order
+///
+const PRIMITIVE_WORDS: [&str; 15] = [
+    "void", "bool", "_Bool", "char", "short", "int", "long", "signed", "unsigned", "float",
+    "double", "__int8", "__int16", "__int32", "__int64",
+];
+
+/// Words that can sit around a type without changing its layout
+///
+const QUALIFIERS: [&str; 16] = [
+    "const",
+    "volatile",
+    "static",
+    "extern",
+    "inline",
+    "register",
+    "mutable",
+    "restrict",
+    "__unaligned",
+    "__ptr32",
+    "__ptr64",
+    "__cdecl",
+    "__stdcall",
+    "__fastcall",
+    "__thiscall",
+    "WINAPI",
+];
+
+/// Qualifiers that are followed by their arguments, e.g. __declspec(align(16))
+///
+const ATTRIBUTES: [&str; 4] = ["__declspec", "__attribute__", "alignas", "_Alignas"];
+
+/// Ways #pragma pack can change the packing
+///
+#[derive(Clone, Debug, PartialEq)]
+enum PackChange {
+    /// Save the current packing, optionally setting a new one
+    Push(Option<usize>),
+    Pop,
+    /// None goes back to the compiler's default
+    Set(Option<usize>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(i64),
+    Symbol(&'static str),
+    Pack(PackChange),
+    /// A #define of a name to whatever follows it, only kept if it turns out to be a constant
+    Define(String, String),
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+}
+
+/// Parse an integer literal, hex, octal or decimal with any suffix
+///
+fn parse_number(text: &str) -> Option<i64> {
+    let text = text.trim().trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None if text.len() > 1 && text.starts_with('0') => {
+            u64::from_str_radix(&text[1..], 8).ok()?
+        }
+        None => text.parse::<u64>().ok()?,
+    };
+    Some(value as i64)
+}
+
+/// Pull what we need out of a preprocessor directive, the packing and any constants defined.
+/// Everything else, includes and conditionals included, is ignored
+///
+fn parse_directive(directive: &str) -> Option<TokenKind> {
+    let directive = directive.trim();
+
+    if let Some(define) = directive.strip_prefix("define") {
+        let define = define.trim_start();
+        let end = define
+            .find(|character: char| !(character.is_ascii_alphanumeric() || character == '_'))
+            .unwrap_or(define.len());
+        let (name, body) = define.split_at(end);
+        // Macros that take arguments can't be evaluated on their own
+        if name.is_empty() || body.starts_with('(') || body.trim().is_empty() {
+            return None;
+        }
+        return Some(TokenKind::Define(name.to_string(), body.trim().to_string()));
+    }
+
+    let pack = directive
+        .strip_prefix("pragma")?
+        .trim_start()
+        .strip_prefix("pack")?
+        .trim_start()
+        .strip_prefix('(')?;
+    let arguments: Vec<&str> = pack
+        .split(')')
+        .next()?
+        .split(',')
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    let number = |text: &str| parse_number(text).map(|value| value as usize);
+
+    Some(TokenKind::Pack(match arguments.as_slice() {
+        [] => PackChange::Set(None),
+        ["push"] => PackChange::Push(None),
+        ["push", value] | ["push", _, value] => PackChange::Push(number(value)),
+        ["pop", ..] => PackChange::Pop,
+        [value] => PackChange::Set(number(value)),
+        _ => return None,
+    }))
+}
+
+/// Split declarations up into tokens, comments and strings are dropped along the way
+///
+fn tokenize(text: &str) -> anyhow::Result<Vec<Token>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    // Directives are only recognised at the start of a line
+    let mut line_start = true;
+
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        let rest = &text[pos..];
+
+        if byte == b'\n' {
+            line += 1;
+            line_start = true;
+            pos += 1;
+            continue;
+        }
+        if byte.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        if rest.starts_with("//") {
+            pos += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").map(|end| end + 4).unwrap_or(rest.len());
+            line += rest[..end].matches('\n').count();
+            pos += end;
+            continue;
+        }
+
+        let start_of_line = line_start;
+        line_start = false;
+
+        if byte == b'#' && start_of_line {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            if let Some(kind) = parse_directive(&rest[1..end]) {
+                tokens.push(Token { kind, line });
+            }
+            pos += end;
+            continue;
+        }
+        // Strings only show up in things like extern "C" so they're skipped over
+        if byte == b'"' || byte == b'\'' {
+            let end = rest[1..]
+                .find(byte as char)
+                .map(|end| end + 2)
+                .unwrap_or(rest.len());
+            pos += end;
+            continue;
+        }
+        if byte.is_ascii_alphanumeric() || byte == b'_' {
+            let end = rest
+                .find(|character: char| !(character.is_ascii_alphanumeric() || character == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let kind = match byte.is_ascii_digit() {
+                true => TokenKind::Number(
+                    parse_number(word).ok_or(CTypeErrors::InvalidConstant { line })?,
+                ),
+                false => TokenKind::Ident(word.to_string()),
+            };
+            tokens.push(Token { kind, line });
+            pos += end;
+            continue;
+        }
+
+        let symbol = SYMBOLS
+            .iter()
+            .find(|symbol| rest.starts_with(**symbol))
+            .ok_or_else(|| CTypeErrors::UnexpectedCharacter {
+                line,
+                character: rest.chars().next().unwrap_or_default(),
+            })?;
+        tokens.push(Token {
+            kind: TokenKind::Symbol(symbol),
+            line,
+        });
+        pos += symbol.len();
+    }
+
+    Ok(tokens)
+}
+
+/// Types that come with a width of their own, from stdint.h and windows.h
+///
+fn named_type(name: &str, pointer_size: usize) -> Option<CType> {
+    let integer = |size, signed| Some(CType::Primitive(integer_type(size, signed)));
+    match name {
+        "int8_t" | "INT8" | "CHAR" | "CCHAR" => integer(1, true),
+        "uint8_t" | "UINT8" | "BYTE" | "UCHAR" | "BOOLEAN" | "char8_t" => integer(1, false),
+        "int16_t" | "INT16" | "SHORT" => integer(2, true),
+        "uint16_t" | "UINT16" | "WORD" | "USHORT" | "wchar_t" | "WCHAR" | "char16_t" => {
+            integer(2, false)
+        }
+        "int32_t" | "INT32" | "INT" | "LONG" | "BOOL" | "HRESULT" => integer(4, true),
+        "uint32_t" | "UINT32" | "UINT" | "DWORD" | "ULONG" | "char32_t" => integer(4, false),
+        "int64_t" | "INT64" | "LONGLONG" | "LONG64" => integer(8, true),
+        "uint64_t" | "UINT64" | "QWORD" | "DWORD64" | "ULONGLONG" | "ULONG64" => integer(8, false),
+        "intptr_t" | "ptrdiff_t" | "ssize_t" | "INT_PTR" | "LONG_PTR" => {
+            integer(pointer_size, true)
+        }
+        "uintptr_t" | "size_t" | "UINT_PTR" | "ULONG_PTR" | "DWORD_PTR" | "SIZE_T" => {
+            integer(pointer_size, false)
+        }
+        "FLOAT" => Some(CType::Primitive(ValueType::Float)),
+        "DOUBLE" => Some(CType::Primitive(ValueType::Double)),
+        "PVOID" | "LPVOID" | "HANDLE" | "HMODULE" | "HWND" => {
+            Some(CType::Pointer(Box::new(CType::Void)))
+        }
+        _ => None,
+    }
+}
+
+/// Work out a built in type from the words it was written with, a single long is as wide as the
+/// target's data model says
+///
+fn primitive_type(words: &[String], long_size: usize) -> CType {
+    let has = |word: &str| words.iter().any(|other| other == word);
+    let longs = words.iter().filter(|word| *word == "long").count();
+
+    let size = match () {
+        _ if has("void") => return CType::Void,
+        _ if has("float") => return CType::Primitive(ValueType::Float),
+        _ if has("double") => return CType::Primitive(ValueType::Double),
+        _ if has("bool") || has("_Bool") => return CType::Primitive(ValueType::Byte),
+        _ if has("char") || has("__int8") => 1,
+        _ if has("short") || has("__int16") => 2,
+        _ if longs >= 2 || has("__int64") => 8,
+        _ if longs == 1 => long_size,
+        _ => 4,
+    };
+    CType::Primitive(integer_type(size, !has("unsigned")))
+}
+
+/// Operators constant expressions can use along with how tightly they bind
+///
+const BINARY_OPERATORS: [(&str, u8); 9] = [
+    ("|", 1),
+    ("&", 2),
+    ("<<", 3),
+    (">>", 3),
+    ("+", 4),
+    ("-", 4),
+    ("*", 5),
+    ("/", 5),
+    ("%", 5),
+];
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    library: &'a mut TypeLibrary,
+    /// Packing in effect, None for the compiler's default
+    pack: Option<usize>,
+    pack_stack: Vec<Option<usize>>,
+    /// Names of every type defined, in the order they were
+    defined: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: Vec<Token>, library: &'a mut TypeLibrary) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            library,
+            pack: None,
+            pack_stack: Vec::new(),
+            defined: Vec::new(),
+        }
+    }
+    fn peek(&self) -> Option<&TokenKind> {
+        self.peek_at(0)
+    }
+    fn peek_at(&self, ahead: usize) -> Option<&TokenKind> {
+        self.tokens.get(self.pos + ahead).map(|token| &token.kind)
+    }
+    fn peek_ident(&self) -> Option<&str> {
+        match self.peek() {
+            Some(TokenKind::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+    /// Line of the next token, or the last one once we've run out
+    ///
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map(|token| token.line)
+            .unwrap_or(1)
+    }
+    fn unexpected(&self, expected: &str) -> anyhow::Error {
+        match self.tokens.get(self.pos) {
+            Some(token) => CTypeErrors::Unexpected {
+                line: token.line,
+                expected: expected.to_string(),
+                found: match &token.kind {
+                    TokenKind::Ident(ident) => ident.clone(),
+                    TokenKind::Number(number) => number.to_string(),
+                    TokenKind::Symbol(symbol) => symbol.to_string(),
+                    TokenKind::Pack(_) | TokenKind::Define(..) => "a directive".to_string(),
+                },
+            }
+            .into(),
+            None => CTypeErrors::UnexpectedEnd {
+                expected: expected.to_string(),
+            }
+            .into(),
+        }
+    }
+    fn next(&mut self, expected: &str) -> anyhow::Result<TokenKind> {
+        let kind = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| self.unexpected(expected))?;
+        self.pos += 1;
+        Ok(kind)
+    }
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(TokenKind::Symbol(other)) if *other == symbol)
+    }
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    fn expect_symbol(&mut self, symbol: &str) -> anyhow::Result<()> {
+        match self.eat_symbol(symbol) {
+            true => Ok(()),
+            false => Err(self.unexpected(&format!("'{symbol}'"))),
+        }
+    }
+    fn expect_ident(&mut self, expected: &str) -> anyhow::Result<String> {
+        match self.peek() {
+            Some(TokenKind::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+    /// Skip over a bracketed run of tokens, handing back whether it asked for packing
+    ///
+    fn skip_balanced(&mut self) -> anyhow::Result<bool> {
+        self.expect_symbol("(")?;
+        let mut depth = 1;
+        let mut packed = false;
+
+        while depth > 0 {
+            match self.next("')'")? {
+                TokenKind::Symbol("(") => depth += 1,
+                TokenKind::Symbol(")") => depth -= 1,
+                TokenKind::Ident(ident) if ident == "packed" || ident == "__packed__" => {
+                    packed = true
+                }
+                _ => {}
+            }
+        }
+        Ok(packed)
+    }
+    /// Skip any qualifiers and attributes, handing back whether any of them asked for packing
+    ///
+    fn skip_qualifiers(&mut self) -> anyhow::Result<bool> {
+        let mut packed = false;
+        while let Some(ident) = self.peek_ident() {
+            if QUALIFIERS.contains(&ident) {
+                self.pos += 1;
+            } else if ATTRIBUTES.contains(&ident) {
+                self.pos += 1;
+                packed |= self.skip_balanced()?;
+            } else {
+                break;
+            }
+        }
+        Ok(packed)
+    }
+    /// Deal with a directive if that's what's next
+    ///
+    fn take_directive(&mut self) -> bool {
+        match self.peek().cloned() {
+            Some(TokenKind::Pack(change)) => {
+                match change {
+                    PackChange::Push(value) => {
+                        self.pack_stack.push(self.pack);
+                        if value.is_some() {
+                            self.pack = value;
+                        }
+                    }
+                    PackChange::Pop => self.pack = self.pack_stack.pop().flatten(),
+                    PackChange::Set(value) => self.pack = value,
+                }
+                self.pos += 1;
+                true
+            }
+            Some(TokenKind::Define(name, body)) => {
+                // Defines that aren't constants are of no use to us
+                if let Ok(value) = self.evaluate_define(&body) {
+                    self.library.constants.insert(name, value);
+                }
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+    fn evaluate_define(&mut self, body: &str) -> anyhow::Result<i64> {
+        let mut parser = Parser::new(tokenize(body)?, &mut *self.library);
+        let value = parser.parse_expression()?;
+        match parser.peek() {
+            None => Ok(value),
+            Some(_) => Err(parser.unexpected("the end of the define")),
+        }
+    }
+    fn parse_expression(&mut self) -> anyhow::Result<i64> {
+        self.parse_binary(0)
+    }
+    /// Parse operators binding at least as tightly as the precedence given
+    ///
+    fn parse_binary(&mut self, min_precedence: u8) -> anyhow::Result<i64> {
+        let mut left = self.parse_unary()?;
+
+        while let Some((symbol, precedence)) = BINARY_OPERATORS
+            .iter()
+            .find(|(symbol, _)| self.is_symbol(symbol))
+            .copied()
+        {
+            if precedence < min_precedence {
+                break;
+            }
+            let line = self.line();
+            self.pos += 1;
+            let right = self.parse_binary(precedence + 1)?;
+
+            let value = match symbol {
+                "|" => Some(left | right),
+                "&" => Some(left & right),
+                "<<" => u32::try_from(right)
+                    .ok()
+                    .and_then(|right| left.checked_shl(right)),
+                ">>" => u32::try_from(right)
+                    .ok()
+                    .and_then(|right| left.checked_shr(right)),
+                "+" => Some(left.wrapping_add(right)),
+                "-" => Some(left.wrapping_sub(right)),
+                "*" => Some(left.wrapping_mul(right)),
+                "/" => left.checked_div(right),
+                _ => left.checked_rem(right),
+            };
+            left = value.ok_or(CTypeErrors::InvalidConstant { line })?;
+        }
+        Ok(left)
+    }
+    fn parse_unary(&mut self) -> anyhow::Result<i64> {
+        let line = self.line();
+        match self.next("a constant")? {
+            TokenKind::Number(value) => Ok(value),
+            TokenKind::Symbol("-") => Ok(self.parse_unary()?.wrapping_neg()),
+            TokenKind::Symbol("+") => self.parse_unary(),
+            TokenKind::Symbol("~") => Ok(!self.parse_unary()?),
+            TokenKind::Symbol("(") => {
+                let value = self.parse_expression()?;
+                self.expect_symbol(")")?;
+                Ok(value)
+            }
+            TokenKind::Ident(ident) if ident == "sizeof" => {
+                self.expect_symbol("(")?;
+                let mut ctype = self.parse_type_spec()?;
+                while self.eat_symbol("*") {
+                    ctype = CType::Pointer(Box::new(ctype));
+                }
+                self.expect_symbol(")")?;
+                Ok(self.library.size_of(&ctype)? as i64)
+            }
+            TokenKind::Ident(name) => self
+                .library
+                .constants
+                .get(&name)
+                .copied()
+                .ok_or_else(|| CTypeErrors::UnknownConstant { line, name }.into()),
+            _ => {
+                self.pos -= 1;
+                Err(self.unexpected("a constant"))
+            }
+        }
+    }
+    /// Parse the type a declaration starts with, defining any struct, union or enum written out
+    /// along the way
+    ///
+    fn parse_type_spec(&mut self) -> anyhow::Result<CType> {
+        let packed = self.skip_qualifiers()?;
+        let line = self.line();
+        let ident = self.expect_ident("a type")?;
+
+        match ident.as_str() {
+            "struct" | "union" | "class" => self.parse_record(ident == "union", packed),
+            "enum" => self.parse_enum(),
+            _ if PRIMITIVE_WORDS.contains(&ident.as_str()) => {
+                let mut words = vec![ident];
+                while let Some(word) = self.peek_ident() {
+                    if PRIMITIVE_WORDS.contains(&word) {
+                        words.push(word.to_string());
+                    } else if !QUALIFIERS.contains(&word) {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(primitive_type(&words, self.library.long_size))
+            }
+            _ => named_type(&ident, self.library.pointer_size)
+                .or_else(|| self.library.resolve(&ident))
+                .ok_or_else(|| CTypeErrors::UnknownType { line, name: ident }.into()),
+        }
+    }
+    /// Parse a struct or union after its keyword, either a reference to one or its definition
+    ///
+    fn parse_record(&mut self, is_union: bool, packed: bool) -> anyhow::Result<CType> {
+        let mut packed = packed | self.skip_qualifiers()?;
+        let name = match self.peek_ident() {
+            Some(_) => Some(self.expect_ident("a name")?),
+            None => None,
+        };
+        if !self.eat_symbol("{") {
+            return match name {
+                Some(name) => Ok(CType::Record(name)),
+                None => Err(self.unexpected("a struct name or body")),
+            };
+        }
+
+        let mut members = Vec::new();
+        while !self.eat_symbol("}") {
+            if self.take_directive() || self.eat_symbol(";") {
+                continue;
+            }
+            let base = self.parse_type_spec()?;
+            if self.eat_symbol(";") {
+                // Anonymous structs and unions within another have their fields reached directly
+                if matches!(&base, CType::Record(name) if is_anonymous(name)) {
+                    members.push((String::new(), base));
+                }
+                continue;
+            }
+
+            loop {
+                let line = self.line();
+                let (field_name, ctype) = self.parse_declarator(base.clone())?;
+                if self.is_symbol(":") {
+                    return Err(CTypeErrors::Bitfield {
+                        line,
+                        name: field_name,
+                    }
+                    .into());
+                }
+                members.push((field_name, ctype));
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            self.expect_symbol(";")?;
+        }
+        packed |= self.skip_qualifiers()?;
+
+        let name = name.unwrap_or_else(|| self.library.anonymous_name());
+        let pack = match packed {
+            true => Some(1),
+            false => self.pack,
+        };
+        let record = self.library.layout(&name, is_union, members, pack)?;
+        self.library.records.insert(name.clone(), record);
+        if !is_anonymous(&name) {
+            self.defined.push(name.clone());
+        }
+        Ok(CType::Record(name))
+    }
+    /// Parse an enum after its keyword, values carry on from the last one unless they're given
+    ///
+    fn parse_enum(&mut self) -> anyhow::Result<CType> {
+        self.skip_qualifiers()?;
+        // C++ scoped enums
+        if matches!(self.peek_ident(), Some("class" | "struct")) {
+            self.pos += 1;
+        }
+        let name = match self.peek_ident() {
+            Some(_) => Some(self.expect_ident("a name")?),
+            None => None,
+        };
+        let size = match self.eat_symbol(":") {
+            true => {
+                let underlying = self.parse_type_spec()?;
+                self.library.size_of(&underlying)?
+            }
+            false => 4,
+        };
+        if !self.eat_symbol("{") {
+            return match name {
+                Some(name) => Ok(CType::Enum(name)),
+                None => Err(self.unexpected("an enum name or body")),
+            };
+        }
+
+        let mut values = Vec::new();
+        let mut next = 0i64;
+        while !self.eat_symbol("}") {
+            let value_name = self.expect_ident("an enumerator")?;
+            let value = match self.eat_symbol("=") {
+                true => self.parse_expression()?,
+                false => next,
+            };
+            self.library.constants.insert(value_name.clone(), value);
+            values.push((value_name, value));
+            next = value.wrapping_add(1);
+
+            if !self.eat_symbol(",") {
+                self.expect_symbol("}")?;
+                break;
+            }
+        }
+
+        let name = name.unwrap_or_else(|| self.library.anonymous_name());
+        self.library.enums.insert(
+            name.clone(),
+            CEnum {
+                name: name.clone(),
+                size,
+                values,
+            },
+        );
+        if !is_anonymous(&name) {
+            self.defined.push(name.clone());
+        }
+        Ok(CType::Enum(name))
+    }
+    /// Lengths of an array, outermost first
+    ///
+    fn parse_dimensions(&mut self) -> anyhow::Result<Vec<usize>> {
+        let mut dimensions = Vec::new();
+        while self.eat_symbol("[") {
+            let line = self.line();
+            let length = self.parse_expression()?;
+            dimensions
+                .push(usize::try_from(length).map_err(|_| CTypeErrors::InvalidConstant { line })?);
+            self.expect_symbol("]")?;
+        }
+        Ok(dimensions)
+    }
+    /// Parse a name along with the pointers and arrays wrapped around the type it's given.
+    /// Function pointers are kept as plain pointers, only their width matters
+    ///
+    fn parse_declarator(&mut self, base: CType) -> anyhow::Result<(String, CType)> {
+        let mut ctype = base;
+        loop {
+            self.skip_qualifiers()?;
+            if !self.eat_symbol("*") && !self.eat_symbol("&") {
+                break;
+            }
+            ctype = CType::Pointer(Box::new(ctype));
+        }
+
+        let (name, dimensions) = match self.eat_symbol("(") {
+            true => {
+                self.skip_qualifiers()?;
+                self.expect_symbol("*")?;
+                let name = self.expect_ident("a name")?;
+                let dimensions = self.parse_dimensions()?;
+                self.expect_symbol(")")?;
+                self.skip_balanced()?;
+                ctype = CType::Pointer(Box::new(CType::Void));
+                (name, dimensions)
+            }
+            false => (self.expect_ident("a name")?, self.parse_dimensions()?),
+        };
+
+        let ctype = dimensions
+            .into_iter()
+            .rev()
+            .fold(ctype, |inner, length| CType::Array(Box::new(inner), length));
+        Ok((name, ctype))
+    }
+    /// Parse a typedef after its keyword. Anonymous structs, unions and enums take on the first
+    /// name they're given
+    ///
+    fn parse_typedef(&mut self) -> anyhow::Result<()> {
+        let mut base = self.parse_type_spec()?;
+        loop {
+            let (name, ctype) = self.parse_declarator(base.clone())?;
+            match &ctype {
+                CType::Record(anonymous) | CType::Enum(anonymous)
+                    if ctype == base && is_anonymous(anonymous) =>
+                {
+                    self.library.rename(anonymous, &name);
+                    base = match base {
+                        CType::Record(_) => CType::Record(name.clone()),
+                        _ => CType::Enum(name.clone()),
+                    };
+                }
+                _ => {
+                    self.library.typedefs.insert(name.clone(), ctype);
+                }
+            }
+            self.defined.push(name);
+
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        self.expect_symbol(";")
+    }
+    /// Skip over a declaration of something that isn't a type, variables and functions have no
+    /// layout worth keeping. Function bodies are skipped whole
+    ///
+    fn skip_declaration(&mut self) -> anyhow::Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.next("';'")? {
+                TokenKind::Symbol("(") | TokenKind::Symbol("{") => depth += 1,
+                TokenKind::Symbol(")") => depth -= 1,
+                TokenKind::Symbol("}") => {
+                    depth -= 1;
+                    if depth == 0 && !self.is_symbol(";") {
+                        return Ok(());
+                    }
+                }
+                TokenKind::Symbol(";") if depth == 0 => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+    fn parse(&mut self) -> anyhow::Result<()> {
+        while let Some(kind) = self.peek().cloned() {
+            if self.take_directive() {
+                continue;
+            }
+            match kind {
+                // Stray closing braces are left over from extern "C" blocks
+                TokenKind::Symbol(";") | TokenKind::Symbol("}") => self.pos += 1,
+                TokenKind::Ident(ident)
+                    if ident == "extern" && self.peek_at(1) == Some(&TokenKind::Symbol("{")) =>
+                {
+                    self.pos += 2
+                }
+                TokenKind::Ident(ident) if ident == "typedef" => {
+                    self.pos += 1;
+                    self.parse_typedef()?;
+                }
+                _ => {
+                    self.parse_type_spec()?;
+                    if !self.eat_symbol(";") {
+                        self.skip_declaration()?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse C declarations into a library, handing back the names of every type they defined. Only
+/// what's needed to lay types out is understood: structs, unions, enums, typedefs, arrays,
+/// pointers, #pragma pack, packed attributes and constants from #define
+///
+pub fn parse_declarations(text: &str, library: &mut TypeLibrary) -> anyhow::Result<Vec<String>> {
+    let mut parser = Parser::new(tokenize(text)?, library);
+    parser.parse()?;
+    Ok(parser.defined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(text: &str, pointer_size: usize, long_size: usize) -> Vec<(usize, usize)> {
+        let mut library = TypeLibrary::new();
+        library.pointer_size = pointer_size;
+        library.long_size = long_size;
+        parse_declarations(text, &mut library).unwrap();
+
+        let record = library.record("Sample").unwrap();
+        record
+            .fields
+            .iter()
+            .map(|field| (field.offset, library.size_of(&field.ctype).unwrap()))
+            .collect()
+    }
+
+    const SAMPLE: &str = "struct Sample { int a; long b; unsigned long c; long long d; LONG e; };";
+
+    #[test]
+    fn longs_follow_the_data_model() {
+        // LLP64 (Windows) and ILP32
+        assert_eq!(
+            layout(SAMPLE, 8, 4),
+            [(0, 4), (4, 4), (8, 4), (16, 8), (24, 4)]
+        );
+        // LP64
+        assert_eq!(
+            layout(SAMPLE, 8, 8),
+            [(0, 4), (8, 8), (16, 8), (24, 8), (32, 4)]
+        );
+    }
+}
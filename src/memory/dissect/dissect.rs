@@ -0,0 +1,504 @@
+use crate::memory::ctypes::ctypes::read_integer;
+use crate::memory::process::types::{MemoryRegion, ModuleInfo};
+use crate::memory::scan::compare::extract_value;
+use crate::memory::scan::scannable::{StructField, StructType};
+use crate::memory::scan::types::ValueType;
+use crate::memory::source::source::MemorySource;
+use thiserror::Error;
+
+/// Number of bytes read from wherever a string pointer leads, longer strings are cut off
+///
+const STRING_PREVIEW_LENGTH: usize = 64;
+
+/// Shortest run of printable characters a pointer has to lead to before it's taken as a string
+///
+const MIN_STRING_LENGTH: usize = 4;
+
+/// Number of leading entries that have to point into code before a pointer is taken as a vtable
+///
+const VTABLE_ENTRIES: usize = 2;
+
+/// Errors that can come out of dissecting memory
+///
+#[derive(Error, Debug)]
+pub enum DissectErrors {
+    #[error("Nothing to dissect, the size is zero")]
+    EmptySize,
+    #[error("A {kind} at +0x{offset:X} runs past the end of the structure")]
+    OutOfBounds { kind: String, offset: usize },
+    #[error("Only pointers can be expanded, +0x{offset:X} is a {kind}")]
+    NotAPointer { kind: String, offset: usize },
+}
+
+/// What a field of a dissected structure is taken to be, either guessed or picked by hand
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldKind {
+    /// Pointer to data within a module or on the heap
+    Pointer,
+    /// Pointer to a table of functions, the first field of anything with virtual methods
+    VTable,
+    /// Pointer to a null terminated string, wide strings are UTF-16
+    StringPointer { wide: bool },
+    /// Anything that isn't a pointer, floats and integers alike
+    Value(ValueType),
+}
+
+impl FieldKind {
+    /// Every kind a field can be set to, registered types included so structs can be nested
+    ///
+    pub fn all() -> Vec<FieldKind> {
+        let mut all = vec![
+            FieldKind::Pointer,
+            FieldKind::VTable,
+            FieldKind::StringPointer { wide: false },
+            FieldKind::StringPointer { wide: true },
+        ];
+        all.extend(
+            ValueType::all()
+                .into_iter()
+                .filter(|value_type| value_type.scannable().is_some())
+                .map(FieldKind::Value),
+        );
+        all
+    }
+    pub fn label(&self) -> String {
+        match self {
+            FieldKind::Pointer => "Pointer".to_string(),
+            FieldKind::VTable => "VTable".to_string(),
+            FieldKind::StringPointer { wide: false } => "String pointer".to_string(),
+            FieldKind::StringPointer { wide: true } => "Wide string pointer".to_string(),
+            FieldKind::Value(value_type) => value_type.label().to_string(),
+        }
+    }
+    /// Whether the field holds an address that can be followed
+    ///
+    pub fn is_pointer(&self) -> bool {
+        !matches!(self, FieldKind::Value(_))
+    }
+    /// Number of bytes the field takes up, pointers are as wide as the target's
+    ///
+    pub fn size(&self, pointer_size: usize) -> usize {
+        match self {
+            FieldKind::Value(value_type) => value_type.size(),
+            _ => pointer_size,
+        }
+    }
+    /// Type the field is saved as within a struct, pointers are kept as integers as wide as they
+    /// are since structs don't lead anywhere
+    ///
+    pub fn value_type(&self, pointer_size: usize) -> ValueType {
+        match self {
+            FieldKind::Value(value_type) => value_type.clone(),
+            _ if pointer_size == 8 => ValueType::EightBytes,
+            _ => ValueType::FourBytes,
+        }
+    }
+}
+
+/// Where everything sits within the target, used to tell pointers apart from other values
+///
+#[derive(Clone, Debug, Default)]
+pub struct MemoryMap {
+    /// Readable regions in address order
+    regions: Vec<MemoryRegion>,
+    modules: Vec<ModuleInfo>,
+    pub pointer_size: usize,
+}
+
+impl MemoryMap {
+    /// Take in the regions and modules of the target as they are right now
+    ///
+    pub fn read(source: &mut dyn MemorySource) -> anyhow::Result<Self> {
+        let mut regions: Vec<MemoryRegion> = source
+            .regions()?
+            .into_iter()
+            .filter(|region| region.is_readable())
+            .collect();
+        regions.sort_by_key(|region| region.base_address);
+
+        Ok(Self {
+            regions,
+            modules: source.modules()?,
+            pointer_size: (source.bitness() as usize / 8).max(4),
+        })
+    }
+    fn region(&self, address: u64) -> Option<&MemoryRegion> {
+        let idx = self
+            .regions
+            .partition_point(|region| region.base_address <= address);
+        self.regions
+            .get(idx.checked_sub(1)?)
+            .filter(|region| region.contains(address))
+    }
+    fn module(&self, address: u64) -> Option<&ModuleInfo> {
+        self.modules.iter().find(|module| module.contains(address))
+    }
+    /// Check if a value leads somewhere that can be read
+    ///
+    pub fn is_pointer(&self, address: u64) -> bool {
+        self.region(address).is_some()
+    }
+    /// Check if an address lies within code that can be run
+    ///
+    pub fn is_code(&self, address: u64) -> bool {
+        self.region(address)
+            .is_some_and(|region| region.is_executable())
+    }
+    /// Describe where an address leads, e.g. game.exe+0x1A20, anything outside a module is heap
+    ///
+    pub fn describe(&self, address: u64) -> String {
+        match self.module(address) {
+            Some(module) => format!("{}+0x{:X}", module.name, address - module.base_address),
+            None => "heap".to_string(),
+        }
+    }
+}
+
+/// Number of printable characters a string starts with, as long as it's null terminated or runs
+/// off the end of what was read
+///
+fn printable_length(characters: impl Iterator<Item = u16>) -> usize {
+    let mut length = 0;
+    for character in characters {
+        match character {
+            0 => return length,
+            0x20..=0x7E | 0x09 | 0x0A | 0x0D => length += 1,
+            _ => return 0,
+        }
+    }
+    length
+}
+
+/// Read the start of a string a pointer leads to, None if it isn't printable
+///
+fn read_string(source: &mut dyn MemorySource, address: u64, wide: bool) -> Option<String> {
+    let bytes = source
+        .read_bytes(address as usize, STRING_PREVIEW_LENGTH)
+        .ok()?;
+    let text = match wide {
+        true => {
+            let characters: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            let length = printable_length(characters.iter().copied());
+            String::from_utf16_lossy(&characters[..length])
+        }
+        false => {
+            let length = printable_length(bytes.iter().map(|&byte| byte as u16));
+            String::from_utf8_lossy(&bytes[..length]).into_owned()
+        }
+    };
+
+    (text.len() >= MIN_STRING_LENGTH).then_some(text)
+}
+
+/// Work out what a pointer leads to. Vtables sit within a module and start off with pointers into
+/// its code, failing that it may lead to a string
+///
+fn guess_pointer(source: &mut dyn MemorySource, map: &MemoryMap, pointer: u64) -> FieldKind {
+    let pointer_size = map.pointer_size;
+    if map.module(pointer).is_some() {
+        if let Ok(entries) = source.read_bytes(pointer as usize, pointer_size * VTABLE_ENTRIES) {
+            if entries
+                .chunks_exact(pointer_size)
+                .all(|entry| map.is_code(read_integer(entry)))
+            {
+                return FieldKind::VTable;
+            }
+        }
+    }
+
+    for wide in [false, true] {
+        if read_string(source, pointer, wide).is_some() {
+            return FieldKind::StringPointer { wide };
+        }
+    }
+    FieldKind::Pointer
+}
+
+/// Whether a float is one a program would plausibly hold, rather than an integer read as one
+///
+fn is_plausible_float(value: f64) -> bool {
+    value.is_normal() && (1e-3..=1e7).contains(&value.abs())
+}
+
+/// Guess what the field at an offset is from the bytes left from there to the end of the
+/// structure. Pointers are only looked for at aligned offsets, small integers win out over floats
+/// and a double has to not look like a pair of floats
+///
+fn guess_kind(
+    source: &mut dyn MemorySource,
+    map: &MemoryMap,
+    bytes: &[u8],
+    offset: usize,
+) -> FieldKind {
+    let pointer_size = map.pointer_size;
+    if offset.is_multiple_of(pointer_size) && bytes.len() >= pointer_size {
+        let pointer = read_integer(&bytes[..pointer_size]);
+        if map.is_pointer(pointer) {
+            return guess_pointer(source, map, pointer);
+        }
+    }
+
+    if offset.is_multiple_of(8) && bytes.len() >= 8 {
+        let double = f64::from_bits(read_integer(&bytes[..8]));
+        let low = f32::from_bits(read_integer(&bytes[..4]) as u32);
+        if is_plausible_float(double) && !is_plausible_float(low as f64) {
+            return FieldKind::Value(ValueType::Double);
+        }
+    }
+
+    if offset.is_multiple_of(4) && bytes.len() >= 4 {
+        let raw = read_integer(&bytes[..4]) as u32;
+        return match raw as i32 {
+            -0xFFFFF..=0xFFFFF => FieldKind::Value(ValueType::SignedFourBytes),
+            _ if is_plausible_float(f32::from_bits(raw) as f64) => {
+                FieldKind::Value(ValueType::Float)
+            }
+            _ => FieldKind::Value(ValueType::FourBytes),
+        };
+    }
+
+    FieldKind::Value(ValueType::Byte)
+}
+
+/// A single field of a dissected structure
+///
+#[derive(Clone, Debug)]
+pub struct DissectField {
+    pub offset: usize,
+    /// Left empty until it's named, the offset is shown in its place
+    pub name: String,
+    pub kind: FieldKind,
+    /// Value as it was last read, pointers show where they lead
+    pub value: String,
+    /// Structure a pointer has been followed into
+    pub expanded: Option<Dissection>,
+}
+
+impl DissectField {
+    fn new(offset: usize, kind: FieldKind) -> Self {
+        Self {
+            offset,
+            name: String::new(),
+            kind,
+            value: String::new(),
+            expanded: None,
+        }
+    }
+    /// Name the field goes by, its offset if it hasn't been named
+    ///
+    pub fn display_name(&self) -> String {
+        match self.name.trim() {
+            "" => format!("+0x{:X}", self.offset),
+            name => name.to_string(),
+        }
+    }
+}
+
+/// A block of memory split up into the fields it's guessed to be made of
+///
+#[derive(Clone, Debug)]
+pub struct Dissection {
+    pub address: u64,
+    pub size: usize,
+    pub pointer_size: usize,
+    /// Bytes as they were last read
+    pub bytes: Vec<u8>,
+    /// Kept in offset order, covering every byte of the structure
+    pub fields: Vec<DissectField>,
+}
+
+impl Dissection {
+    /// Read a block of memory and guess a field for every offset of it, pages that can't be read
+    /// come back as zeros
+    ///
+    pub fn read(
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+        address: u64,
+        size: usize,
+    ) -> anyhow::Result<Self> {
+        if size == 0 {
+            return Err(DissectErrors::EmptySize.into());
+        }
+
+        let mut dissection = Self {
+            address,
+            size,
+            pointer_size: map.pointer_size,
+            bytes: source.read_bytes_paged(address as usize, size)?,
+            fields: Vec::new(),
+        };
+        dissection.fields = dissection.guess_fields(source, map, 0, size);
+        Ok(dissection)
+    }
+    /// Guess every field between two offsets
+    ///
+    fn guess_fields(
+        &self,
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+        start: usize,
+        end: usize,
+    ) -> Vec<DissectField> {
+        let mut fields = Vec::new();
+        let mut offset = start;
+
+        while offset < end {
+            let kind = guess_kind(source, map, &self.bytes[offset..end], offset);
+            let mut field = DissectField::new(offset, kind);
+            field.value = self.describe(source, map, &field);
+            offset += field.kind.size(self.pointer_size);
+            fields.push(field);
+        }
+        fields
+    }
+    /// Address a pointer field holds
+    ///
+    pub fn pointer_at(&self, field: &DissectField) -> u64 {
+        read_integer(&self.bytes[field.offset..field.offset + self.pointer_size])
+    }
+    /// Turn the field's current bytes into text
+    ///
+    fn describe(
+        &self,
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+        field: &DissectField,
+    ) -> String {
+        let pointer = || self.pointer_at(field);
+        match &field.kind {
+            FieldKind::Value(value_type) => extract_value(
+                &self.bytes[field.offset..field.offset + value_type.size()],
+                value_type,
+            ),
+            FieldKind::StringPointer { wide } => match read_string(source, pointer(), *wide) {
+                Some(text) => format!("0x{:X} \"{}\"", pointer(), text),
+                None => format!("0x{:X} (not a string)", pointer()),
+            },
+            FieldKind::Pointer | FieldKind::VTable => {
+                format!("0x{:X} ({})", pointer(), map.describe(pointer()))
+            }
+        }
+    }
+    /// Read the structure again keeping every field as it is, expanded pointers are followed to
+    /// wherever they lead now
+    ///
+    pub fn refresh(
+        &mut self,
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+    ) -> anyhow::Result<()> {
+        self.bytes = source.read_bytes_paged(self.address as usize, self.size)?;
+
+        for idx in 0..self.fields.len() {
+            let value = self.describe(source, map, &self.fields[idx]);
+            let pointer = match self.fields[idx].kind.is_pointer() {
+                true => Some(self.pointer_at(&self.fields[idx])),
+                false => None,
+            };
+            let field = &mut self.fields[idx];
+            field.value = value;
+
+            if let (Some(nested), Some(pointer)) = (field.expanded.as_mut(), pointer) {
+                nested.address = pointer;
+                nested.refresh(source, map)?;
+            }
+        }
+        Ok(())
+    }
+    /// Override what a field is. Any fields it now covers are taken over and whatever gap it
+    /// leaves behind is guessed again, names are kept
+    ///
+    pub fn set_kind(
+        &mut self,
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+        index: usize,
+        kind: FieldKind,
+    ) -> anyhow::Result<()> {
+        let offset = self.fields[index].offset;
+        let end = offset + kind.size(self.pointer_size);
+        if end > self.size {
+            return Err(DissectErrors::OutOfBounds {
+                kind: kind.label(),
+                offset,
+            }
+            .into());
+        }
+
+        let next = self.fields[index + 1..]
+            .iter()
+            .position(|field| field.offset >= end)
+            .map(|position| index + 1 + position)
+            .unwrap_or(self.fields.len());
+        let gap_end = self
+            .fields
+            .get(next)
+            .map(|field| field.offset)
+            .unwrap_or(self.size);
+
+        let mut field = DissectField::new(offset, kind);
+        field.name = std::mem::take(&mut self.fields[index].name);
+        field.value = self.describe(source, map, &field);
+
+        let mut replaced = vec![field];
+        replaced.extend(self.guess_fields(source, map, end, gap_end));
+        self.fields.splice(index..next, replaced);
+        Ok(())
+    }
+    /// Follow a pointer field and dissect whatever it leads to
+    ///
+    pub fn expand(
+        &mut self,
+        source: &mut dyn MemorySource,
+        map: &MemoryMap,
+        index: usize,
+        size: usize,
+    ) -> anyhow::Result<()> {
+        let field = &self.fields[index];
+        if !field.kind.is_pointer() {
+            return Err(DissectErrors::NotAPointer {
+                kind: field.kind.label(),
+                offset: field.offset,
+            }
+            .into());
+        }
+
+        let nested = Dissection::read(source, map, self.pointer_at(field), size)?;
+        self.fields[index].expanded = Some(nested);
+        Ok(())
+    }
+    /// Find a structure that was expanded into, by the index of each field followed to get there
+    ///
+    pub fn nested_mut(&mut self, path: &[usize]) -> Option<&mut Dissection> {
+        match path.split_first() {
+            None => Some(self),
+            Some((index, rest)) => self
+                .fields
+                .get_mut(*index)?
+                .expanded
+                .as_mut()?
+                .nested_mut(rest),
+        }
+    }
+    /// Save the layout as a struct type. Structs don't lead anywhere so pointers are kept as
+    /// integers and whatever they were expanded into is left out
+    ///
+    pub fn to_struct_type(&self, name: &str) -> anyhow::Result<StructType> {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| StructField {
+                name: field.display_name(),
+                offset: field.offset,
+                value_type: field.kind.value_type(self.pointer_size),
+            })
+            .collect();
+
+        StructType::new(name, self.size, fields)
+    }
+}
@@ -0,0 +1,282 @@
+use crate::memory::process::types::*;
+use crate::memory::process::ProcessErrors;
+use crate::memory::source::source::{MemorySource, SourceErrors};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Read a little endian u16 out of a buffer, dump parsers work on untrusted files so every read
+/// is bounds checked rather than indexed directly
+///
+pub fn read_u16(bytes: &[u8], offset: usize) -> anyhow::Result<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| anyhow::anyhow!("Truncated read of u16 at offset 0x{:X}", offset))
+}
+
+/// Read a little endian u32 out of a buffer
+///
+pub fn read_u32(bytes: &[u8], offset: usize) -> anyhow::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| anyhow::anyhow!("Truncated read of u32 at offset 0x{:X}", offset))
+}
+
+/// Read a little endian u64 out of a buffer
+///
+pub fn read_u64(bytes: &[u8], offset: usize) -> anyhow::Result<u64> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+        .ok_or_else(|| anyhow::anyhow!("Truncated read of u64 at offset 0x{:X}", offset))
+}
+
+/// Read a chunk of a file at a given offset, sizes come out of the file itself so they're checked
+/// against its length before anything is allocated for them
+///
+pub fn read_file_at(file: &mut File, offset: u64, size: usize) -> anyhow::Result<Vec<u8>> {
+    let length = file.metadata()?.len();
+    if offset
+        .checked_add(size as u64)
+        .is_none_or(|end| end > length)
+    {
+        return Err(anyhow::anyhow!(
+            "Truncated read of 0x{:X} bytes at offset 0x{:X}",
+            size,
+            offset
+        ));
+    }
+
+    let mut buffer = vec![0u8; size];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Number of entries a table read out of a file holds, this has to fit within the bytes
+/// available for it so a corrupt count can't have us allocating or looping over billions of them
+///
+pub fn checked_count(count: u64, available: usize, entry_size: usize) -> anyhow::Result<usize> {
+    if count > (available / entry_size.max(1)) as u64 {
+        return Err(anyhow::anyhow!(
+            "{} entries of 0x{:X} bytes don't fit within 0x{:X} bytes",
+            count,
+            entry_size,
+            available
+        ));
+    }
+    Ok(count as usize)
+}
+
+/// Modules are stored with their full path on the machine the dump came from, which may well use
+/// either kind of separator
+///
+pub fn file_name_of(path: &str) -> String {
+    path.rsplit(['\\', '/'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A range of the target's memory that was captured into the dump file, address is where it lived
+/// within the target and file_offset is where its bytes sit within the file
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DumpRange {
+    pub address: u64,
+    pub size: u64,
+    pub file_offset: u64,
+    /// Size of the range within the file when it's stored lz4 compressed, None if it's stored
+    /// as is
+    pub compressed_size: Option<u64>,
+}
+
+/// A read-only target backed by a dump file on disk, every dump format boils down to a list of
+/// captured ranges plus whatever region, module and thread information it carried with it so they
+/// all parse into this. Bytes are only ever read from the file on demand since dumps can easily be
+/// several gigabytes
+///
+#[derive(Debug)]
+pub struct DumpTarget {
+    name: String,
+    file: File,
+    ranges: Vec<DumpRange>,
+    regions: Vec<MemoryRegion>,
+    modules: Vec<ModuleInfo>,
+    threads: Vec<ThreadInfo>,
+    bitness: u32,
+    windows: bool,
+    /// The last compressed range read along with its bytes, reads tend to walk through a range
+    /// so this saves decompressing it for every one of them
+    cached: Option<(u64, Vec<u8>)>,
+}
+
+impl DumpTarget {
+    /// Build the target up from everything a parser pulled out of the file, if the dump didn't
+    /// carry any region information we'll make it up from the captured ranges instead
+    ///
+    pub fn new(
+        name: String,
+        file: File,
+        mut ranges: Vec<DumpRange>,
+        mut regions: Vec<MemoryRegion>,
+        modules: Vec<ModuleInfo>,
+        threads: Vec<ThreadInfo>,
+    ) -> Self {
+        ranges.retain(|range| range.size != 0);
+        ranges.sort_by_key(|range| range.address);
+
+        if regions.is_empty() {
+            regions = ranges
+                .iter()
+                .map(|range| {
+                    let in_module = modules.iter().any(|module| module.contains(range.address));
+                    MemoryRegion {
+                        base_address: range.address,
+                        region_size: range.size,
+                        protect: if in_module {
+                            PAGE_EXECUTE_READ
+                        } else {
+                            PAGE_READWRITE
+                        },
+                        state: MEM_COMMIT,
+                        region_type: if in_module { MEM_IMAGE } else { MEM_PRIVATE },
+                    }
+                })
+                .collect();
+        }
+
+        Self {
+            name,
+            file,
+            ranges,
+            regions,
+            modules,
+            threads,
+            bitness: 64,
+            windows: cfg!(windows),
+            cached: None,
+        }
+    }
+    /// Set the bitness of the target, dumps default to 64-bit unless the parser knows otherwise
+    ///
+    pub fn with_bitness(mut self, bitness: u32) -> Self {
+        self.bitness = bitness;
+        self
+    }
+    /// Set whether the dump was taken on Windows, otherwise it's assumed to match our platform
+    ///
+    pub fn with_windows(mut self, windows: bool) -> Self {
+        self.windows = windows;
+        self
+    }
+    /// Bytes of a compressed range, decompressed only if it isn't the one we last read from
+    ///
+    fn decompressed(&mut self, range: &DumpRange, compressed_size: u64) -> anyhow::Result<&[u8]> {
+        let cached = self
+            .cached
+            .as_ref()
+            .is_some_and(|(file_offset, _)| *file_offset == range.file_offset);
+
+        if !cached {
+            let compressed =
+                read_file_at(&mut self.file, range.file_offset, compressed_size as usize)?;
+            let bytes = lz4_flex::decompress(&compressed, range.size as usize)?;
+            if bytes.len() as u64 != range.size {
+                return Err(anyhow::anyhow!(
+                    "Range at 0x{:X} decompressed to 0x{:X} bytes, expected 0x{:X}",
+                    range.address,
+                    bytes.len(),
+                    range.size
+                ));
+            }
+            self.cached = Some((range.file_offset, bytes));
+        }
+
+        Ok(self.cached.as_ref().map_or(&[], |(_, bytes)| bytes))
+    }
+    /// Find the captured range an address falls into
+    ///
+    fn locate(&self, address: u64) -> Option<DumpRange> {
+        let idx = self
+            .ranges
+            .partition_point(|range| range.address + range.size <= address);
+        self.ranges
+            .get(idx)
+            .filter(|range| range.address <= address)
+            .copied()
+    }
+}
+
+impl MemorySource for DumpTarget {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    /// Reads may span neighbouring ranges but every byte has to have been captured
+    ///
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = vec![0u8; size];
+        let mut read = 0;
+
+        while read < size {
+            let current = address as u64 + read as u64;
+            let range = self
+                .locate(current)
+                .ok_or(SourceErrors::Unmapped { address: current })?;
+            let offset = current - range.address;
+            let to_read = std::cmp::min((size - read) as u64, range.size - offset) as usize;
+
+            match range.compressed_size {
+                Some(compressed_size) => {
+                    let offset = offset as usize;
+                    let bytes = self.decompressed(&range, compressed_size)?;
+                    buffer[read..read + to_read].copy_from_slice(&bytes[offset..offset + to_read]);
+                }
+                None => {
+                    self.file
+                        .seek(SeekFrom::Start(range.file_offset + offset))?;
+                    self.file.read_exact(&mut buffer[read..read + to_read])?;
+                }
+            }
+            read += to_read;
+        }
+
+        Ok(buffer)
+    }
+    fn write_bytes(&mut self, _address: usize, _bytes: &[u8]) -> anyhow::Result<()> {
+        Err(SourceErrors::ReadOnly {
+            target_name: self.name.clone(),
+        })?
+    }
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+        Ok(self.regions.clone())
+    }
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+        Ok(self.modules.clone())
+    }
+    fn base(&mut self) -> anyhow::Result<u64> {
+        self.modules
+            .iter()
+            .find(|module| module.name == self.name)
+            .map(|module| module.base_address)
+            .ok_or_else(|| {
+                ProcessErrors::ModuleNotFound {
+                    module_name: self.name.clone(),
+                }
+                .into()
+            })
+    }
+    fn threads(&mut self) -> anyhow::Result<Vec<ThreadInfo>> {
+        Ok(self.threads.clone())
+    }
+    fn is_read_only(&self) -> bool {
+        true
+    }
+    fn bitness(&self) -> u32 {
+        self.bitness
+    }
+    fn is_windows(&self) -> bool {
+        self.windows
+    }
+}
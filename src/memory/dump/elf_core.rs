@@ -0,0 +1,514 @@
+use super::dump::{
+    checked_count, file_name_of, read_file_at, read_u16, read_u32, read_u64, DumpRange, DumpTarget,
+};
+use crate::memory::process::types::*;
+use std::fs::File;
+
+/// "\x7fELF" as it appears at the very start of every ELF file
+///
+pub const ELF_MAGIC: u32 = 0x464C_457F;
+
+/// Identification bytes and the e_type we accept, we only handle little endian core files
+///
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ET_CORE: u16 = 4;
+
+/// e_machine values we know how to pull registers out of
+///
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+/// Program header types and segment permission flags
+///
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+/// Note types the kernel writes into core files
+///
+const NT_PRSTATUS: u32 = 1;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x4649_4C45;
+
+/// Auxiliary vector entry holding the main executable's entry point, we use it to work out which
+/// mapped file is the main module
+///
+const AT_ENTRY: u64 = 9;
+
+/// Offsets within elf_prstatus, the thread id and the start of the saved general purpose registers
+/// differ between 32 and 64 bit since the signal sets and timevals shrink
+///
+const PRSTATUS64_PID_OFFSET: usize = 32;
+const PRSTATUS64_REGS_OFFSET: usize = 112;
+const PRSTATUS32_PID_OFFSET: usize = 24;
+const PRSTATUS32_REGS_OFFSET: usize = 72;
+
+/// Register indexes within each architecture's user_regs_struct
+///
+const X86_64_RIP_INDEX: usize = 16;
+const X86_64_RSP_INDEX: usize = 19;
+const X86_64_FS_BASE_INDEX: usize = 21;
+const AARCH64_SP_INDEX: usize = 31;
+const AARCH64_PC_INDEX: usize = 32;
+const I386_EIP_INDEX: usize = 12;
+const I386_ESP_INDEX: usize = 15;
+
+/// The parts of the ELF header we need, word sized fields are widened so the rest of the parser
+/// doesn't care about the class
+///
+struct ElfHeader {
+    is_64: bool,
+    machine: u16,
+    phoff: u64,
+    phentsize: usize,
+    phnum: usize,
+}
+
+/// A single program header
+///
+struct ProgramHeader {
+    p_type: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    filesz: u64,
+    memsz: u64,
+}
+
+/// A single entry of the NT_FILE note, a file mapped into the process at the time it crashed
+///
+struct FileMapping {
+    start: u64,
+    end: u64,
+    path: String,
+}
+
+/// Read a word sized value, these are u32 on 32 bit cores and u64 on 64 bit ones
+///
+fn read_word(bytes: &[u8], offset: usize, is_64: bool) -> anyhow::Result<u64> {
+    if is_64 {
+        read_u64(bytes, offset)
+    } else {
+        read_u32(bytes, offset).map(u64::from)
+    }
+}
+
+/// Parse the ELF header, making sure we've actually been handed a little endian core file
+///
+fn parse_header(bytes: &[u8]) -> anyhow::Result<ElfHeader> {
+    let is_64 = match bytes.get(4) {
+        Some(&ELFCLASS64) => true,
+        Some(&ELFCLASS32) => false,
+        _ => return Err(anyhow::anyhow!("Unknown ELF class")),
+    };
+    if bytes.get(5) != Some(&ELFDATA2LSB) {
+        return Err(anyhow::anyhow!(
+            "Only little endian ELF files are supported"
+        ));
+    }
+    if read_u16(bytes, 16)? != ET_CORE {
+        return Err(anyhow::anyhow!("ELF file is not a core dump"));
+    }
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (
+            read_u64(bytes, 32)?,
+            read_u16(bytes, 54)?,
+            read_u16(bytes, 56)?,
+        )
+    } else {
+        (
+            read_u32(bytes, 28)? as u64,
+            read_u16(bytes, 42)?,
+            read_u16(bytes, 44)?,
+        )
+    };
+
+    Ok(ElfHeader {
+        is_64,
+        machine: read_u16(bytes, 18)?,
+        phoff,
+        phentsize: phentsize as usize,
+        phnum: phnum as usize,
+    })
+}
+
+/// Parse every program header out of the program header table
+///
+fn parse_program_headers(table: &[u8], header: &ElfHeader) -> anyhow::Result<Vec<ProgramHeader>> {
+    (0..header.phnum)
+        .map(|i| {
+            let entry = i * header.phentsize;
+            if header.is_64 {
+                Ok(ProgramHeader {
+                    p_type: read_u32(table, entry)?,
+                    flags: read_u32(table, entry + 4)?,
+                    offset: read_u64(table, entry + 8)?,
+                    vaddr: read_u64(table, entry + 16)?,
+                    filesz: read_u64(table, entry + 32)?,
+                    memsz: read_u64(table, entry + 40)?,
+                })
+            } else {
+                Ok(ProgramHeader {
+                    p_type: read_u32(table, entry)?,
+                    offset: read_u32(table, entry + 4)? as u64,
+                    vaddr: read_u32(table, entry + 8)? as u64,
+                    filesz: read_u32(table, entry + 16)? as u64,
+                    memsz: read_u32(table, entry + 20)? as u64,
+                    flags: read_u32(table, entry + 24)?,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Walk a note segment, handing back the type and descriptor of each note. Names and descriptors
+/// are both padded out to 4 bytes within core files
+///
+fn parse_notes(segment: &[u8]) -> anyhow::Result<Vec<(u32, &[u8])>> {
+    let align = |size: usize| (size + 3) & !3;
+    let mut notes = Vec::new();
+    let mut offset = 0;
+
+    while offset + 12 <= segment.len() {
+        let name_size = read_u32(segment, offset)? as usize;
+        let desc_size = read_u32(segment, offset + 4)? as usize;
+        let note_type = read_u32(segment, offset + 8)?;
+        let desc_start = offset + 12 + align(name_size);
+
+        let desc = segment
+            .get(desc_start..desc_start + desc_size)
+            .ok_or_else(|| anyhow::anyhow!("Truncated note at offset 0x{:X}", offset))?;
+        notes.push((note_type, desc));
+        offset = desc_start + align(desc_size);
+    }
+
+    Ok(notes)
+}
+
+/// Parse the NT_FILE note, this is a count and page size followed by a (start, end, offset) triple
+/// per mapping and then all of the null terminated paths back to back
+///
+fn parse_file_note(desc: &[u8], is_64: bool) -> anyhow::Result<Vec<FileMapping>> {
+    let word = if is_64 { 8 } else { 4 };
+    let count = read_word(desc, 0, is_64)?;
+    let count = checked_count(count, desc.len().saturating_sub(word * 2), word * 3)?;
+    let mut names = desc
+        .get(word * 2 + count * word * 3..)
+        .unwrap_or_default()
+        .split(|&byte| byte == 0);
+
+    (0..count)
+        .map(|i| {
+            let entry = word * 2 + i * word * 3;
+            Ok(FileMapping {
+                start: read_word(desc, entry, is_64)?,
+                end: read_word(desc, entry + word, is_64)?,
+                path: String::from_utf8_lossy(names.next().unwrap_or_default()).to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse a thread out of an NT_PRSTATUS note, pulling the instruction and stack pointer out of the
+/// saved registers if we understand the architecture. There's no TEB on linux so we hand back the
+/// fs base instead on x86_64, it's what points at the thread's TLS block
+///
+fn parse_thread(desc: &[u8], header: &ElfHeader) -> anyhow::Result<ThreadInfo> {
+    let (pid_offset, regs_offset) = if header.is_64 {
+        (PRSTATUS64_PID_OFFSET, PRSTATUS64_REGS_OFFSET)
+    } else {
+        (PRSTATUS32_PID_OFFSET, PRSTATUS32_REGS_OFFSET)
+    };
+    let register = |index: usize| read_word(desc, regs_offset + index * 8, true).ok();
+
+    let (instruction_pointer, stack_pointer, teb) = match header.machine {
+        EM_X86_64 => (
+            register(X86_64_RIP_INDEX),
+            register(X86_64_RSP_INDEX),
+            register(X86_64_FS_BASE_INDEX).unwrap_or_default(),
+        ),
+        EM_AARCH64 => (register(AARCH64_PC_INDEX), register(AARCH64_SP_INDEX), 0),
+        EM_386 => (
+            read_u32(desc, regs_offset + I386_EIP_INDEX * 4)
+                .ok()
+                .map(u64::from),
+            read_u32(desc, regs_offset + I386_ESP_INDEX * 4)
+                .ok()
+                .map(u64::from),
+            0,
+        ),
+        _ => (None, None, 0),
+    };
+
+    Ok(ThreadInfo {
+        thread_id: read_u32(desc, pid_offset)?,
+        teb,
+        instruction_pointer,
+        stack_pointer,
+        ..Default::default()
+    })
+}
+
+/// Group every mapped file into a module spanning from its lowest to highest mapped address, same
+/// as we do for live linux processes. Mappings that end before they start only turn up in corrupt
+/// cores and are skipped
+///
+fn build_modules(mappings: &[FileMapping]) -> Vec<ModuleInfo> {
+    let mut modules: Vec<ModuleInfo> = Vec::new();
+
+    for mapping in mappings {
+        let Some(size) = mapping.end.checked_sub(mapping.start) else {
+            continue;
+        };
+
+        match modules
+            .iter_mut()
+            .find(|module| module.path == mapping.path)
+        {
+            Some(module) => {
+                let end = std::cmp::max(module.base_address + module.size, mapping.end);
+                module.base_address = std::cmp::min(module.base_address, mapping.start);
+                module.size = end - module.base_address;
+            }
+            None => modules.push(ModuleInfo {
+                name: file_name_of(&mapping.path),
+                path: mapping.path.clone(),
+                base_address: mapping.start,
+                size,
+            }),
+        }
+    }
+
+    modules
+}
+
+/// Map a segment's rwx flags onto the PAGE_* protection values our views understand
+///
+fn protection_of(flags: u32) -> u32 {
+    match (flags & PF_R != 0, flags & PF_W != 0, flags & PF_X != 0) {
+        (false, false, false) => PAGE_NOACCESS,
+        (true, false, false) => PAGE_READONLY,
+        (_, true, false) => PAGE_READWRITE,
+        (false, false, true) => PAGE_EXECUTE,
+        (true, false, true) => PAGE_EXECUTE_READ,
+        (_, true, true) => PAGE_EXECUTE_READWRITE,
+    }
+}
+
+/// Open an ELF core file as a read-only target
+///
+pub fn open_elf_core(path: &str) -> anyhow::Result<DumpTarget> {
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let header_bytes = read_file_at(&mut file, 0, std::cmp::min(file_size, 64) as usize)?;
+    let header = parse_header(&header_bytes)?;
+
+    let table = read_file_at(&mut file, header.phoff, header.phnum * header.phentsize)?;
+    let program_headers = parse_program_headers(&table, &header)?;
+
+    // Walk the notes first since we need the file mappings to tell image segments apart from the
+    // rest of memory
+    let mut mappings = Vec::new();
+    let mut threads = Vec::new();
+    let mut entry_point = None;
+
+    for note_segment in program_headers.iter().filter(|ph| ph.p_type == PT_NOTE) {
+        let segment = read_file_at(&mut file, note_segment.offset, note_segment.filesz as usize)?;
+
+        for (note_type, desc) in parse_notes(&segment)? {
+            match note_type {
+                NT_PRSTATUS => threads.push(parse_thread(desc, &header)?),
+                NT_FILE => mappings.extend(parse_file_note(desc, header.is_64)?),
+                NT_AUXV => {
+                    let word = if header.is_64 { 16 } else { 8 };
+                    entry_point = (0..desc.len() / word)
+                        .filter_map(|i| {
+                            let key = read_word(desc, i * word, header.is_64).ok()?;
+                            let value = read_word(desc, i * word + word / 2, header.is_64).ok()?;
+                            (key == AT_ENTRY).then_some(value)
+                        })
+                        .next();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Segments that weren't dumped (file size of 0) still show up as regions so the layout
+    // matches the process, reading from them just fails the same way unreadable memory would
+    let mut ranges = Vec::new();
+    let mut regions = Vec::new();
+
+    for segment in program_headers.iter().filter(|ph| ph.p_type == PT_LOAD) {
+        if segment.filesz != 0 {
+            ranges.push(DumpRange {
+                address: segment.vaddr,
+                size: std::cmp::min(segment.filesz, segment.memsz),
+                file_offset: segment.offset,
+                compressed_size: None,
+            });
+        }
+
+        let is_image = mappings
+            .iter()
+            .any(|mapping| segment.vaddr >= mapping.start && segment.vaddr < mapping.end);
+
+        regions.push(MemoryRegion {
+            base_address: segment.vaddr,
+            region_size: segment.memsz,
+            protect: protection_of(segment.flags),
+            state: MEM_COMMIT,
+            region_type: if is_image { MEM_IMAGE } else { MEM_PRIVATE },
+        });
+    }
+
+    // Stacks aren't described anywhere within a core so we take whichever segment the stack
+    // pointer sits in
+    for thread in threads.iter_mut() {
+        if let Some(region) = thread
+            .stack_pointer
+            .and_then(|sp| regions.iter().find(|region| region.contains(sp)))
+        {
+            thread.stack_start = region.base_address;
+            thread.stack_size = region.region_size;
+        }
+    }
+
+    let mut modules = build_modules(&mappings);
+
+    // Without any file mappings there's nothing to name the main module after, so the segment
+    // holding the entry point (or failing that the lowest executable one) stands in for it under
+    // the core's own file name. That keeps base() and the views relying on it working
+    if modules.is_empty() {
+        let main_region = entry_point
+            .and_then(|entry| regions.iter().find(|region| region.contains(entry)))
+            .or_else(|| {
+                regions
+                    .iter()
+                    .filter(|region| region.is_executable())
+                    .min_by_key(|region| region.base_address)
+            });
+
+        if let Some(region) = main_region {
+            modules.push(ModuleInfo {
+                name: file_name_of(path),
+                path: path.to_string(),
+                base_address: region.base_address,
+                size: region.region_size,
+            });
+        }
+    }
+
+    // The main module is whichever file holds the entry point, fall back onto the first mapping
+    // and then the core's own file name
+    let name = entry_point
+        .and_then(|entry| modules.iter().find(|module| module.contains(entry)))
+        .or_else(|| modules.first())
+        .map(|module| module.name.clone())
+        .unwrap_or_else(|| file_name_of(path));
+
+    let bitness = if header.is_64 { 64 } else { 32 };
+
+    Ok(
+        DumpTarget::new(name, file, ranges, regions, modules, threads)
+            .with_bitness(bitness)
+            .with_windows(false),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::source::source::MemorySource;
+
+    /// Write out a 64 bit core with no file mappings, a data segment, two executable segments and
+    /// optionally an auxiliary vector naming the entry point, then open it
+    ///
+    fn open_unmapped_core(name: &str, entry_point: Option<u64>) -> anyhow::Result<DumpTarget> {
+        let mut notes = Vec::new();
+        if let Some(entry) = entry_point {
+            notes.extend(5u32.to_le_bytes());
+            notes.extend(32u32.to_le_bytes());
+            notes.extend(NT_AUXV.to_le_bytes());
+            notes.extend(b"CORE\0\0\0\0");
+            for word in [AT_ENTRY, entry, 0, 0] {
+                notes.extend(word.to_le_bytes());
+            }
+        }
+
+        let segments = [
+            (PT_NOTE, 0, 64 + 56 * 4, 0u64, notes.len() as u64),
+            (PT_LOAD, PF_R | PF_W, 0, 0x1000, 0),
+            (PT_LOAD, PF_R | PF_X, 0, 0x40_0000, 0),
+            (PT_LOAD, PF_R | PF_X, 0, 0x20_0000, 0),
+        ];
+
+        let mut file = ELF_MAGIC.to_le_bytes().to_vec();
+        file.extend([ELFCLASS64, ELFDATA2LSB]);
+        file.resize(16, 0);
+        file.extend(ET_CORE.to_le_bytes());
+        file.extend(EM_X86_64.to_le_bytes());
+        file.resize(32, 0);
+        file.extend(64u64.to_le_bytes());
+        file.resize(54, 0);
+        file.extend(56u16.to_le_bytes());
+        file.extend((segments.len() as u16).to_le_bytes());
+        file.resize(64, 0);
+
+        for (p_type, flags, offset, vaddr, filesz) in segments {
+            file.extend(p_type.to_le_bytes());
+            file.extend(flags.to_le_bytes());
+            file.extend((offset as u64).to_le_bytes());
+            file.extend(vaddr.to_le_bytes());
+            file.extend(vaddr.to_le_bytes());
+            file.extend(filesz.to_le_bytes());
+            file.extend(0x2000u64.to_le_bytes());
+            file.extend(0x1000u64.to_le_bytes());
+        }
+        file.extend(notes);
+
+        let path = std::env::temp_dir().join(format!("procvamp-{}-{}", std::process::id(), name));
+        std::fs::write(&path, file)?;
+        let opened = open_elf_core(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        opened
+    }
+
+    #[test]
+    fn cores_without_file_mappings_get_a_main_module() {
+        let mut target = open_unmapped_core("entry.core", Some(0x40_0100)).unwrap();
+        assert!(target.name().ends_with("entry.core"));
+        assert_eq!(target.base().unwrap(), 0x40_0000);
+
+        // Without an entry point the lowest executable segment stands in
+        let mut target = open_unmapped_core("lowest.core", None).unwrap();
+        assert_eq!(target.base().unwrap(), 0x20_0000);
+        assert_eq!(target.modules().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn mappings_ending_before_they_start_are_skipped() {
+        let mapping = |start, end, path: &str| FileMapping {
+            start,
+            end,
+            path: path.to_string(),
+        };
+        let modules = build_modules(&[
+            mapping(0x2000, 0x1000, "/usr/bin/corrupt"),
+            mapping(0x40_0000, 0x40_1000, "/usr/bin/app"),
+            mapping(0x40_3000, 0x40_2000, "/usr/bin/app"),
+            mapping(0x40_1000, 0x40_3000, "/usr/bin/app"),
+        ]);
+
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "app");
+        assert_eq!(modules[0].base_address, 0x40_0000);
+        assert_eq!(modules[0].size, 0x3000);
+    }
+}
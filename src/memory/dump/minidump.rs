@@ -0,0 +1,366 @@
+use super::dump::{
+    checked_count, file_name_of, read_file_at, read_u16, read_u32, read_u64, DumpRange, DumpTarget,
+};
+use crate::memory::process::types::{MemoryRegion, ModuleInfo, ThreadInfo};
+use std::fs::File;
+
+/// "MDMP" as it appears at the very start of every minidump
+///
+pub const MINIDUMP_SIGNATURE: u32 = 0x504D_444D;
+
+/// The streams we care about out of a minidump's stream directory
+///
+const THREAD_LIST_STREAM: u32 = 3;
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const MEMORY64_LIST_STREAM: u32 = 9;
+const MEMORY_INFO_LIST_STREAM: u32 = 16;
+
+/// Sizes of the fixed structures we walk through
+///
+const HEADER_SIZE: usize = 32;
+const DIRECTORY_ENTRY_SIZE: usize = 12;
+const MODULE_SIZE: usize = 108;
+const THREAD_SIZE: usize = 48;
+const MEMORY_DESCRIPTOR_SIZE: usize = 16;
+const MEMORY_INFO_SIZE: usize = 48;
+
+/// PROCESSOR_ARCHITECTURE_* values from the system info stream, we need these to know where the
+/// registers sit within each thread's CONTEXT
+///
+const ARCH_X86: u16 = 0;
+const ARCH_AMD64: u16 = 9;
+
+/// Offsets of the instruction and stack pointers within CONTEXT for each architecture
+///
+const AMD64_RSP_OFFSET: usize = 0x98;
+const AMD64_RIP_OFFSET: usize = 0xF8;
+const X86_EIP_OFFSET: usize = 0xB8;
+const X86_ESP_OFFSET: usize = 0xC4;
+
+/// A single entry of the stream directory
+///
+struct StreamEntry {
+    stream_type: u32,
+    data_size: u32,
+    rva: u32,
+}
+
+/// Read a MINIDUMP_STRING, these are a u32 byte length followed by UTF-16
+///
+fn read_minidump_string(file: &mut File, rva: u32) -> anyhow::Result<String> {
+    let length = read_u32(&read_file_at(file, rva as u64, 4)?, 0)?;
+    let buffer = read_file_at(file, rva as u64 + 4, length as usize)?;
+    let utf16: Vec<u16> = buffer
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Ok(String::from_utf16_lossy(&utf16))
+}
+
+/// Parse every module out of the module list stream
+///
+fn parse_modules(file: &mut File, stream: &[u8]) -> anyhow::Result<Vec<ModuleInfo>> {
+    let count = read_u32(stream, 0)? as u64;
+    let count = checked_count(count, stream.len().saturating_sub(4), MODULE_SIZE)?;
+    let mut modules = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let entry = 4 + i * MODULE_SIZE;
+        let base_address = read_u64(stream, entry)?;
+        let size = read_u32(stream, entry + 8)? as u64;
+        let name_rva = read_u32(stream, entry + 20)?;
+        let path = read_minidump_string(file, name_rva)?;
+
+        modules.push(ModuleInfo {
+            name: file_name_of(&path),
+            path,
+            base_address,
+            size,
+        });
+    }
+
+    Ok(modules)
+}
+
+/// Parse every thread out of the thread list stream, pulling the instruction and stack pointer
+/// out of the saved context if we understand the architecture
+///
+fn parse_threads(
+    file: &mut File,
+    stream: &[u8],
+    architecture: Option<u16>,
+) -> anyhow::Result<Vec<ThreadInfo>> {
+    let count = read_u32(stream, 0)? as u64;
+    let count = checked_count(count, stream.len().saturating_sub(4), THREAD_SIZE)?;
+    let mut threads = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let entry = 4 + i * THREAD_SIZE;
+        let context_size = read_u32(stream, entry + 40)? as usize;
+        let context_rva = read_u32(stream, entry + 44)?;
+        let context = read_file_at(file, context_rva as u64, context_size).unwrap_or_default();
+
+        let (instruction_pointer, stack_pointer) = match architecture {
+            Some(ARCH_AMD64) => (
+                read_u64(&context, AMD64_RIP_OFFSET).ok(),
+                read_u64(&context, AMD64_RSP_OFFSET).ok(),
+            ),
+            Some(ARCH_X86) => (
+                read_u32(&context, X86_EIP_OFFSET).ok().map(u64::from),
+                read_u32(&context, X86_ESP_OFFSET).ok().map(u64::from),
+            ),
+            _ => (None, None),
+        };
+
+        threads.push(ThreadInfo {
+            thread_id: read_u32(stream, entry)?,
+            teb: read_u64(stream, entry + 16)?,
+            stack_start: read_u64(stream, entry + 24)?,
+            stack_size: read_u32(stream, entry + 32)? as u64,
+            instruction_pointer,
+            stack_pointer,
+        });
+    }
+
+    Ok(threads)
+}
+
+/// Parse the ranges out of a MINIDUMP_MEMORY_LIST, each one points at its own bytes
+///
+fn parse_memory_list(stream: &[u8]) -> anyhow::Result<Vec<DumpRange>> {
+    let count = read_u32(stream, 0)? as u64;
+    let count = checked_count(
+        count,
+        stream.len().saturating_sub(4),
+        MEMORY_DESCRIPTOR_SIZE,
+    )?;
+
+    (0..count)
+        .map(|i| {
+            let entry = 4 + i * MEMORY_DESCRIPTOR_SIZE;
+            Ok(DumpRange {
+                address: read_u64(stream, entry)?,
+                size: read_u32(stream, entry + 8)? as u64,
+                file_offset: read_u32(stream, entry + 12)? as u64,
+                compressed_size: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse the ranges out of a MINIDUMP_MEMORY64_LIST, full memory dumps use this one and store all
+/// of the bytes back to back starting at a single base rva
+///
+fn parse_memory64_list(stream: &[u8]) -> anyhow::Result<Vec<DumpRange>> {
+    let count = read_u64(stream, 0)?;
+    let count = checked_count(
+        count,
+        stream.len().saturating_sub(16),
+        MEMORY_DESCRIPTOR_SIZE,
+    )?;
+    let mut file_offset = read_u64(stream, 8)?;
+    let mut ranges = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let entry = 16 + i * MEMORY_DESCRIPTOR_SIZE;
+        let size = read_u64(stream, entry + 8)?;
+        ranges.push(DumpRange {
+            address: read_u64(stream, entry)?,
+            size,
+            file_offset,
+            compressed_size: None,
+        });
+        file_offset = file_offset
+            .checked_add(size)
+            .ok_or_else(|| anyhow::anyhow!("Memory range {i} runs past the end of the file"))?;
+    }
+
+    Ok(ranges)
+}
+
+/// Parse the memory info list, this lines up exactly with what VirtualQueryEx gave back on the
+/// machine the dump was taken on
+///
+fn parse_memory_info_list(stream: &[u8]) -> anyhow::Result<Vec<MemoryRegion>> {
+    let header_size = read_u32(stream, 0)? as usize;
+    let entry_size = std::cmp::max(read_u32(stream, 4)? as usize, MEMORY_INFO_SIZE);
+    let count = read_u64(stream, 8)?;
+    let count = checked_count(count, stream.len().saturating_sub(header_size), entry_size)?;
+
+    (0..count)
+        .map(|i| {
+            let entry = header_size + i * entry_size;
+            Ok(MemoryRegion {
+                base_address: read_u64(stream, entry)?,
+                region_size: read_u64(stream, entry + 24)?,
+                state: read_u32(stream, entry + 32)?,
+                protect: read_u32(stream, entry + 36)?,
+                region_type: read_u32(stream, entry + 40)?,
+            })
+        })
+        .collect()
+}
+
+/// Open a minidump as a read-only target
+///
+pub fn open_minidump(path: &str) -> anyhow::Result<DumpTarget> {
+    let mut file = File::open(path)?;
+    let header = read_file_at(&mut file, 0, HEADER_SIZE)?;
+
+    if read_u32(&header, 0)? != MINIDUMP_SIGNATURE {
+        return Err(anyhow::anyhow!("{path} is not a minidump"));
+    }
+
+    // Read through the stream directory
+    let stream_count = read_u32(&header, 8)? as u64;
+    let directory_rva = read_u32(&header, 12)?;
+    let available = (file.metadata()?.len()).saturating_sub(directory_rva as u64);
+    let stream_count = checked_count(
+        stream_count,
+        available.min(usize::MAX as u64) as usize,
+        DIRECTORY_ENTRY_SIZE,
+    )?;
+    let directory = read_file_at(
+        &mut file,
+        directory_rva as u64,
+        stream_count * DIRECTORY_ENTRY_SIZE,
+    )?;
+    let streams: Vec<StreamEntry> = (0..stream_count)
+        .map(|i| {
+            let entry = i * DIRECTORY_ENTRY_SIZE;
+            Ok(StreamEntry {
+                stream_type: read_u32(&directory, entry)?,
+                data_size: read_u32(&directory, entry + 4)?,
+                rva: read_u32(&directory, entry + 8)?,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    // Pull out a whole stream by type, these are all small apart from the memory itself which
+    // we never read up front
+    let mut read_stream = |stream_type: u32| -> anyhow::Result<Option<Vec<u8>>> {
+        match streams
+            .iter()
+            .find(|stream| stream.stream_type == stream_type)
+        {
+            Some(stream) => Ok(Some(read_file_at(
+                &mut file,
+                stream.rva as u64,
+                stream.data_size as usize,
+            )?)),
+            None => Ok(None),
+        }
+    };
+
+    let architecture = match read_stream(SYSTEM_INFO_STREAM)? {
+        Some(stream) => Some(read_u16(&stream, 0)?),
+        None => None,
+    };
+    let module_stream = read_stream(MODULE_LIST_STREAM)?;
+    let thread_stream = read_stream(THREAD_LIST_STREAM)?;
+
+    let mut ranges = Vec::new();
+    if let Some(stream) = read_stream(MEMORY_LIST_STREAM)? {
+        ranges.extend(parse_memory_list(&stream)?);
+    }
+    if let Some(stream) = read_stream(MEMORY64_LIST_STREAM)? {
+        ranges.extend(parse_memory64_list(&stream)?);
+    }
+    let regions = match read_stream(MEMORY_INFO_LIST_STREAM)? {
+        Some(stream) => parse_memory_info_list(&stream)?,
+        None => Vec::new(),
+    };
+
+    let modules = match module_stream {
+        Some(stream) => parse_modules(&mut file, &stream)?,
+        None => Vec::new(),
+    };
+    let threads = match thread_stream {
+        Some(stream) => parse_threads(&mut file, &stream, architecture)?,
+        None => Vec::new(),
+    };
+
+    // The first module is always the main executable, fall back onto the dump's file name if there
+    // somehow aren't any modules at all
+    let name = modules
+        .first()
+        .map(|module| module.name.clone())
+        .unwrap_or_else(|| file_name_of(path));
+
+    let bitness = match architecture {
+        Some(ARCH_X86) => 32,
+        _ => 64,
+    };
+
+    Ok(
+        DumpTarget::new(name, file, ranges, regions, modules, threads)
+            .with_bitness(bitness)
+            .with_windows(true),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write out a minidump holding the given streams and try to open it
+    ///
+    fn open(stream_count: u32, streams: &[(u32, Vec<u8>)]) -> anyhow::Result<DumpTarget> {
+        let mut file = Vec::new();
+        file.extend(MINIDUMP_SIGNATURE.to_le_bytes());
+        file.extend(0xA793u32.to_le_bytes());
+        file.extend(stream_count.to_le_bytes());
+        file.extend((HEADER_SIZE as u32).to_le_bytes());
+        file.resize(HEADER_SIZE, 0);
+
+        let mut rva = HEADER_SIZE + streams.len() * DIRECTORY_ENTRY_SIZE;
+        for (stream_type, data) in streams {
+            file.extend(stream_type.to_le_bytes());
+            file.extend((data.len() as u32).to_le_bytes());
+            file.extend((rva as u32).to_le_bytes());
+            rva += data.len();
+        }
+        for (_, data) in streams {
+            file.extend(data);
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "procvamp-{}-{}.dmp",
+            std::process::id(),
+            stream_count
+        ));
+        std::fs::write(&path, file)?;
+        let opened = open_minidump(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        opened
+    }
+
+    #[test]
+    fn counts_larger_than_their_stream_are_refused() {
+        // More streams than the file could ever hold
+        assert!(open(u32::MAX, &[]).is_err());
+
+        // Lists claiming far more entries than they carry
+        let mut memory64 = u64::MAX.to_le_bytes().to_vec();
+        memory64.extend(0u64.to_le_bytes());
+        assert!(open(1, &[(MEMORY64_LIST_STREAM, memory64)]).is_err());
+        let modules = 0x1000_0000u32.to_le_bytes().to_vec();
+        assert!(open(1, &[(MODULE_LIST_STREAM, modules)]).is_err());
+    }
+
+    #[test]
+    fn empty_lists_open() {
+        let mut memory64 = 0u64.to_le_bytes().to_vec();
+        memory64.extend(0u64.to_le_bytes());
+        let target = open(
+            2,
+            &[
+                (MEMORY64_LIST_STREAM, memory64),
+                (MODULE_LIST_STREAM, 0u32.to_le_bytes().to_vec()),
+            ],
+        );
+        assert!(target.is_ok());
+    }
+}
@@ -0,0 +1,39 @@
+pub mod dump;
+pub mod elf_core;
+pub mod minidump;
+pub mod raw;
+pub mod snapshot;
+use crate::memory::source::source::{share_source, SharedSource};
+use crate::pe::image::ImageTarget;
+use elf_core::{open_elf_core, ELF_MAGIC};
+use minidump::{open_minidump, MINIDUMP_SIGNATURE};
+use raw::RawTarget;
+use snapshot::{open_snapshot, SNAPSHOT_MAGIC};
+use std::io::Read;
+
+/// Open any supported file as a target, whether that's a dump, a snapshot or an image on disk. We
+/// work out what we've been handed from its magic rather than trusting the extension
+///
+pub fn open_target_file(path: &str) -> anyhow::Result<SharedSource> {
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)?.read_exact(&mut magic)?;
+
+    // PE images only carry a two byte "MZ" magic so they're checked separately
+    if magic.starts_with(b"MZ") {
+        return Ok(share_source(ImageTarget::open(path)?));
+    }
+
+    match u32::from_le_bytes(magic) {
+        MINIDUMP_SIGNATURE => Ok(share_source(open_minidump(path)?)),
+        ELF_MAGIC => Ok(share_source(open_elf_core(path)?)),
+        SNAPSHOT_MAGIC => Ok(share_source(open_snapshot(path)?)),
+        _ => Err(anyhow::anyhow!("{path} is not a supported file format")),
+    }
+}
+
+/// Open a raw file such as shellcode as a target, there's no magic to go off of so the caller has
+/// to tell us where it's placed and how it should be decoded
+///
+pub fn open_raw_file(path: &str, base: u64, bitness: u32) -> anyhow::Result<SharedSource> {
+    Ok(share_source(RawTarget::open(path, base, bitness)?))
+}
@@ -0,0 +1,96 @@
+use super::dump::file_name_of;
+use crate::memory::process::types::*;
+use crate::memory::source::source::{MemorySource, SourceErrors};
+
+/// Bitnesses code can be decoded as
+///
+pub const SUPPORTED_BITNESS: [u32; 3] = [16, 32, 64];
+
+/// A raw blob such as extracted shellcode, there's nothing within the file telling us where it
+/// lives or how it should be decoded so both are chosen when it's opened
+///
+#[derive(Debug, Clone)]
+pub struct RawTarget {
+    name: String,
+    path: String,
+    base: u64,
+    bitness: u32,
+    bytes: Vec<u8>,
+}
+
+impl RawTarget {
+    /// Read a raw file in and place it at the given base
+    ///
+    pub fn open(path: &str, base: u64, bitness: u32) -> anyhow::Result<Self> {
+        if !SUPPORTED_BITNESS.contains(&bitness) {
+            return Err(anyhow::anyhow!("{bitness}-bit code is not supported"));
+        }
+
+        let bytes = std::fs::read(path)?;
+        if base.checked_add(bytes.len() as u64).is_none() {
+            return Err(anyhow::anyhow!(
+                "0x{:X} is too high to place {path} at",
+                base
+            ));
+        }
+
+        Ok(Self {
+            name: file_name_of(path),
+            path: path.to_string(),
+            base,
+            bitness,
+            bytes,
+        })
+    }
+}
+
+impl MemorySource for RawTarget {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        let offset = (address as u64)
+            .checked_sub(self.base)
+            .map(|offset| offset as usize)
+            .filter(|offset| offset + size <= self.bytes.len())
+            .ok_or(SourceErrors::Unmapped {
+                address: address as u64,
+            })?;
+
+        Ok(self.bytes[offset..offset + size].to_vec())
+    }
+    fn write_bytes(&mut self, _address: usize, _bytes: &[u8]) -> anyhow::Result<()> {
+        Err(SourceErrors::ReadOnly {
+            target_name: self.name.clone(),
+        })?
+    }
+    /// The blob is a single region, we've no idea what it should be protected as so it's treated
+    /// as code since that's what these almost always are
+    ///
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+        Ok(vec![MemoryRegion {
+            base_address: self.base,
+            region_size: self.bytes.len() as u64,
+            protect: PAGE_EXECUTE_READ,
+            state: MEM_COMMIT,
+            region_type: MEM_PRIVATE,
+        }])
+    }
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+        Ok(vec![ModuleInfo {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            base_address: self.base,
+            size: self.bytes.len() as u64,
+        }])
+    }
+    fn base(&mut self) -> anyhow::Result<u64> {
+        Ok(self.base)
+    }
+    fn is_read_only(&self) -> bool {
+        true
+    }
+    fn bitness(&self) -> u32 {
+        self.bitness
+    }
+}
@@ -0,0 +1,403 @@
+use super::dump::{DumpRange, DumpTarget};
+use crate::memory::process::types::*;
+use crate::memory::scan::scan_job::{ScanJob, ScanProgress};
+use crate::memory::source::source::{
+    lock_source, read_shared_runs, SharedSource, SHARED_READ_CHUNK,
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+
+/// "PVSN" as it appears at the very start of every snapshot
+///
+pub const SNAPSHOT_MAGIC: u32 = 0x4E53_5650;
+
+/// Bumped whenever the layout changes, older snapshots are refused rather than misread
+///
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Extension snapshots are saved with when no path is given
+///
+pub const SNAPSHOT_EXTENSION: &str = "pvsnap";
+
+/// Small helpers for writing out the little endian fields of a snapshot, anything else stored to
+/// disk such as pointer maps shares them
+///
+pub fn write_u32(writer: &mut impl Write, value: u32) -> anyhow::Result<()> {
+    Ok(writer.write_all(&value.to_le_bytes())?)
+}
+
+pub fn write_u64(writer: &mut impl Write, value: u64) -> anyhow::Result<()> {
+    Ok(writer.write_all(&value.to_le_bytes())?)
+}
+
+pub fn write_string(writer: &mut impl Write, value: &str) -> anyhow::Result<()> {
+    write_u32(writer, value.len() as u32)?;
+    Ok(writer.write_all(value.as_bytes())?)
+}
+
+/// Raw bytes are stored with their length up front
+///
+pub fn write_blob(writer: &mut impl Write, value: &[u8]) -> anyhow::Result<()> {
+    write_u32(writer, value.len() as u32)?;
+    Ok(writer.write_all(value)?)
+}
+
+/// Optional values are stored as a presence byte followed by the value
+///
+pub fn write_option(writer: &mut impl Write, value: Option<u64>) -> anyhow::Result<()> {
+    writer.write_all(&[value.is_some() as u8])?;
+    write_u64(writer, value.unwrap_or_default())
+}
+
+/// And the matching helpers for reading them back in
+///
+pub fn take_u8(reader: &mut impl Read) -> anyhow::Result<u8> {
+    let mut buffer = [0u8; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+pub fn take_u32(reader: &mut impl Read) -> anyhow::Result<u32> {
+    let mut buffer = [0u8; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+pub fn take_u64(reader: &mut impl Read) -> anyhow::Result<u64> {
+    let mut buffer = [0u8; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_le_bytes(buffer))
+}
+
+pub fn take_string(reader: &mut impl Read) -> anyhow::Result<String> {
+    Ok(String::from_utf8(take_blob(reader)?)?)
+}
+
+/// The length comes from the file so it can't be trusted, the buffer only grows as bytes are
+/// actually read rather than being allocated up front
+///
+pub fn take_blob(reader: &mut impl Read) -> anyhow::Result<Vec<u8>> {
+    let length = take_u32(reader)? as usize;
+    let mut buffer = Vec::new();
+    reader.take(length as u64).read_to_end(&mut buffer)?;
+    if buffer.len() != length {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(buffer)
+}
+
+pub fn take_option(reader: &mut impl Read) -> anyhow::Result<Option<u64>> {
+    let present = take_u8(reader)? != 0;
+    let value = take_u64(reader)?;
+    Ok(present.then_some(value))
+}
+
+/// Only committed readable regions have their bytes captured, everything else is still recorded
+/// so the layout of the snapshot matches the target's
+///
+fn is_captured(region: &MemoryRegion) -> bool {
+    region.state == MEM_COMMIT && region.is_readable()
+}
+
+/// How a snapshot went, pages that couldn't be read are left as holes rather than saved as zeros
+///
+#[derive(Debug, Clone, Default)]
+pub struct SavedSnapshot {
+    pub path: String,
+    pub bytes_saved: u64,
+    pub bytes_missing: u64,
+}
+
+/// Capture every readable region of a target, along with its modules and threads, into a
+/// snapshot file. The layout is the header and every table up front followed by blocks of
+/// captured bytes, each being where it sits within the target, its size and its bytes lz4
+/// compressed. A block with a size of zero marks the end
+///
+pub fn save_snapshot(
+    target: &SharedSource,
+    path: &str,
+    progress: &ScanProgress,
+) -> anyhow::Result<SavedSnapshot> {
+    // Only hold onto the target for as long as it takes to list everything
+    let (name, bitness, windows, regions, modules, threads) = {
+        let mut source = lock_source(target);
+        (
+            source.name(),
+            source.bitness(),
+            source.is_windows(),
+            source.regions()?,
+            source.modules()?,
+            source.threads()?,
+        )
+    };
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    write_u32(&mut writer, SNAPSHOT_MAGIC)?;
+    write_u32(&mut writer, SNAPSHOT_VERSION)?;
+    write_string(&mut writer, &name)?;
+    write_u32(&mut writer, bitness)?;
+    writer.write_all(&[windows as u8])?;
+
+    write_u32(&mut writer, modules.len() as u32)?;
+    for module in modules.iter() {
+        write_string(&mut writer, &module.name)?;
+        write_string(&mut writer, &module.path)?;
+        write_u64(&mut writer, module.base_address)?;
+        write_u64(&mut writer, module.size)?;
+    }
+
+    write_u32(&mut writer, threads.len() as u32)?;
+    for thread in threads.iter() {
+        write_u32(&mut writer, thread.thread_id)?;
+        write_u64(&mut writer, thread.teb)?;
+        write_u64(&mut writer, thread.stack_start)?;
+        write_u64(&mut writer, thread.stack_size)?;
+        write_option(&mut writer, thread.instruction_pointer)?;
+        write_option(&mut writer, thread.stack_pointer)?;
+    }
+
+    write_u32(&mut writer, regions.len() as u32)?;
+    for region in regions.iter() {
+        write_u64(&mut writer, region.base_address)?;
+        write_u64(&mut writer, region.region_size)?;
+        write_u32(&mut writer, region.protect)?;
+        write_u32(&mut writer, region.state)?;
+        write_u32(&mut writer, region.region_type)?;
+        writer.write_all(&[is_captured(region) as u8])?;
+    }
+
+    let mut saved = SavedSnapshot {
+        path: path.to_string(),
+        ..Default::default()
+    };
+
+    // Blocks are a chunk at most so only one is ever held in memory and each one can be read back
+    // on its own
+    for region in regions.iter().filter(|region| is_captured(region)) {
+        for offset in (0..region.region_size).step_by(SHARED_READ_CHUNK) {
+            progress.check_cancelled()?;
+
+            let size = (region.region_size - offset).min(SHARED_READ_CHUNK as u64);
+            let mut read = 0;
+            for (address, bytes) in
+                read_shared_runs(target, region.base_address + offset, size as usize)
+            {
+                write_u64(&mut writer, address)?;
+                write_u32(&mut writer, bytes.len() as u32)?;
+                write_blob(&mut writer, &lz4_flex::compress(&bytes))?;
+                read += bytes.len() as u64;
+            }
+
+            saved.bytes_saved += read;
+            saved.bytes_missing += size - read;
+            progress.advance(size, 0);
+        }
+    }
+    write_u64(&mut writer, 0)?;
+    write_u32(&mut writer, 0)?;
+
+    writer.flush()?;
+    Ok(saved)
+}
+
+impl ScanJob<SavedSnapshot> {
+    /// Save a snapshot in the background, the target is only held a chunk at a time so every
+    /// view carries on while it's saved. A snapshot cancelled part way is removed
+    ///
+    pub fn save_snapshot(target: SharedSource, path: String) -> anyhow::Result<Self> {
+        let total_bytes = lock_source(&target)
+            .regions()?
+            .iter()
+            .filter(|region| is_captured(region))
+            .map(|region| region.region_size)
+            .sum();
+
+        Ok(Self::spawn_single(
+            target,
+            total_bytes,
+            move |target, progress| {
+                let saved = save_snapshot(target, &path, progress);
+                if saved.is_err() {
+                    let _ = std::fs::remove_file(&path);
+                }
+                saved
+            },
+        ))
+    }
+}
+
+/// Reopen a snapshot as a read-only target
+///
+pub fn open_snapshot(path: &str) -> anyhow::Result<DumpTarget> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    if take_u32(&mut reader)? != SNAPSHOT_MAGIC {
+        return Err(anyhow::anyhow!("{path} is not a snapshot"));
+    }
+    let version = take_u32(&mut reader)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(anyhow::anyhow!(
+            "Snapshot version {version} is not supported, expected {SNAPSHOT_VERSION}"
+        ));
+    }
+    let name = take_string(&mut reader)?;
+    let bitness = take_u32(&mut reader)?;
+    let windows = take_u8(&mut reader)? != 0;
+
+    let module_count = take_u32(&mut reader)?;
+    let modules = (0..module_count)
+        .map(|_| {
+            Ok(ModuleInfo {
+                name: take_string(&mut reader)?,
+                path: take_string(&mut reader)?,
+                base_address: take_u64(&mut reader)?,
+                size: take_u64(&mut reader)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let thread_count = take_u32(&mut reader)?;
+    let threads = (0..thread_count)
+        .map(|_| {
+            Ok(ThreadInfo {
+                thread_id: take_u32(&mut reader)?,
+                teb: take_u64(&mut reader)?,
+                stack_start: take_u64(&mut reader)?,
+                stack_size: take_u64(&mut reader)?,
+                instruction_pointer: take_option(&mut reader)?,
+                stack_pointer: take_option(&mut reader)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let region_count = take_u32(&mut reader)?;
+    let regions = (0..region_count)
+        .map(|_| {
+            let region = MemoryRegion {
+                base_address: take_u64(&mut reader)?,
+                region_size: take_u64(&mut reader)?,
+                protect: take_u32(&mut reader)?,
+                state: take_u32(&mut reader)?,
+                region_type: take_u32(&mut reader)?,
+            };
+            // Whether it was captured only matters while saving, the blocks say what was
+            take_u8(&mut reader)?;
+            Ok(region)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Only where each block sits is noted, they're decompressed as they're read
+    let mut ranges = Vec::new();
+    loop {
+        let address = take_u64(&mut reader)?;
+        let size = take_u32(&mut reader)? as u64;
+        if size == 0 {
+            break;
+        }
+        if size > SHARED_READ_CHUNK as u64 {
+            return Err(anyhow::anyhow!(
+                "Block at 0x{address:X} is 0x{size:X} bytes, larger than any snapshot writes"
+            ));
+        }
+        let compressed_size = take_u32(&mut reader)? as u64;
+        ranges.push(DumpRange {
+            address,
+            size,
+            file_offset: reader.stream_position()?,
+            compressed_size: Some(compressed_size),
+        });
+        reader.seek_relative(compressed_size as i64)?;
+    }
+
+    Ok(
+        DumpTarget::new(name, reader.into_inner(), ranges, regions, modules, threads)
+            .with_bitness(bitness)
+            .with_windows(windows),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::source::mock::MockTarget;
+    use crate::memory::source::source::{share_source, MemorySource};
+
+    /// A target with a page in the middle of a region that can't be read, like a guard page
+    ///
+    struct GuardedTarget(MockTarget);
+
+    const GUARD_PAGE: u64 = 0x11000;
+
+    impl MemorySource for GuardedTarget {
+        fn name(&self) -> String {
+            self.0.name()
+        }
+        fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+            let (start, end) = (address as u64, address as u64 + size as u64);
+            if start < GUARD_PAGE + 0x1000 && end > GUARD_PAGE {
+                return Err(anyhow::anyhow!("Guard page"));
+            }
+            self.0.read_bytes(address, size)
+        }
+        fn write_bytes(&mut self, address: usize, bytes: &[u8]) -> anyhow::Result<()> {
+            self.0.write_bytes(address, bytes)
+        }
+        fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+            self.0.regions()
+        }
+        fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+            self.0.modules()
+        }
+        fn base(&mut self) -> anyhow::Result<u64> {
+            self.0.base()
+        }
+    }
+
+    #[test]
+    fn snapshots_keep_unreadable_pages_as_holes() {
+        let bytes: Vec<u8> = (0..0x13000u32).map(|idx| (idx % 251) as u8).collect();
+        let target = share_source(GuardedTarget(
+            MockTarget::new("game")
+                .with_region(0x10000, bytes[..0x3000].to_vec(), PAGE_READWRITE)
+                .with_region(0x20000, bytes.clone(), PAGE_READONLY),
+        ));
+        let path = std::env::temp_dir().join(format!("procvamp-{}.pvsnap", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let saved = save_snapshot(&target, path, &ScanProgress::default()).unwrap();
+        let mut snapshot = open_snapshot(path).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        assert_eq!(saved.bytes_saved, 0x2000 + 0x13000);
+        assert_eq!(saved.bytes_missing, 0x1000);
+        assert_eq!(snapshot.regions().unwrap().len(), 2);
+        assert_eq!(
+            snapshot.read_bytes(0x10000, 0x1000).unwrap(),
+            bytes[..0x1000]
+        );
+        assert!(snapshot.read_bytes(0x11000, 4).is_err());
+        assert_eq!(
+            snapshot.read_bytes(0x12000, 0x1000).unwrap(),
+            bytes[0x2000..0x3000]
+        );
+        // Reads spanning blocks
+        assert_eq!(snapshot.read_bytes(0x20000, 0x13000).unwrap(), bytes);
+    }
+
+    #[test]
+    fn blob_lengths_past_the_end_of_the_file_are_refused() {
+        let mut file = Vec::new();
+        write_blob(&mut file, &[1, 2, 3]).unwrap();
+        write_string(&mut file, "procvamp").unwrap();
+        let mut reader = file.as_slice();
+        assert_eq!(take_blob(&mut reader).unwrap(), [1, 2, 3]);
+        assert_eq!(take_string(&mut reader).unwrap(), "procvamp");
+
+        // A length of nearly 4GB with only a few bytes behind it
+        let mut truncated = u32::MAX.to_le_bytes().to_vec();
+        truncated.extend([1, 2, 3]);
+        assert!(take_blob(&mut truncated.as_slice()).is_err());
+        assert!(take_string(&mut truncated.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,546 @@
+use crate::memory::process::types::ModuleInfo;
+use crate::memory::source::source::MemorySource;
+use crate::pe::pe::{get_exports, get_pe_from_source};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use thiserror::Error;
+
+/// Errors that can come out of evaluating an address expression
+///
+#[derive(Error, Debug)]
+pub enum ExpressionErrors {
+    #[error("Unexpected character '{character}' at position {position}")]
+    UnexpectedCharacter { character: char, position: usize },
+    #[error("Quote at position {position} is never closed")]
+    UnterminatedQuote { position: usize },
+    #[error("Unexpected '{token}' within expression")]
+    UnexpectedToken { token: String },
+    #[error("Expression ended unexpectedly")]
+    UnexpectedEnd,
+    #[error("Unknown symbol '{symbol}'")]
+    UnknownSymbol { symbol: String },
+    #[error("Unknown symbol '{symbol}', exports are only read out of Windows (PE) images")]
+    NoExports { symbol: String },
+    #[error("'{symbol}' needs a target to be resolved against")]
+    NoTarget { symbol: String },
+    #[error("Division by zero")]
+    DivisionByZero,
+}
+
+/// Export tables of every module looked through so far, keyed by the module's path and where it
+/// sits so a module that's moved or been swapped out is read again. Images that can't be parsed
+/// are kept as empty tables so they aren't read over and over
+///
+type ExportCache = HashMap<(String, u64, u64), Arc<HashMap<String, u32>>>;
+
+static EXPORT_CACHE: LazyLock<Mutex<ExportCache>> = LazyLock::new(Mutex::default);
+
+/// Tokens an expression is broken up into
+///
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(u64),
+    Symbol(String),
+    /// A word such as `dead` which reads as both a hex number and a symbol
+    HexOrSymbol(String, u64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "0x{:X}", value),
+            Token::Symbol(symbol) => write!(f, "{}", quote_symbol(symbol)),
+            Token::HexOrSymbol(word, _) => write!(f, "{}", word),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Slash => write!(f, "/"),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+        }
+    }
+}
+
+/// Characters that can make up a number or symbol, module names carry dots and exports can be
+/// qualified with their module as module!export. Anything else, such as the dashes and pluses in
+/// libc-2.31.so or libstdc++.so.6, needs the symbol to be quoted
+///
+fn is_word_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || "_.!@?$#".contains(character)
+}
+
+/// Write a symbol out so it reads back as one, quoting it if it holds anything a bare word can't
+///
+pub fn quote_symbol(symbol: &str) -> String {
+    match symbol.chars().all(is_word_character) {
+        true => symbol.to_string(),
+        false => format!("\"{}\"", symbol),
+    }
+}
+
+/// Work out whether a word is a number or a symbol. Numbers are hex by default, with or without a
+/// 0x prefix, while a leading # marks a decimal number. Words such as `dead` could be either so
+/// they're only settled once there's a target to look for a symbol in
+///
+fn classify_word(word: &str) -> Token {
+    if let Some(decimal) = word.strip_prefix('#') {
+        if let Ok(value) = decimal.parse::<u64>() {
+            return Token::Number(value);
+        }
+    }
+    if let Some(hex) = word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        if let Ok(value) = u64::from_str_radix(hex, 16) {
+            return Token::Number(value);
+        }
+    }
+
+    match u64::from_str_radix(word, 16) {
+        Ok(value) if word.chars().all(|c| c.is_ascii_digit()) => Token::Number(value),
+        Ok(value) => Token::HexOrSymbol(word.to_string(), value),
+        Err(_) => Token::Symbol(word.to_string()),
+    }
+}
+
+/// Read a word in, the first character has already been taken
+///
+fn take_word(
+    first: char,
+    characters: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+) -> String {
+    let mut word = first.to_string();
+    while let Some(&(_, next)) = characters.peek() {
+        if !is_word_character(next) {
+            break;
+        }
+        word.push(next);
+        characters.next();
+    }
+    word
+}
+
+/// Break an expression up into tokens
+///
+fn tokenize(expression: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut characters = expression.char_indices().peekable();
+
+    while let Some((position, character)) = characters.next() {
+        let token = match character {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '[' => Token::OpenBracket,
+            ']' => Token::CloseBracket,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            // Quoted symbols are taken as written, an export can still follow as "module"!export
+            '"' => {
+                let mut symbol = String::new();
+                loop {
+                    match characters.next() {
+                        Some((_, '"')) => break,
+                        Some((_, next)) => symbol.push(next),
+                        None => Err(ExpressionErrors::UnterminatedQuote { position })?,
+                    }
+                }
+                if let Some(&(_, '!')) = characters.peek() {
+                    characters.next();
+                    symbol.push_str(&take_word('!', &mut characters));
+                }
+                Token::Symbol(symbol)
+            }
+            c if is_word_character(c) => classify_word(&take_word(c, &mut characters)),
+            character => Err(ExpressionErrors::UnexpectedCharacter {
+                character,
+                position,
+            })?,
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// A simple recursive descent evaluator, the grammar is
+///
+/// expression := term (('+' | '-') term)*
+/// term       := unary (('*' | '/') unary)*
+/// unary      := '-' unary | primary
+/// primary    := number | symbol | '"' symbol '"' | '[' expression ']' | '(' expression ')'
+///
+/// Brackets dereference whatever they hold, reading a pointer sized value out of the target
+///
+struct Evaluator<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    source: Option<&'a mut dyn MemorySource>,
+    modules: Option<Vec<ModuleInfo>>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or(ExpressionErrors::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(ExpressionErrors::UnexpectedToken {
+                token: token.to_string(),
+            })?,
+        }
+    }
+    fn expression(&mut self) -> anyhow::Result<u64> {
+        let mut value = self.term()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Plus) => {
+                    self.position += 1;
+                    value = value.wrapping_add(self.term()?);
+                }
+                Some(Token::Minus) => {
+                    self.position += 1;
+                    value = value.wrapping_sub(self.term()?);
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+    fn term(&mut self) -> anyhow::Result<u64> {
+        let mut value = self.unary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Star) => {
+                    self.position += 1;
+                    value = value.wrapping_mul(self.unary()?);
+                }
+                Some(Token::Slash) => {
+                    self.position += 1;
+                    let divisor = self.unary()?;
+                    value = value
+                        .checked_div(divisor)
+                        .ok_or(ExpressionErrors::DivisionByZero)?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+    fn unary(&mut self) -> anyhow::Result<u64> {
+        if self.peek() == Some(&Token::Minus) {
+            self.position += 1;
+            return Ok(self.unary()?.wrapping_neg());
+        }
+        self.primary()
+    }
+    fn primary(&mut self) -> anyhow::Result<u64> {
+        match self.next()? {
+            Token::Number(value) => Ok(value),
+            Token::Symbol(symbol) => self.resolve_symbol(&symbol),
+            Token::HexOrSymbol(word, value) => self.resolve_hex_or_symbol(&word, value),
+            Token::OpenParen => {
+                let value = self.expression()?;
+                self.expect(Token::CloseParen)?;
+                Ok(value)
+            }
+            Token::OpenBracket => {
+                let address = self.expression()?;
+                self.expect(Token::CloseBracket)?;
+                self.dereference(address)
+            }
+            token => Err(ExpressionErrors::UnexpectedToken {
+                token: token.to_string(),
+            })?,
+        }
+    }
+    /// Read a pointer out of the target, pointers are sized by the target's bitness
+    ///
+    fn dereference(&mut self, address: u64) -> anyhow::Result<u64> {
+        let source = self
+            .source
+            .as_deref_mut()
+            .ok_or(ExpressionErrors::NoTarget {
+                symbol: format!("[0x{:X}]", address),
+            })?;
+
+        source.read_pointer(address as usize)
+    }
+    /// Find a module by name, the extension can be left off so kernel32 finds kernel32.dll
+    ///
+    fn find_module(&self, name: &str) -> Option<ModuleInfo> {
+        self.modules.as_ref()?.iter().find_map(|module| {
+            let stem = module
+                .name
+                .rsplit_once('.')
+                .map_or(module.name.as_str(), |(stem, _)| stem);
+            (module.name.eq_ignore_ascii_case(name) || stem.eq_ignore_ascii_case(name))
+                .then(|| module.clone())
+        })
+    }
+    /// Look an export up within a module. Its image is read straight out of the target and parsed
+    /// the first time, after that the export table is cached
+    ///
+    fn find_export(&mut self, module: &ModuleInfo, export: &str) -> Option<u64> {
+        let key = (module.path.clone(), module.base_address, module.size);
+        let cached = EXPORT_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+            .cloned();
+
+        let exports = match cached {
+            Some(exports) => exports,
+            None => {
+                let source = self.source.as_deref_mut()?;
+                let exports = Arc::new(
+                    get_pe_from_source(source, module)
+                        .and_then(|image| get_exports(&image))
+                        .unwrap_or_default(),
+                );
+                EXPORT_CACHE
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .insert(key, exports.clone());
+                exports
+            }
+        };
+
+        exports
+            .get(export)
+            .map(|&rva| module.base_address + rva as u64)
+    }
+    /// Resolve a symbol against the target, this is either a module, an export qualified with its
+    /// module as module!export or a bare export which is searched for within every module.
+    /// Exports are only read out of PE images, so on anything but a Windows target only modules
+    /// resolve
+    ///
+    fn resolve_symbol(&mut self, symbol: &str) -> anyhow::Result<u64> {
+        self.load_modules(symbol)?;
+        let is_windows = self
+            .source
+            .as_deref()
+            .is_some_and(|source| source.is_windows());
+        let unknown = || match is_windows {
+            true => ExpressionErrors::UnknownSymbol {
+                symbol: symbol.to_string(),
+            },
+            false => ExpressionErrors::NoExports {
+                symbol: symbol.to_string(),
+            },
+        };
+
+        if let Some((module_name, export)) = symbol.split_once('!') {
+            let module = self.find_module(module_name).ok_or_else(unknown)?;
+            if !is_windows {
+                Err(unknown())?
+            }
+            return Ok(self.find_export(&module, export).ok_or_else(unknown)?);
+        }
+
+        if let Some(module) = self.find_module(symbol) {
+            return Ok(module.base_address);
+        }
+
+        if is_windows {
+            let modules = self.modules.clone().unwrap_or_default();
+            for module in modules.iter() {
+                if let Some(address) = self.find_export(module, symbol) {
+                    return Ok(address);
+                }
+            }
+        }
+
+        Err(unknown())?
+    }
+    /// A word that reads as both a number and a symbol is a module if the target has one by that
+    /// name, otherwise it's a number. Exports aren't searched for since every module's image would
+    /// be read just to use a number, module!export or a quoted name reaches those
+    ///
+    fn resolve_hex_or_symbol(&mut self, word: &str, value: u64) -> anyhow::Result<u64> {
+        if self.source.is_none() {
+            return Ok(value);
+        }
+        self.load_modules(word)?;
+        Ok(self
+            .find_module(word)
+            .map_or(value, |module| module.base_address))
+    }
+    /// Pull the target's modules in the first time a symbol needs resolving
+    ///
+    fn load_modules(&mut self, symbol: &str) -> anyhow::Result<()> {
+        if self.modules.is_none() {
+            let source = self
+                .source
+                .as_deref_mut()
+                .ok_or(ExpressionErrors::NoTarget {
+                    symbol: symbol.to_string(),
+                })?;
+            self.modules = Some(source.modules()?);
+        }
+        Ok(())
+    }
+}
+
+/// Run an evaluator over a whole expression, making sure nothing is left over at the end
+///
+fn evaluate(expression: &str, source: Option<&mut dyn MemorySource>) -> anyhow::Result<u64> {
+    let mut evaluator = Evaluator {
+        tokens: tokenize(expression)?,
+        position: 0,
+        source,
+        modules: None,
+    };
+
+    let value = evaluator.expression()?;
+    match evaluator.peek() {
+        Some(token) => Err(ExpressionErrors::UnexpectedToken {
+            token: token.to_string(),
+        })?,
+        None => Ok(value),
+    }
+}
+
+/// Evaluate an address expression against a target, such as `game.exe+0x1A20`,
+/// `kernel32!CreateFileW`, `"libc-2.31.so"+0x10` or `[[game.exe+0x10]+0x8]`
+///
+pub fn evaluate_address(expression: &str, source: &mut dyn MemorySource) -> anyhow::Result<u64> {
+    evaluate(expression, Some(source))
+}
+
+/// Evaluate an expression that can't reference a target, only numbers and arithmetic are allowed
+///
+pub fn evaluate_constant(expression: &str) -> anyhow::Result<u64> {
+    evaluate(expression, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::pointer::pointer::PointerChain;
+    use crate::memory::process::types::{MemoryRegion, PAGE_READWRITE};
+    use crate::memory::source::mock::MockTarget;
+
+    fn target() -> MockTarget {
+        let mut libc = vec![0u8; 0x100];
+        libc[0x10..0x18].copy_from_slice(&0x20000u64.to_le_bytes());
+
+        MockTarget::new("game")
+            .with_module("libc-2.31.so", 0x10000, libc, PAGE_READWRITE)
+            .with_module("dead.so", 0x20000, vec![0u8; 0x100], PAGE_READWRITE)
+    }
+
+    #[test]
+    fn quoted_symbols() {
+        let mut target = target();
+
+        assert_eq!(
+            evaluate_address("\"libc-2.31.so\"+0x10", &mut target).unwrap(),
+            0x10010
+        );
+        assert_eq!(
+            evaluate_address("[\"libc-2.31.so\"+10]+8", &mut target).unwrap(),
+            0x20008
+        );
+        assert!(evaluate_address("\"libc-2.31.so+0x10", &mut target).is_err());
+    }
+
+    #[test]
+    fn modules_win_over_hex() {
+        let mut target = target();
+
+        assert_eq!(evaluate_address("dead+10", &mut target).unwrap(), 0x20010);
+        assert_eq!(evaluate_address("beef", &mut target).unwrap(), 0xBEEF);
+        assert_eq!(evaluate_constant("dead").unwrap(), 0xDEAD);
+    }
+
+    #[test]
+    fn pointer_chains_parse_back() {
+        let mut target = target();
+        let chain = PointerChain::new(Some("libc-2.31.so"), 0x10, vec![0x8]);
+
+        assert_eq!(chain.to_string(), "[\"libc-2.31.so\"+0x10]+0x8");
+        assert_eq!(
+            evaluate_address(&chain.to_string(), &mut target).unwrap(),
+            chain.resolve(&mut target).unwrap()
+        );
+    }
+
+    /// A Windows target counting how many times its memory is read
+    ///
+    struct WindowsTarget(MockTarget, usize);
+
+    impl MemorySource for WindowsTarget {
+        fn name(&self) -> String {
+            self.0.name()
+        }
+        fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+            self.1 += 1;
+            self.0.read_bytes(address, size)
+        }
+        fn write_bytes(&mut self, address: usize, bytes: &[u8]) -> anyhow::Result<()> {
+            self.0.write_bytes(address, bytes)
+        }
+        fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+            self.0.regions()
+        }
+        fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+            self.0.modules()
+        }
+        fn base(&mut self) -> anyhow::Result<u64> {
+            self.0.base()
+        }
+        fn is_windows(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn export_tables_are_only_read_once() {
+        let mut target = WindowsTarget(
+            MockTarget::new("cached.exe").with_module(
+                "cached.exe",
+                0x7FF0_0000,
+                vec![0u8; 0x100],
+                PAGE_READWRITE,
+            ),
+            0,
+        );
+
+        assert!(evaluate_address("Missing", &mut target).is_err());
+        let reads = target.1;
+        assert!(reads > 0);
+        assert!(evaluate_address("Missing", &mut target).is_err());
+        assert!(evaluate_address("cached!Missing", &mut target).is_err());
+        assert_eq!(target.1, reads);
+    }
+
+    #[test]
+    fn exports_need_a_windows_target() {
+        let mut target = target();
+
+        let error = evaluate_address("malloc", &mut target).unwrap_err();
+        assert!(error.to_string().contains("PE"), "{error}");
+        assert!(evaluate_address("libc-2.31.so!malloc", &mut target).is_err());
+    }
+}
@@ -1,3 +1,11 @@
+pub mod address_list;
 pub mod breakpoint;
+pub mod ctypes;
+pub mod dissect;
+pub mod dump;
+pub mod expression;
+pub mod pointer;
 pub mod process;
+pub mod scan;
+pub mod source;
 pub mod memory {}
@@ -0,0 +1,91 @@
+use crate::memory::expression::expression::quote_symbol;
+use crate::memory::source::source::MemorySource;
+use thiserror::Error;
+
+/// Errors that can come out of resolving a pointer chain, a failed read reports which level of
+/// the chain it happened at so a broken chain can be tracked down
+///
+#[derive(Error, Debug)]
+pub enum PointerErrors {
+    #[error("Failed to find module '{module_name}' the chain is based on")]
+    ModuleNotFound { module_name: String },
+    #[error("Failed to read pointer at 0x{address:X} (level {level})")]
+    ReadFailed { level: usize, address: u64 },
+}
+
+/// A multi-level pointer, a static base (optionally relative to a module) followed by a list of
+/// offsets. Resolving it reads a pointer at the base, adds the first offset, reads a pointer there
+/// and so on until every offset has been added
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PointerChain {
+    pub module: Option<String>,
+    pub base_offset: u64,
+    pub offsets: Vec<i64>,
+}
+
+impl PointerChain {
+    /// Create a chain, leave the module empty for a chain based at an absolute address
+    ///
+    pub fn new(module: Option<&str>, base_offset: u64, offsets: Vec<i64>) -> Self {
+        Self {
+            module: module.map(|module| module.to_string()),
+            base_offset,
+            offsets,
+        }
+    }
+    /// Work out where the chain starts, this moves around between restarts whenever the module
+    /// it's based on is relocated
+    ///
+    pub fn base_address(&self, source: &mut dyn MemorySource) -> anyhow::Result<u64> {
+        let Some(module_name) = &self.module else {
+            return Ok(self.base_offset);
+        };
+
+        source
+            .modules()?
+            .iter()
+            .find(|module| module.name.eq_ignore_ascii_case(module_name))
+            .map(|module| module.base_address.wrapping_add(self.base_offset))
+            .ok_or_else(|| {
+                PointerErrors::ModuleNotFound {
+                    module_name: module_name.clone(),
+                }
+                .into()
+            })
+    }
+    /// Follow the chain through the target and hand back the final address
+    ///
+    pub fn resolve(&self, source: &mut dyn MemorySource) -> anyhow::Result<u64> {
+        let mut address = self.base_address(source)?;
+
+        for (level, offset) in self.offsets.iter().enumerate() {
+            let pointer = source
+                .read_pointer(address as usize)
+                .map_err(|_| PointerErrors::ReadFailed { level, address })?;
+            address = pointer.wrapping_add_signed(*offset);
+        }
+
+        Ok(address)
+    }
+}
+
+/// Write the chain out as an address expression, e.g. [[game.exe+0x10]+0x8]+0x18, which can be
+/// handed straight to anything that takes an address. Module names a bare word can't hold are
+/// quoted
+///
+impl std::fmt::Display for PointerChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut expression = match &self.module {
+            Some(module) => format!("{}+0x{:X}", quote_symbol(module), self.base_offset),
+            None => format!("0x{:X}", self.base_offset),
+        };
+
+        for offset in self.offsets.iter() {
+            let sign = if *offset < 0 { '-' } else { '+' };
+            expression = format!("[{}]{}0x{:X}", expression, sign, offset.unsigned_abs());
+        }
+
+        write!(f, "{}", expression)
+    }
+}
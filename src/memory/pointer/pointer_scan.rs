@@ -0,0 +1,435 @@
+use super::pointer::PointerChain;
+use crate::memory::dump::snapshot::{
+    take_string, take_u32, take_u64, write_string, write_u32, write_u64,
+};
+use crate::memory::process::types::{MemoryRegion, ModuleInfo, MEM_COMMIT};
+use crate::memory::scan::scan_job::{ScanJob, ScanProgress};
+use crate::memory::source::source::{lock_source, read_shared_paged, SharedSource};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+/// "PVPM" as it appears at the very start of every stored pointer map
+///
+pub const POINTER_MAP_MAGIC: u32 = 0x4D50_5650;
+
+/// Extension pointer maps are saved with
+///
+pub const POINTER_MAP_EXTENSION: &str = "pvptr";
+
+/// Bumped whenever the layout changes
+///
+const POINTER_MAP_VERSION: u32 = 1;
+
+/// How far a pointer scan is allowed to go
+///
+#[derive(Debug, Clone, Copy)]
+pub struct PointerScanOptions {
+    /// Maximum number of pointers within a chain
+    pub max_depth: usize,
+    /// Maximum offset added on after following each pointer
+    pub max_offset: u64,
+    /// Stop once this many chains have been found, scans explode quickly with depth
+    pub max_results: usize,
+}
+
+impl Default for PointerScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_offset: 0x1000,
+            max_results: 10_000,
+        }
+    }
+}
+
+/// Every pointer within a target at one moment in time, that is every pointer aligned value within
+/// a readable region which itself points into a readable region. Entries are sorted by the value
+/// they point to so we can look backwards from the address being hunted to everything near it
+///
+#[derive(Debug, Clone, Default)]
+pub struct PointerMap {
+    /// The address chains are being searched for at the time the map was built
+    pub address: u64,
+    pub bitness: u32,
+    pub modules: Vec<ModuleInfo>,
+    /// (value, address the value lives at)
+    pub pointers: Vec<(u64, u64)>,
+}
+
+/// A finished pointer scan, the map that was searched along with every chain found within it
+///
+#[derive(Debug, Clone, Default)]
+pub struct PointerScan {
+    pub map: PointerMap,
+    pub chains: Vec<PointerChain>,
+}
+
+/// Everything shared between the levels of a backwards search
+///
+struct ChainSearch<'a> {
+    options: &'a PointerScanOptions,
+    progress: &'a ScanProgress,
+    /// (address, depth left) pairs already searched from, pointers leading back to each other
+    /// would otherwise be searched again and again
+    visited: HashSet<(u64, usize)>,
+    results: Vec<PointerChain>,
+}
+
+impl ChainSearch<'_> {
+    /// Whether to stop going any deeper, either enough chains were found or we were cancelled
+    ///
+    fn is_done(&self) -> bool {
+        self.results.len() >= self.options.max_results || self.progress.is_cancelled()
+    }
+}
+
+/// Is the region something we can pull pointers out of
+///
+fn is_scannable(region: &MemoryRegion) -> bool {
+    region.state == MEM_COMMIT && region.is_readable()
+}
+
+/// Every region pointers can be pulled out of, lowest first
+///
+fn scannable_regions(target: &SharedSource) -> anyhow::Result<Vec<MemoryRegion>> {
+    let mut regions: Vec<MemoryRegion> = lock_source(target)
+        .regions()?
+        .into_iter()
+        .filter(is_scannable)
+        .collect();
+    regions.sort_by_key(|region| region.base_address);
+    Ok(regions)
+}
+
+impl PointerMap {
+    /// Walk every given region of a target and record every pointer within it, regions are read
+    /// a chunk at a time so the target isn't held onto for the whole build
+    ///
+    pub fn build(
+        target: &SharedSource,
+        address: u64,
+        regions: &[MemoryRegion],
+        progress: &ScanProgress,
+    ) -> anyhow::Result<Self> {
+        let (bitness, modules) = {
+            let mut source = lock_source(target);
+            (source.bitness(), source.modules()?)
+        };
+        let pointer_size = bitness as usize / 8;
+
+        // Any value landing within a readable region counts as a pointer
+        let points_to_region = |value: u64| {
+            let idx = regions.partition_point(|region| region.base_address <= value);
+            idx > 0 && regions[idx - 1].contains(value)
+        };
+
+        let mut pointers = Vec::new();
+        for region in regions.iter() {
+            progress.check_cancelled()?;
+            let bytes = read_shared_paged(
+                target,
+                region.base_address as usize,
+                region.region_size as usize,
+            )?;
+
+            for (idx, chunk) in bytes.chunks_exact(pointer_size).enumerate() {
+                let value = chunk
+                    .iter()
+                    .rev()
+                    .fold(0u64, |value, &byte| (value << 8) | byte as u64);
+                if value != 0 && points_to_region(value) {
+                    pointers.push((value, region.base_address + (idx * pointer_size) as u64));
+                }
+            }
+            progress.advance(region.region_size, 0);
+        }
+        pointers.sort_unstable();
+
+        Ok(Self {
+            address,
+            bitness,
+            modules,
+            pointers,
+        })
+    }
+    /// Find the module an address lives in, pointers within module images are static and are what
+    /// chains are rooted at
+    ///
+    fn static_module(&self, address: u64) -> Option<&ModuleInfo> {
+        self.modules.iter().find(|module| module.contains(address))
+    }
+    /// Search backwards from the map's address for chains rooted in module images, a cancelled
+    /// search still hands back whatever it found before it stopped
+    ///
+    pub fn scan(&self, options: &PointerScanOptions, progress: &ScanProgress) -> Vec<PointerChain> {
+        let mut search = ChainSearch {
+            options,
+            progress,
+            visited: HashSet::new(),
+            results: Vec::new(),
+        };
+        self.scan_level(&mut search, self.address, options.max_depth, &[]);
+        search.results
+    }
+    /// One level of the backwards search, offsets holds the offsets found so far from the
+    /// deepest level outwards
+    ///
+    fn scan_level(&self, search: &mut ChainSearch, address: u64, depth: usize, offsets: &[i64]) {
+        if depth == 0 || !search.visited.insert((address, depth)) {
+            return;
+        }
+
+        // Everything pointing at or within max_offset before the address
+        let lowest = address.saturating_sub(search.options.max_offset);
+        let start = self.pointers.partition_point(|&(value, _)| value < lowest);
+        let end = self
+            .pointers
+            .partition_point(|&(value, _)| value <= address);
+
+        for &(value, pointer_address) in self.pointers[start..end].iter() {
+            if search.is_done() {
+                return;
+            }
+
+            let mut chain_offsets = vec![(address - value) as i64];
+            chain_offsets.extend_from_slice(offsets);
+
+            if let Some(module) = self.static_module(pointer_address) {
+                search.results.push(PointerChain::new(
+                    Some(&module.name),
+                    pointer_address - module.base_address,
+                    chain_offsets.clone(),
+                ));
+                search.progress.advance(0, 1);
+            }
+
+            self.scan_level(search, pointer_address, depth - 1, &chain_offsets);
+        }
+    }
+    /// Store the map to disk so a scan can be repeated against it after the target has restarted,
+    /// uses the same little endian layout as snapshots
+    ///
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        write_u32(&mut writer, POINTER_MAP_MAGIC)?;
+        write_u32(&mut writer, POINTER_MAP_VERSION)?;
+        write_u64(&mut writer, self.address)?;
+        write_u32(&mut writer, self.bitness)?;
+
+        write_u32(&mut writer, self.modules.len() as u32)?;
+        for module in self.modules.iter() {
+            write_string(&mut writer, &module.name)?;
+            write_string(&mut writer, &module.path)?;
+            write_u64(&mut writer, module.base_address)?;
+            write_u64(&mut writer, module.size)?;
+        }
+
+        write_u64(&mut writer, self.pointers.len() as u64)?;
+        for (value, address) in self.pointers.iter() {
+            write_u64(&mut writer, *value)?;
+            write_u64(&mut writer, *address)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+    /// Load a map back in from disk
+    ///
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        if take_u32(&mut reader)? != POINTER_MAP_MAGIC {
+            return Err(anyhow::anyhow!("{path} is not a pointer map"));
+        }
+        let version = take_u32(&mut reader)?;
+        if version != POINTER_MAP_VERSION {
+            return Err(anyhow::anyhow!(
+                "Pointer map version {version} is not supported, expected {POINTER_MAP_VERSION}"
+            ));
+        }
+        let address = take_u64(&mut reader)?;
+        let bitness = take_u32(&mut reader)?;
+
+        // Counts come from the file so nothing is allocated up front for them
+        let module_count = take_u32(&mut reader)?;
+        let modules = (0..module_count)
+            .map(|_| {
+                Ok(ModuleInfo {
+                    name: take_string(&mut reader)?,
+                    path: take_string(&mut reader)?,
+                    base_address: take_u64(&mut reader)?,
+                    size: take_u64(&mut reader)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let pointer_count = take_u64(&mut reader)?;
+        let pointers = (0..pointer_count)
+            .map(|_| Ok((take_u64(&mut reader)?, take_u64(&mut reader)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            address,
+            bitness,
+            modules,
+            pointers,
+        })
+    }
+}
+
+impl ScanJob<PointerScan> {
+    /// Build a fresh pointer map of the target in the background and search it for the address
+    ///
+    pub fn pointer_scan(
+        target: SharedSource,
+        address: u64,
+        options: PointerScanOptions,
+    ) -> anyhow::Result<Self> {
+        let regions = scannable_regions(&target)?;
+        let total_bytes = regions.iter().map(|region| region.region_size).sum();
+
+        Ok(Self::spawn_single(
+            target,
+            total_bytes,
+            move |target, progress| {
+                let map = PointerMap::build(target, address, &regions, progress)?;
+                let chains = map.scan(&options, progress);
+                Ok(PointerScan { map, chains })
+            },
+        ))
+    }
+    /// Load a stored map and search it the same way, there's nothing to read from the target
+    ///
+    pub fn stored_pointer_scan(
+        target: SharedSource,
+        path: String,
+        options: PointerScanOptions,
+    ) -> Self {
+        Self::spawn_single(target, 0, move |_, progress| {
+            let map = PointerMap::load(&path)?;
+            let chains = map.scan(&options, progress);
+            Ok(PointerScan { map, chains })
+        })
+    }
+}
+
+/// Keep only the chains found by both scans, chains that hold up across restarts are the ones
+/// worth keeping
+///
+pub fn intersect_chains(first: &[PointerChain], second: &[PointerChain]) -> Vec<PointerChain> {
+    first
+        .iter()
+        .filter(|chain| second.contains(chain))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::process::types::{PAGE_READONLY, PAGE_READWRITE};
+    use crate::memory::source::mock::MockTarget;
+    use crate::memory::source::source::share_source;
+
+    /// A page of memory holding pointers at the given offsets
+    ///
+    fn page(pointers: &[(usize, u64)]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x1000];
+        for (offset, value) in pointers {
+            bytes[*offset..*offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// A game whose player lives on the heap, reached through game+0x100 -> [+0x18] -> +0x10.
+    /// Some runs also leave a pointer straight to the player at game+0x200
+    ///
+    fn target(object: u64, player: u64, direct: u64) -> SharedSource {
+        share_source(
+            MockTarget::new("game")
+                .with_module(
+                    "game",
+                    0x40_0000,
+                    page(&[(0x100, object), (0x200, direct)]),
+                    PAGE_READONLY,
+                )
+                .with_region(object, page(&[(0x18, player)]), PAGE_READWRITE)
+                .with_region(player, page(&[]), PAGE_READWRITE),
+        )
+    }
+
+    fn scan(target: &SharedSource, address: u64) -> PointerScan {
+        let progress = ScanProgress::default();
+        let regions = scannable_regions(target).unwrap();
+        let map = PointerMap::build(target, address, &regions, &progress).unwrap();
+        let chains = map.scan(&PointerScanOptions::default(), &progress);
+        PointerScan { map, chains }
+    }
+
+    #[test]
+    fn static_chains_are_found() {
+        let target = target(0x100_0000, 0x200_0000, 0x200_0000);
+        let found = scan(&target, 0x200_0010);
+
+        assert_eq!(
+            found.chains,
+            [
+                PointerChain::new(Some("game"), 0x200, vec![0x10]),
+                PointerChain::new(Some("game"), 0x100, vec![0x18, 0x10]),
+            ]
+        );
+        for chain in found.chains.iter() {
+            assert_eq!(
+                chain.resolve(&mut *lock_source(&target)).unwrap(),
+                0x200_0010
+            );
+        }
+    }
+
+    #[test]
+    fn only_chains_surviving_a_restart_are_kept() {
+        let first = scan(&target(0x100_0000, 0x200_0000, 0x200_0000), 0x200_0010);
+        // After a restart the heap has moved and game+0x200 points somewhere else
+        let second = scan(&target(0x180_0000, 0x280_0000, 0x280_0800), 0x280_0010);
+
+        assert_eq!(second.chains.len(), 1);
+        assert_eq!(
+            intersect_chains(&first.chains, &second.chains),
+            [PointerChain::new(Some("game"), 0x100, vec![0x18, 0x10])]
+        );
+        assert!(intersect_chains(&first.chains, &[]).is_empty());
+    }
+
+    #[test]
+    fn stored_maps_scan_the_same() {
+        let found = scan(&target(0x100_0000, 0x200_0000, 0x200_0000), 0x200_0010);
+
+        let path = std::env::temp_dir()
+            .join(format!(
+                "procvamp-{}.{}",
+                std::process::id(),
+                POINTER_MAP_EXTENSION
+            ))
+            .to_string_lossy()
+            .into_owned();
+        found.map.save(&path).unwrap();
+        let loaded = PointerMap::load(&path);
+
+        // Anything else is refused
+        std::fs::write(&path, b"PVSN").unwrap();
+        let refused = PointerMap::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(refused.is_err());
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.address, found.map.address);
+        assert_eq!(loaded.bitness, found.map.bitness);
+        assert_eq!(loaded.modules, found.map.modules);
+        assert_eq!(loaded.pointers, found.map.pointers);
+
+        let chains = loaded.scan(&PointerScanOptions::default(), &ScanProgress::default());
+        assert_eq!(chains, found.chains);
+    }
+}
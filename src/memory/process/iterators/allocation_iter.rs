@@ -1,9 +1,8 @@
 use super::super::process::Process;
+use super::super::types::MemoryRegion;
 use std::ffi::c_void;
 use windows::Win32::Foundation::HANDLE;
-use windows::Win32::System::Memory::{
-    VirtualQueryEx, MEMORY_BASIC_INFORMATION, PAGE_PROTECTION_FLAGS,
-};
+use windows::Win32::System::Memory::{VirtualQueryEx, MEMORY_BASIC_INFORMATION};
 
 /// Create an allocation iteration struct, this will let us iterate through every valid allocation
 /// within a process that's passed to the 'new' constructor
@@ -28,20 +27,17 @@ impl Allocation {
     }
     /// Filter allocations with a certain protection
     ///
-    pub fn with_protection(
-        self,
-        prot: PAGE_PROTECTION_FLAGS,
-    ) -> impl Iterator<Item = MEMORY_BASIC_INFORMATION> {
-        self.filter(move |entry| entry.Protect == prot)
+    pub fn with_protection(self, prot: u32) -> impl Iterator<Item = MemoryRegion> {
+        self.filter(move |entry| entry.protect == prot)
     }
 }
 
 /// Implement the Iterator type for our struct
 ///
 impl Iterator for Allocation {
-    /// Our type will just be a MEMORY_BASIC_INFORMATION
+    /// Our type will be the platform neutral region converted from MEMORY_BASIC_INFORMATION
     ///
-    type Item = MEMORY_BASIC_INFORMATION;
+    type Item = MemoryRegion;
     /// Implement the next function, this will effectively just get the last allocation and
     /// virtualquery onto the next allocation after curr.regionsize
     ///
@@ -66,7 +62,7 @@ impl Iterator for Allocation {
                 None => return None,
             };
 
-            Some(self.entry)
+            Some(self.entry.into())
         }
     }
 }
@@ -0,0 +1,34 @@
+use super::super::super::process::Process;
+use super::super::super::types::MemoryRegion;
+use super::maps::{read_maps, MapsEntry};
+
+/// Create an allocation iteration struct, this will let us iterate through every mapping within a
+/// process that's passed to the 'new' constructor. On linux this is all read out of
+/// /proc/<pid>/maps in one go rather than being queried region by region
+///
+pub struct Allocation {
+    entries: std::vec::IntoIter<MapsEntry>,
+}
+
+impl Allocation {
+    /// Simple constructor for our iteration, all we need from the process is its pid
+    ///
+    pub unsafe fn new(process: &mut Process) -> anyhow::Result<Self> {
+        Ok(Self {
+            entries: read_maps(process.pid())?.into_iter(),
+        })
+    }
+}
+
+/// Implement the Iterator type for our struct
+///
+impl Iterator for Allocation {
+    /// Our type will be the same platform neutral region the windows iterator gives back
+    ///
+    type Item = MemoryRegion;
+    /// Move onto the next mapping and convert it over
+    ///
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|entry| MemoryRegion::from(&entry))
+    }
+}
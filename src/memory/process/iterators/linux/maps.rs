@@ -0,0 +1,148 @@
+use super::super::super::types::*;
+
+/// A single parsed line out of /proc/<pid>/maps, both the allocation and module iterators on linux
+/// are built on top of this
+///
+#[derive(Debug, Clone, Default)]
+pub struct MapsEntry {
+    pub start: u64,
+    pub end: u64,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+    pub shared: bool,
+    pub path: String,
+}
+
+impl MapsEntry {
+    /// Parse a maps line, the format is "start-end perms offset dev inode    path" where the path
+    /// is optional and padded out with spaces. Lines that end before they start are refused
+    ///
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, ' ');
+
+        let (start, end) = fields.next()?.split_once('-')?;
+        let perms = fields.next()?.as_bytes();
+        // Skip over the offset, device and inode, we've no use for them
+        fields.next()?;
+        fields.next()?;
+        fields.next()?;
+        let path = fields.next().unwrap_or_default().trim_start().to_string();
+
+        let start = u64::from_str_radix(start, 16).ok()?;
+        let end = u64::from_str_radix(end, 16).ok()?;
+        if perms.len() < 4 || end < start {
+            return None;
+        }
+
+        Some(Self {
+            start,
+            end,
+            read: perms[0] == b'r',
+            write: perms[1] == b'w',
+            execute: perms[2] == b'x',
+            shared: perms[3] == b's',
+            path,
+        })
+    }
+    /// Check if the mapping is backed by a file on disk rather than being anonymous or one of the
+    /// special kernel mappings like [heap] or [stack]
+    ///
+    pub fn is_file_backed(&self) -> bool {
+        self.path.starts_with('/') && !self.path.starts_with("/dev/")
+    }
+    /// Map the rwx permissions onto the PAGE_* protection values our views understand
+    ///
+    pub fn protection(&self) -> u32 {
+        match (self.read, self.write, self.execute) {
+            (false, false, false) => PAGE_NOACCESS,
+            (true, false, false) => PAGE_READONLY,
+            (_, true, false) => PAGE_READWRITE,
+            (false, false, true) => PAGE_EXECUTE,
+            (true, false, true) => PAGE_EXECUTE_READ,
+            (_, true, true) => PAGE_EXECUTE_READWRITE,
+        }
+    }
+    /// Map the mapping kind onto the MEM_* types, private file mappings are what the loader
+    /// produces for images while shared ones are regular file mappings
+    ///
+    pub fn region_type(&self) -> u32 {
+        match (self.is_file_backed(), self.shared) {
+            (true, false) => MEM_IMAGE,
+            (true, true) | (false, true) => MEM_MAPPED,
+            (false, false) => MEM_PRIVATE,
+        }
+    }
+}
+
+impl From<&MapsEntry> for MemoryRegion {
+    fn from(entry: &MapsEntry) -> Self {
+        Self {
+            base_address: entry.start,
+            region_size: entry.end - entry.start,
+            protect: entry.protection(),
+            // Anything listed within maps is always backed so it's effectively committed
+            state: MEM_COMMIT,
+            region_type: entry.region_type(),
+        }
+    }
+}
+
+/// Read and parse every mapping of a process
+///
+pub fn read_maps(pid: u32) -> anyhow::Result<Vec<MapsEntry>> {
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid))?;
+    Ok(maps.lines().filter_map(MapsEntry::parse).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_maps_lines() {
+        let image = MapsEntry::parse(
+            "55d0c3a00000-55d0c3a21000 r-xp 00002000 08:02 173521                     /usr/bin/cat",
+        )
+        .unwrap();
+        assert_eq!((image.start, image.end), (0x55d0c3a00000, 0x55d0c3a21000));
+        assert!(image.read && !image.write && image.execute && !image.shared);
+        assert_eq!(image.path, "/usr/bin/cat");
+        assert_eq!(image.protection(), PAGE_EXECUTE_READ);
+        assert_eq!(image.region_type(), MEM_IMAGE);
+
+        // Paths keep their spaces and the kernel's marker for files removed since being mapped
+        let spaced = MapsEntry::parse(
+            "7f1c2e400000-7f1c2e401000 rw-s 00000000 08:02 98213   /home/user/My Games/save data.bin",
+        )
+        .unwrap();
+        assert_eq!(spaced.path, "/home/user/My Games/save data.bin");
+        assert_eq!(spaced.region_type(), MEM_MAPPED);
+        let deleted = MapsEntry::parse(
+            "7f1c2e600000-7f1c2e628000 r--p 00000000 08:02 98214   /opt/app/libgame.so (deleted)",
+        )
+        .unwrap();
+        assert_eq!(deleted.path, "/opt/app/libgame.so (deleted)");
+        assert!(deleted.is_file_backed());
+
+        // Kernel mappings and anonymous memory aren't file backed
+        let vdso =
+            MapsEntry::parse("7ffd8a5f2000-7ffd8a5f4000 r-xp 00000000 00:00 0   [vdso]").unwrap();
+        assert_eq!(vdso.path, "[vdso]");
+        assert!(!vdso.is_file_backed());
+        assert_eq!(vdso.region_type(), MEM_PRIVATE);
+        let anonymous =
+            MapsEntry::parse("7f1c2e700000-7f1c2e800000 ---p 00000000 00:00 0 ").unwrap();
+        assert!(anonymous.path.is_empty());
+        assert_eq!(anonymous.protection(), PAGE_NOACCESS);
+    }
+
+    #[test]
+    fn malformed_maps_lines_are_refused() {
+        assert!(MapsEntry::parse("").is_none());
+        assert!(MapsEntry::parse("7f1c2e700000 rw-p 00000000 00:00 0").is_none());
+        assert!(MapsEntry::parse("7f1c2e700000-zz rw-p 00000000 00:00 0").is_none());
+        assert!(MapsEntry::parse("7f1c2e700000-7f1c2e800000 rw 00000000 00:00 0").is_none());
+        assert!(MapsEntry::parse("7f1c2e800000-7f1c2e700000 rw-p 00000000 00:00 0").is_none());
+    }
+}
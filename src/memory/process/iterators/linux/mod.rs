@@ -0,0 +1,4 @@
+pub mod allocation_iter;
+pub mod maps;
+pub mod module_iter;
+pub mod process_iter;
@@ -0,0 +1,62 @@
+use super::super::super::types::ModuleInfo;
+use super::maps::read_maps;
+
+/// Define our custom iterator for modules, on linux there's no module list to walk so we build one
+/// from every file backed mapping within /proc/<pid>/maps
+///
+pub struct ModuleIterator {
+    modules: std::vec::IntoIter<ModuleInfo>,
+}
+
+impl ModuleIterator {
+    /// Define our constructor, this will group up every mapping of the same file into one module
+    /// spanning from its lowest to highest mapped address
+    ///
+    pub unsafe fn new(pid: u32) -> anyhow::Result<Self> {
+        let mut modules: Vec<ModuleInfo> = Vec::new();
+
+        for entry in read_maps(pid)?
+            .into_iter()
+            .filter(|entry| entry.is_file_backed())
+        {
+            // Maps is sorted by address so the first mapping we see of a file is its base
+            match modules.iter_mut().find(|module| module.path == entry.path) {
+                Some(module) => {
+                    module.size = std::cmp::max(module.size, entry.end - module.base_address);
+                }
+                None => {
+                    let name = entry
+                        .path
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+
+                    modules.push(ModuleInfo {
+                        name,
+                        path: entry.path.clone(),
+                        base_address: entry.start,
+                        size: entry.end - entry.start,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            modules: modules.into_iter(),
+        })
+    }
+}
+
+/// Define iterator type for struct
+///
+impl Iterator for ModuleIterator {
+    /// Define our item, same platform neutral module as on windows
+    ///
+    type Item = ModuleInfo;
+    /// Define our .next() function
+    ///
+    fn next(&mut self) -> Option<Self::Item> {
+        self.modules.next()
+    }
+}
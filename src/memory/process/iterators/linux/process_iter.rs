@@ -0,0 +1,101 @@
+use super::super::super::types::ProcessEntry;
+
+/// Define our custom iterator for processes, on linux every numeric directory under /proc is a
+/// running process
+///
+pub struct ProcessIterator {
+    entries: std::fs::ReadDir,
+}
+
+impl ProcessIterator {
+    /// Define our constructor to open up /proc and get ready to iterate through all entries
+    ///
+    pub unsafe fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            entries: std::fs::read_dir("/proc")?,
+        })
+    }
+}
+
+/// Get the name of a process, we prefer the file name of the executable since it matches up with
+/// the module name later on but we can only read that link for processes we have access to, so
+/// fall back onto comm otherwise (which the kernel truncates to 15 characters)
+///
+fn process_name(pid: u32) -> Option<String> {
+    name_from(
+        std::fs::read_link(format!("/proc/{}/exe", pid)).ok(),
+        || std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok(),
+    )
+}
+
+/// Pick the name out of whatever we managed to read for a process, comm is only read when the
+/// executable's path is no use
+///
+fn name_from(
+    exe_path: Option<std::path::PathBuf>,
+    comm: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    if let Some(file_name) = exe_path.as_deref().and_then(std::path::Path::file_name) {
+        return Some(file_name.to_string_lossy().to_string());
+    }
+
+    comm().map(|comm| comm.trim_end().to_string())
+}
+
+/// Define iterator type for struct
+///
+impl Iterator for ProcessIterator {
+    /// Define our item, same platform neutral entry as on windows
+    ///
+    type Item = ProcessEntry;
+    /// Define our .next() function, anything that isn't a pid or exited while we were iterating
+    /// is just skipped over
+    ///
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.entries.by_ref().flatten() {
+            let pid = match entry.file_name().to_str().map(|name| name.parse::<u32>()) {
+                Some(Ok(pid)) => pid,
+                _ => continue,
+            };
+
+            if let Some(name) = process_name(pid) {
+                return Some(ProcessEntry { pid, name });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_prefer_the_executable_over_comm() {
+        let exe = Some(std::path::PathBuf::from("/usr/lib/game/game-server"));
+        assert_eq!(
+            name_from(exe, || Some("game-server-lo\n".to_string())),
+            Some("game-server".to_string())
+        );
+
+        // Without access to the exe link, or one without a file name, we fall back onto comm
+        assert_eq!(
+            name_from(None, || Some("kworker/0:1\n".to_string())),
+            Some("kworker/0:1".to_string())
+        );
+        assert_eq!(
+            name_from(Some("/".into()), || Some("init\n".to_string())),
+            Some("init".to_string())
+        );
+        assert_eq!(name_from(None, || None), None);
+    }
+
+    #[test]
+    fn finds_our_own_process() {
+        let name = process_name(std::process::id()).unwrap();
+        assert!(unsafe { ProcessIterator::new() }
+            .unwrap()
+            .any(|entry| entry.pid == std::process::id() && entry.name == name));
+    }
+}
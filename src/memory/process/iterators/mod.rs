@@ -1,3 +1,11 @@
+#[cfg(windows)]
 pub mod allocation_iter;
+#[cfg(windows)]
 pub mod module_iter;
+#[cfg(windows)]
 pub mod process_iter;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::{allocation_iter, module_iter, process_iter};
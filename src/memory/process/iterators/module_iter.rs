@@ -1,3 +1,4 @@
+use super::super::types::ModuleInfo;
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, Module32First, Module32Next, MODULEENTRY32, TH32CS_SNAPMODULE,
@@ -36,9 +37,9 @@ impl ModuleIterator {
 /// Define iterator type for struct
 ///
 impl Iterator for ModuleIterator {
-    /// Define our item, we'll only entry
+    /// Define our item, we'll convert the entry into our platform neutral module
     ///
-    type Item = MODULEENTRY32;
+    type Item = ModuleInfo;
     /// Define our .next() function, this will be where our iterator moves onto next entries when
     /// needed
     ///
@@ -66,7 +67,7 @@ impl Iterator for ModuleIterator {
                 .iter_mut()
                 .for_each(|e_byte| *e_byte = 0x0);
 
-            Some(entry_clone)
+            Some(entry_clone.into())
         }
     }
 }
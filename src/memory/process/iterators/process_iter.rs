@@ -1,3 +1,4 @@
+use super::super::types::ProcessEntry;
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32, TH32CS_SNAPPROCESS,
@@ -34,9 +35,9 @@ impl ProcessIterator {
 /// Define iterator type for struct
 ///
 impl Iterator for ProcessIterator {
-    /// Define our item, we'll only take the whole entry
+    /// Define our item, we'll convert the entry into our platform neutral process entry
     ///
-    type Item = PROCESSENTRY32;
+    type Item = ProcessEntry;
     /// Define our .next() function, this will be where our iterator moves onto next entries when
     /// needed
     ///
@@ -64,7 +65,7 @@ impl Iterator for ProcessIterator {
                 .iter_mut()
                 .for_each(|e_byte| *e_byte = 0x0);
 
-            Some(proc_copy)
+            Some(proc_copy.into())
         }
     }
 }
@@ -1,29 +1,43 @@
 use super::iterators::allocation_iter::Allocation;
 use super::iterators::module_iter::ModuleIterator;
 use super::iterators::process_iter::ProcessIterator;
+use super::types::{MemoryRegion, ModuleInfo, ProcessEntry};
 use super::ProcessErrors;
-use super::{compare_values, convert_value_to_bytes, extract_value};
-use crate::gui::windows::scanner_view::scanner_view::ScanResult;
-use crate::gui::windows::scanner_view::scanner_view::ScanType;
-use crate::gui::windows::scanner_view::scanner_view::ValueType;
-use crate::to_rstr;
+use crate::memory::source::source::MemorySource;
+#[cfg(windows)]
 use std::ffi::c_void;
+#[cfg(target_os = "linux")]
+use std::fs::{File, OpenOptions};
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::FileExt;
+#[cfg(target_os = "linux")]
+use std::sync::Arc;
+#[cfg(windows)]
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(windows)]
 use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
-use windows::Win32::System::Diagnostics::ToolHelp::MODULEENTRY32;
-use windows::Win32::System::Memory::*;
+#[cfg(windows)]
 use windows::Win32::System::Threading::{OpenProcess, PROCESS_ALL_ACCESS};
 
+/// The handle we keep open to a process, on windows this is what OpenProcess gives back and on
+/// linux it's /proc/<pid>/mem which we read and write through directly
+///
+#[cfg(windows)]
+pub type ProcessHandle = HANDLE;
+#[cfg(target_os = "linux")]
+pub type ProcessHandle = Option<Arc<File>>;
+
 #[derive(Debug, Clone)]
 pub struct Process {
     process_name: String,
-    process_handle: HANDLE,
+    process_handle: ProcessHandle,
     process_id: u32,
     process_base: u64,
 }
 
 /// Implement the Drop type for our process struct to automatically close handle on destruction
 ///
+#[cfg(windows)]
 impl Drop for Process {
     fn drop(&mut self) {
         unsafe {
@@ -34,48 +48,49 @@ impl Drop for Process {
     }
 }
 
+/// Handles are just values the kernel hands back to us, nothing about them is tied to the thread
+/// that opened them so it's fine for a process to be moved onto another thread
+///
+#[cfg(windows)]
+unsafe impl Send for Process {}
+
+/// Convert an entry from our process iterator into a process we can later open
+///
+impl From<ProcessEntry> for Process {
+    fn from(entry: ProcessEntry) -> Self {
+        Self {
+            process_name: entry.name,
+            process_handle: ProcessHandle::default(),
+            process_id: entry.pid,
+            process_base: u64::default(),
+        }
+    }
+}
+
 impl Process {
     /// Constructor to get all running processes as a list
     ///
     pub unsafe fn get_processes() -> anyhow::Result<Vec<Self>> {
-        ProcessIterator::new()?
-            .map(|entry| {
-                // Get process name
-                let proc_name: String = to_rstr!(entry.szExeFile);
-
-                Ok(Self {
-                    process_name: proc_name,
-                    process_handle: HANDLE::default(),
-                    process_id: entry.th32ProcessID,
-                    process_base: u64::default(),
-                })
-            })
-            .collect()
+        Ok(ProcessIterator::new()?.map(Self::from).collect())
     }
     /// Get running modules under a process as a list
     ///
-    pub unsafe fn get_modules(&self) -> anyhow::Result<Vec<MODULEENTRY32>> {
+    pub unsafe fn get_modules(&self) -> anyhow::Result<Vec<ModuleInfo>> {
         Ok(ModuleIterator::new(self.process_id)?.collect())
     }
     /// Constructor to find a process by name and extract all the key information we'll need to perform analysis on said process
     ///
+    #[cfg(windows)]
     pub unsafe fn find(name_of_process: &str) -> anyhow::Result<Self> {
         ProcessIterator::new()?
-            .find(|entry| to_rstr!(entry.szExeFile) == name_of_process)
-            .map(|entry| {
-                // Get process name
-                let proc_name: String = to_rstr!(entry.szExeFile);
-
-                Ok(Self {
-                    process_name: proc_name,
-                    process_handle: HANDLE::default(),
-                    process_id: entry.th32ProcessID,
-                    process_base: u64::default(),
-                })
+            .find(|entry| entry.name == name_of_process)
+            .map(Self::from)
+            .ok_or_else(|| {
+                ProcessErrors::ProcessNotFound {
+                    process_name: name_of_process.to_string(),
+                }
+                .into()
             })
-            .ok_or_else(|| ProcessErrors::ProcessNotFound {
-                process_name: name_of_process.to_string(),
-            })?
     }
     /// Write value of type T to the given process at location addr_to_write
     ///
@@ -84,16 +99,59 @@ impl Process {
         addr_to_write: usize,
         value_to_write: T,
     ) -> anyhow::Result<()> {
-        let handle = self.get_handle()?;
-        WriteProcessMemory(
-            handle,
-            addr_to_write as *const c_void,
-            &value_to_write as *const T as *const c_void,
+        let value_bytes = std::slice::from_raw_parts(
+            &value_to_write as *const T as *const u8,
             std::mem::size_of::<T>(),
-            None,
-        )?;
-        Ok(())
+        );
+        self.write_bytes(addr_to_write, value_bytes)
+    }
+    /// Read memory of type T from the process at the given location addr_to_read
+    ///
+    pub unsafe fn read<T /*: std::fmt::Display*/>(
+        &mut self,
+        addr_to_read: usize,
+    ) -> anyhow::Result<T> {
+        let buffer_vec = self.read_bytes(addr_to_read, std::mem::size_of::<T>())?;
+        // Create an uninitialized value of type T
+        let mut result_value: std::mem::MaybeUninit<T> = std::mem::MaybeUninit::uninit();
+        // Use copy_nonoverlapping to copy the bytes from the buffer vector to the target type
+        std::ptr::copy_nonoverlapping(
+            buffer_vec.as_ptr(),
+            result_value.as_mut_ptr() as *mut u8,
+            std::mem::size_of::<T>(),
+        );
+        // Convert from MaybeUninit<T> to T
+        Ok(result_value.assume_init())
+    }
+    /// Return process name
+    ///
+    pub fn name(&self) -> String {
+        self.process_name.clone()
     }
+    /// Return process id
+    ///
+    pub fn pid(&self) -> u32 {
+        self.process_id
+    }
+    /// Return process base
+    ///
+    pub unsafe fn base(&mut self) -> anyhow::Result<u64> {
+        if self.process_base == u64::default() {
+            self.process_base = ModuleIterator::new(self.process_id)?
+                .find(|module| module.name == self.process_name)
+                .map(|module| module.base_address)
+                .ok_or_else(|| ProcessErrors::ModuleNotFound {
+                    module_name: self.process_name.clone(),
+                })?;
+        }
+        Ok(self.process_base)
+    }
+}
+
+/// Everything that has to talk to the operating system directly on windows
+///
+#[cfg(windows)]
+impl Process {
     /// Write an array of bytes to the given process at location addr_to_write
     ///
     pub unsafe fn write_bytes(
@@ -112,32 +170,6 @@ impl Process {
         )?;
         Ok(())
     }
-    /// Read memory of type T from the process at the given location addr_to_read
-    ///
-    pub unsafe fn read<T /*: std::fmt::Display*/>(
-        &mut self,
-        addr_to_read: usize,
-    ) -> anyhow::Result<T> {
-        let handle = self.get_handle()?;
-        let mut buffer_vec: Vec<u8> = vec![0; std::mem::size_of::<T>()];
-        ReadProcessMemory(
-            handle,
-            addr_to_read as *const c_void,
-            buffer_vec.as_mut_ptr() as _,
-            std::mem::size_of::<T>(),
-            None,
-        )?;
-        // Create an uninitialized value of type T
-        let mut result_value: std::mem::MaybeUninit<T> = std::mem::MaybeUninit::uninit();
-        // Use copy_nonoverlapping to copy the bytes from the buffer vector to the target type
-        std::ptr::copy_nonoverlapping(
-            buffer_vec.as_ptr(),
-            result_value.as_mut_ptr() as *mut u8,
-            std::mem::size_of::<T>(),
-        );
-        // Convert from MaybeUninit<T> to T
-        Ok(result_value.assume_init())
-    }
     /// Read bytes from the process at the given location addr_to_read
     ///
     pub unsafe fn read_bytes(
@@ -191,132 +223,83 @@ impl Process {
         self.process_handle = HANDLE::default();
         Ok(())
     }
-    /// Return process name
-    ///
-    pub fn name(&self) -> String {
-        self.process_name.clone()
-    }
-    /// Return process id
-    ///
-    pub fn pid(&self) -> u32 {
-        self.process_id
-    }
-    /// Return process base
+}
+
+/// Everything that has to talk to the operating system directly on linux, all memory access goes
+/// through /proc/<pid>/mem which the kernel guards with the same checks as a ptrace attach (same
+/// user and a permissive ptrace_scope, or CAP_SYS_PTRACE)
+///
+#[cfg(target_os = "linux")]
+impl Process {
+    /// Write an array of bytes to the given process at location addr_to_write
     ///
-    pub unsafe fn base(&mut self) -> anyhow::Result<u64> {
-        if self.process_base == u64::default() {
-            self.process_base = ModuleIterator::new(self.process_id)?
-                .find(|module| to_rstr!(module.szModule) == self.process_name.clone())
-                .map(|module| module.modBaseAddr as u64)
-                .ok_or_else(|| ProcessErrors::ModuleNotFound {
-                    module_name: self.process_name.clone(),
-                })?;
-        }
-        Ok(self.process_base)
+    pub unsafe fn write_bytes(
+        &mut self,
+        addr_to_write: usize,
+        value_to_write: &[u8],
+    ) -> anyhow::Result<()> {
+        let handle = self.get_handle()?;
+        handle.write_all_at(value_to_write, addr_to_write as u64)?;
+        Ok(())
     }
-    /// Read partitions of large allocations
+    /// Read bytes from the process at the given location addr_to_read
     ///
-    pub unsafe fn read_bytes_paged(
+    pub unsafe fn read_bytes(
         &mut self,
-        address: usize,
-        size: usize,
+        addr_to_read: usize,
+        size_to_read: usize,
     ) -> anyhow::Result<Vec<u8>> {
-        let step_size: usize = std::cmp::min(size, 0x1000);
-        let mut buffer = Vec::with_capacity(size);
-
-        for i in (0..size).step_by(step_size) {
-            let remaining = size - i;
-            let size_to_read = std::cmp::min(step_size, remaining);
-
-            let page_result = self.read_bytes(address + i, size_to_read);
-
-            match page_result {
-                Ok(page_data) => {
-                    buffer.extend_from_slice(&page_data);
-                }
-                Err(_) => {
-                    // If a page fails, fill with zeros and continue
-                    buffer.extend_from_slice(&vec![0u8; size_to_read]);
-                }
-            }
-        }
-
-        Ok(buffer)
+        let handle = self.get_handle()?;
+        let mut buffer_vec: Vec<u8> = vec![0; size_to_read];
+        handle.read_exact_at(&mut buffer_vec, addr_to_read as u64)?;
+        Ok(buffer_vec)
     }
-    /// Search through allocations for specific data
+    /// Open or return an open handle to a targeted process
     ///
-    pub unsafe fn find_data<T>(
-        &mut self,
-        scan_type: ScanType,
-        value_type: ValueType,
-        value: T,
-        fast_scan: bool,
-    ) -> anyhow::Result<Vec<ScanResult>>
-    where
-        T: Clone + std::fmt::Debug + 'static,
-    {
-        // Our return result
-        let mut result = Vec::new();
-        // So we're going to want to take in three parameters, fast or slow scan as well as what type
-        // we're searching for and lastly the value we're looking for
-        let allocations: Vec<MEMORY_BASIC_INFORMATION> = Allocation::new(self)?.collect();
+    pub unsafe fn get_handle(&mut self) -> anyhow::Result<Arc<File>> {
+        // Check if fields are valid
+        if self.process_id == 0 {
+            Err(ProcessErrors::InvalidProcessFields)?
+        }
 
-        // Get our value into bytes for comparison
-        let value_bytes = convert_value_to_bytes(&value, &value_type)?;
+        // Check if handle is already open
+        if let Some(handle) = &self.process_handle {
+            return Ok(handle.clone());
+        }
 
-        // Set the step size depending on the valuetype
-        let step_size = match value_type {
-            ValueType::Byte => 1,
-            ValueType::TwoBytes => 2,
-            ValueType::FourBytes => 4,
-            ValueType::EightBytes => 8,
+        // Open and return otherwise, if we aren't allowed to write we can still try to at least
+        // read from the process
+        let mem_path = format!("/proc/{}/mem", self.process_id);
+        let mem_file = match OpenOptions::new().read(true).write(true).open(&mem_path) {
+            Ok(file) => file,
+            Err(_) => File::open(&mem_path)?,
         };
+        let handle = Arc::new(mem_file);
+        self.process_handle = Some(handle.clone());
+        Ok(handle)
+    }
+}
 
-        // Iterate over all allocations now
-        for alloc in allocations.iter() {
-            // Skip if region has unsuitable protection (e.g., no read access)
-            if alloc.Protect.0 & (PAGE_NOACCESS.0 | PAGE_GUARD.0) != 0 {
-                continue;
-            }
-
-            // Only scan regions that have read access
-            if alloc.Protect.0
-                & (PAGE_READONLY.0
-                    | PAGE_READWRITE.0
-                    | PAGE_EXECUTE_READ.0
-                    | PAGE_EXECUTE_READWRITE.0)
-                == 0
-            {
-                continue;
-            }
-            let alloc_bytes =
-                self.read_bytes_paged(alloc.BaseAddress as _, alloc.RegionSize as _)?;
-            // Iterate through the allocation now
-            for idx in (0..alloc_bytes.len()).step_by(if fast_scan { step_size } else { 1 }) {
-                if idx + step_size > alloc_bytes.len() {
-                    break;
-                }
-
-                // Check if the value matches
-                let is_match = compare_values(
-                    &alloc_bytes[idx..idx + step_size],
-                    &value_bytes,
-                    &scan_type,
-                    &value_type,
-                )?;
-
-                if is_match {
-                    let found_value =
-                        extract_value(&alloc_bytes[idx..idx + step_size], &value_type);
-
-                    result.push(ScanResult::new(
-                        (alloc.BaseAddress as u64) + idx as u64,
-                        found_value,
-                    ));
-                }
-            }
-        }
-
-        Ok(result)
+/// Hook the process up to the rest of procvamp, all of this just forwards onto the platform
+/// specific functions above
+///
+impl MemorySource for Process {
+    fn name(&self) -> String {
+        self.process_name.clone()
+    }
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        unsafe { Process::read_bytes(self, address, size) }
+    }
+    fn write_bytes(&mut self, address: usize, bytes: &[u8]) -> anyhow::Result<()> {
+        unsafe { Process::write_bytes(self, address, bytes) }
+    }
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+        unsafe { Ok(Allocation::new(self)?.collect()) }
+    }
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+        unsafe { self.get_modules() }
+    }
+    fn base(&mut self) -> anyhow::Result<u64> {
+        unsafe { Process::base(self) }
     }
 }
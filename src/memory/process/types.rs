@@ -0,0 +1,153 @@
+/// Platform neutral descriptions of what a target exposes, every backend converts its own native
+/// structures into these so the views never have to know whether they're looking at a windows or
+/// linux process.
+///
+/// Protection, state and type values follow the windows PAGE_*/MEM_* encoding since that's what
+/// the views were originally built around, other backends map their flags onto them.
+///
+pub const PAGE_NOACCESS: u32 = 0x01;
+pub const PAGE_READONLY: u32 = 0x02;
+pub const PAGE_READWRITE: u32 = 0x04;
+pub const PAGE_WRITECOPY: u32 = 0x08;
+pub const PAGE_EXECUTE: u32 = 0x10;
+pub const PAGE_EXECUTE_READ: u32 = 0x20;
+pub const PAGE_EXECUTE_READWRITE: u32 = 0x40;
+pub const PAGE_EXECUTE_WRITECOPY: u32 = 0x80;
+pub const PAGE_GUARD: u32 = 0x100;
+pub const PAGE_NOCACHE: u32 = 0x200;
+pub const PAGE_WRITECOMBINE: u32 = 0x400;
+
+pub const MEM_COMMIT: u32 = 0x1000;
+pub const MEM_RESERVE: u32 = 0x2000;
+pub const MEM_FREE: u32 = 0x10000;
+pub const MEM_PRIVATE: u32 = 0x20000;
+pub const MEM_MAPPED: u32 = 0x40000;
+pub const MEM_IMAGE: u32 = 0x1000000;
+
+/// A single region of memory within a target, this is what the allocation iterators yield
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub base_address: u64,
+    pub region_size: u64,
+    pub protect: u32,
+    pub state: u32,
+    pub region_type: u32,
+}
+
+impl MemoryRegion {
+    /// Check if the region can be read from
+    ///
+    pub fn is_readable(&self) -> bool {
+        // Skip if region has unsuitable protection (e.g., no read access)
+        if self.protect & (PAGE_NOACCESS | PAGE_GUARD) != 0 {
+            return false;
+        }
+
+        self.protect
+            & (PAGE_READONLY
+                | PAGE_READWRITE
+                | PAGE_WRITECOPY
+                | PAGE_EXECUTE_READ
+                | PAGE_EXECUTE_READWRITE
+                | PAGE_EXECUTE_WRITECOPY)
+            != 0
+    }
+    /// Check if the region can be written to, copy on write regions included
+    ///
+    pub fn is_writable(&self) -> bool {
+        self.protect
+            & (PAGE_READWRITE | PAGE_WRITECOPY | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY)
+            != 0
+    }
+    /// Check if code within the region can be run
+    ///
+    pub fn is_executable(&self) -> bool {
+        self.protect
+            & (PAGE_EXECUTE | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY)
+            != 0
+    }
+    /// Check if writes to the region get a private copy of the page
+    ///
+    pub fn is_copy_on_write(&self) -> bool {
+        self.protect & (PAGE_WRITECOPY | PAGE_EXECUTE_WRITECOPY) != 0
+    }
+    /// Check if an address lies within this region
+    ///
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base_address && address - self.base_address < self.region_size
+    }
+}
+
+/// A module (image or mapped file) loaded within a target
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleInfo {
+    pub name: String,
+    pub path: String,
+    pub base_address: u64,
+    pub size: u64,
+}
+
+impl ModuleInfo {
+    /// Check if an address lies within this module
+    ///
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base_address && address - self.base_address < self.size
+    }
+}
+
+/// A thread within a target, live processes don't fill these in yet but dumps carry them along
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadInfo {
+    pub thread_id: u32,
+    pub teb: u64,
+    pub stack_start: u64,
+    pub stack_size: u64,
+    pub instruction_pointer: Option<u64>,
+    pub stack_pointer: Option<u64>,
+}
+
+/// A running process as seen by the process iterators
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub name: String,
+}
+
+#[cfg(windows)]
+impl From<windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION> for MemoryRegion {
+    fn from(entry: windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION) -> Self {
+        Self {
+            base_address: entry.BaseAddress as u64,
+            region_size: entry.RegionSize as u64,
+            protect: entry.Protect.0,
+            state: entry.State.0,
+            region_type: entry.Type.0,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<windows::Win32::System::Diagnostics::ToolHelp::MODULEENTRY32> for ModuleInfo {
+    fn from(entry: windows::Win32::System::Diagnostics::ToolHelp::MODULEENTRY32) -> Self {
+        Self {
+            name: crate::to_rstr!(entry.szModule),
+            path: crate::to_rstr!(entry.szExePath),
+            base_address: entry.modBaseAddr as u64,
+            size: entry.modBaseSize as u64,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<windows::Win32::System::Diagnostics::ToolHelp::PROCESSENTRY32> for ProcessEntry {
+    fn from(entry: windows::Win32::System::Diagnostics::ToolHelp::PROCESSENTRY32) -> Self {
+        Self {
+            pid: entry.th32ProcessID,
+            name: crate::to_rstr!(entry.szExeFile),
+        }
+    }
+}
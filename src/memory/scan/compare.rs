@@ -0,0 +1,237 @@
+use super::types::{FloatMatch, ScanType, ValueType};
+
+/// Helper function to compare values based on scan type used within our scanning function, how
+/// they're compared is up to the value type
+///
+pub fn compare_values(
+    memory_bytes: &[u8],
+    value_bytes: &[u8],
+    scan_type: &ScanType,
+    value_type: &ValueType,
+    float_match: &FloatMatch,
+) -> anyhow::Result<bool> {
+    // Patterns carry wildcards and case folding so they're matched through BytePattern instead,
+    // groups are matched a value at a time through ScanGroup
+    let Some(scannable) = value_type.scannable() else {
+        return Err(anyhow::anyhow!(
+            "{} values can't be compared as a single value",
+            value_type.label()
+        ));
+    };
+
+    scannable.compare(memory_bytes, value_bytes, scan_type, float_match)
+}
+
+/// Compare a freshly read value against both the value it had last scan and the value typed in,
+/// used when narrowing down the results of an earlier scan. Increased by and decreased by are up
+/// to the value type
+///
+pub fn compare_with_previous(
+    memory_bytes: &[u8],
+    previous_bytes: &[u8],
+    value_bytes: &[u8],
+    scan_type: &ScanType,
+    value_type: &ValueType,
+    float_match: &FloatMatch,
+) -> anyhow::Result<bool> {
+    match scan_type {
+        ScanType::Changed => Ok(memory_bytes != previous_bytes),
+        ScanType::Unchanged => Ok(memory_bytes == previous_bytes),
+        ScanType::Increased => compare_values(
+            memory_bytes,
+            previous_bytes,
+            &ScanType::BiggerThan,
+            value_type,
+            float_match,
+        ),
+        ScanType::Decreased => compare_values(
+            memory_bytes,
+            previous_bytes,
+            &ScanType::SmallerThan,
+            value_type,
+            float_match,
+        ),
+        ScanType::IncreasedBy | ScanType::DecreasedBy => {
+            let Some(scannable) = value_type.scannable() else {
+                return Err(anyhow::anyhow!(
+                    "{} values can't be {:?}",
+                    value_type.label(),
+                    scan_type
+                ));
+            };
+
+            scannable.compare_change(
+                memory_bytes,
+                previous_bytes,
+                value_bytes,
+                scan_type,
+                float_match,
+            )
+        }
+        _ => compare_values(
+            memory_bytes,
+            value_bytes,
+            scan_type,
+            value_type,
+            float_match,
+        ),
+    }
+}
+
+/// Last helper function to extract value as string and send it back to return for our scanning
+/// function
+///
+pub fn extract_value(memory_bytes: &[u8], value_type: &ValueType) -> String {
+    match (value_type, value_type.scannable()) {
+        (_, Some(scannable)) => scannable.format(memory_bytes),
+        (ValueType::Ascii, None) => String::from_utf8_lossy(memory_bytes).into_owned(),
+        (ValueType::Utf16, None) => String::from_utf16_lossy(
+            &memory_bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        // Arrays of bytes and groups
+        (_, None) => memory_bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::scan::scannable::{ByteArray, CustomType, Scannable};
+    use std::sync::Arc;
+
+    /// An ordered type wider than any built in number, such as a 128 bit counter
+    ///
+    #[derive(Debug)]
+    struct Wide;
+
+    impl Scannable for Wide {
+        fn label(&self) -> &str {
+            "Wide"
+        }
+        fn size(&self) -> usize {
+            16
+        }
+        fn is_ordered(&self) -> bool {
+            true
+        }
+        fn parse(&self, text: &str, _is_hex: bool) -> anyhow::Result<Vec<u8>> {
+            Ok(text.parse::<u128>()?.to_le_bytes().to_vec())
+        }
+        fn format(&self, bytes: &[u8]) -> String {
+            u128::from_le_bytes(bytes[..16].try_into().unwrap()).to_string()
+        }
+    }
+
+    fn changed_by(
+        value_type: &ValueType,
+        memory: &[u8],
+        previous: &[u8],
+        value: &[u8],
+        scan_type: ScanType,
+    ) -> bool {
+        compare_with_previous(
+            memory,
+            previous,
+            value,
+            &scan_type,
+            value_type,
+            &FloatMatch::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn changes_wrap_around_at_the_size_of_the_type() {
+        let byte = ValueType::Byte;
+        assert!(changed_by(
+            &byte,
+            &[0x01],
+            &[0xFF],
+            &[2],
+            ScanType::IncreasedBy
+        ));
+        assert!(changed_by(
+            &byte,
+            &[0xFF],
+            &[0x01],
+            &[2],
+            ScanType::DecreasedBy
+        ));
+        assert!(!changed_by(
+            &byte,
+            &[0x02],
+            &[0xFF],
+            &[2],
+            ScanType::IncreasedBy
+        ));
+
+        let signed = ValueType::SignedFourBytes;
+        assert!(changed_by(
+            &signed,
+            &(-3i32).to_le_bytes(),
+            &5i32.to_le_bytes(),
+            &8i32.to_le_bytes(),
+            ScanType::DecreasedBy
+        ));
+
+        let float = ValueType::Float;
+        assert!(changed_by(
+            &float,
+            &1.75f32.to_le_bytes(),
+            &1.5f32.to_le_bytes(),
+            &0.25f32.to_le_bytes(),
+            ScanType::IncreasedBy
+        ));
+    }
+
+    #[test]
+    fn changes_carry_across_types_wider_than_eight_bytes() {
+        let wide = ValueType::Custom(CustomType(Arc::new(Wide)));
+        let previous = (u64::MAX as u128).to_le_bytes();
+        let memory = (u64::MAX as u128 + 3).to_le_bytes();
+        let value = 3u128.to_le_bytes();
+
+        assert!(changed_by(
+            &wide,
+            &memory,
+            &previous,
+            &value,
+            ScanType::IncreasedBy
+        ));
+        assert!(changed_by(
+            &wide,
+            &previous,
+            &memory,
+            &value,
+            ScanType::DecreasedBy
+        ));
+        assert!(!changed_by(
+            &wide,
+            &previous,
+            &memory,
+            &value,
+            ScanType::IncreasedBy
+        ));
+    }
+
+    #[test]
+    fn unordered_types_refuse_changes() {
+        let array = ValueType::Custom(CustomType(Arc::new(ByteArray::new("Buffer", 4, false))));
+        assert!(compare_with_previous(
+            &[1; 4],
+            &[0; 4],
+            &[1; 4],
+            &ScanType::IncreasedBy,
+            &array,
+            &FloatMatch::default()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::memory::scan::compare::{compare_values, extract_value};
+use crate::memory::scan::types::{FloatMatch, ScanType, ValueType};
+use thiserror::Error;
+
+/// Errors that can come out of putting a group together
+///
+#[derive(Error, Debug)]
+pub enum GroupErrors {
+    #[error("Group has no values in it")]
+    Empty,
+    #[error("{value_type:?} can't be part of a group")]
+    InvalidMember { value_type: ValueType },
+    #[error("Values at +0x{first:X} and +0x{second:X} overlap")]
+    Overlapping { first: usize, second: usize },
+}
+
+/// A single value within a group, found at an offset from the start of the group
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupMember {
+    pub offset: usize,
+    pub value_type: ValueType,
+    /// What the value has to be, in the bytes it'd have within the target
+    pub bytes: Vec<u8>,
+    /// Floats are rounded to however many decimals their own value was typed in with
+    pub float_match: FloatMatch,
+}
+
+/// Several typed values at fixed offsets from each other, such as health at +0, max health at +4
+/// and a position at +0x10, used to find whole structures in one pass
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanGroup {
+    /// Kept in offset order
+    pub members: Vec<GroupMember>,
+}
+
+impl ScanGroup {
+    /// Put a group together, members have to be plain numbers that don't overlap
+    ///
+    pub fn new(mut members: Vec<GroupMember>) -> anyhow::Result<Self> {
+        if members.is_empty() {
+            return Err(GroupErrors::Empty.into());
+        }
+        if let Some(member) = members
+            .iter()
+            .find(|member| member.value_type.is_pattern() || member.value_type.is_group())
+        {
+            return Err(GroupErrors::InvalidMember {
+                value_type: member.value_type.clone(),
+            }
+            .into());
+        }
+
+        members.sort_by_key(|member| member.offset);
+        for pair in members.windows(2) {
+            if pair[0].offset + pair[0].value_type.size() > pair[1].offset {
+                return Err(GroupErrors::Overlapping {
+                    first: pair[0].offset,
+                    second: pair[1].offset,
+                }
+                .into());
+            }
+        }
+
+        Ok(Self { members })
+    }
+    /// Number of bytes from the start of the group to the end of its last value
+    ///
+    pub fn width(&self) -> usize {
+        self.members
+            .iter()
+            .map(|member| member.offset + member.value_type.size())
+            .max()
+            .unwrap_or(0)
+    }
+    /// Fast scan aligns the group the way its first value would be
+    ///
+    pub fn alignment(&self) -> usize {
+        self.members[0].value_type.alignment()
+    }
+    /// The member searched for before the rest of the group is checked. Floats are matched
+    /// loosely and structs may have padding so neither can be searched for byte for byte, out of
+    /// the rest the widest is picked since it turns up least often
+    ///
+    pub fn anchor(&self) -> Option<&GroupMember> {
+        self.members
+            .iter()
+            .filter(|member| member.value_type.is_ordered() && !member.value_type.is_float())
+            .max_by_key(|member| member.value_type.size())
+    }
+    /// Check whether some bytes start with the group
+    ///
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        if bytes.len() < self.width() {
+            return false;
+        }
+
+        self.members.iter().all(|member| {
+            let range = member.offset..member.offset + member.value_type.size();
+            compare_values(
+                &bytes[range],
+                &member.bytes,
+                &ScanType::Exact,
+                &member.value_type,
+                &member.float_match,
+            )
+            .unwrap_or(false)
+        })
+    }
+    /// Show each value within the group as its own type
+    ///
+    pub fn format(&self, bytes: &[u8]) -> String {
+        self.members
+            .iter()
+            .filter(|member| member.offset + member.value_type.size() <= bytes.len())
+            .map(|member| {
+                let range = member.offset..member.offset + member.value_type.size();
+                extract_value(&bytes[range], &member.value_type)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(offset: usize, value_type: ValueType, bytes: &[u8]) -> GroupMember {
+        GroupMember {
+            offset,
+            value_type,
+            bytes: bytes.to_vec(),
+            float_match: FloatMatch::Rounded { decimals: 1 },
+        }
+    }
+
+    #[test]
+    fn overlapping_and_invalid_members_are_refused() {
+        assert!(ScanGroup::new(Vec::new()).is_err());
+
+        let overlapping = ScanGroup::new(vec![
+            member(2, ValueType::TwoBytes, &[0; 2]),
+            member(0, ValueType::FourBytes, &[0; 4]),
+        ]);
+        let error = overlapping.unwrap_err().to_string();
+        assert!(error.contains("+0x0 and +0x2 overlap"), "{error}");
+
+        // Touching is fine
+        let touching = ScanGroup::new(vec![
+            member(4, ValueType::TwoBytes, &[0; 2]),
+            member(0, ValueType::FourBytes, &[0; 4]),
+        ])
+        .unwrap();
+        assert_eq!(touching.members[0].offset, 0);
+        assert_eq!(touching.width(), 6);
+
+        let pattern = ScanGroup::new(vec![member(0, ValueType::ArrayOfBytes, &[0x90])]);
+        assert!(pattern.is_err());
+    }
+
+    #[test]
+    fn the_widest_exact_member_is_the_anchor() {
+        let group = ScanGroup::new(vec![
+            member(0, ValueType::Byte, &[1]),
+            member(4, ValueType::Double, &1.5f64.to_le_bytes()),
+            member(0x10, ValueType::FourBytes, &100u32.to_le_bytes()),
+            member(0x14, ValueType::TwoBytes, &7u16.to_le_bytes()),
+        ])
+        .unwrap();
+        assert_eq!(group.anchor().unwrap().offset, 0x10);
+        assert_eq!(group.alignment(), 1);
+
+        // Floats can't be searched for byte for byte
+        let floats = ScanGroup::new(vec![
+            member(0, ValueType::Float, &1.5f32.to_le_bytes()),
+            member(4, ValueType::Double, &2.5f64.to_le_bytes()),
+        ])
+        .unwrap();
+        assert!(floats.anchor().is_none());
+    }
+
+    #[test]
+    fn groups_match_every_member() {
+        let group = ScanGroup::new(vec![
+            member(0, ValueType::FourBytes, &100u32.to_le_bytes()),
+            member(8, ValueType::Float, &1.5f32.to_le_bytes()),
+        ])
+        .unwrap();
+
+        let mut bytes = vec![0xCCu8; 12];
+        bytes[..4].copy_from_slice(&100u32.to_le_bytes());
+        bytes[8..].copy_from_slice(&1.54f32.to_le_bytes());
+        assert!(group.matches(&bytes));
+        assert_eq!(group.format(&bytes), "100, 1.54");
+
+        // Floats are only matched to the decimals they were typed in with
+        bytes[8..].copy_from_slice(&1.6f32.to_le_bytes());
+        assert!(!group.matches(&bytes));
+        bytes[8..].copy_from_slice(&1.5f32.to_le_bytes());
+        bytes[0] = 101;
+        assert!(!group.matches(&bytes));
+
+        // Not enough bytes left for the last member
+        assert!(!group.matches(&bytes[..11]));
+    }
+}
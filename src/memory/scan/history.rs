@@ -0,0 +1,344 @@
+use super::group::{GroupMember, ScanGroup};
+use super::region_store::RegionStore;
+use super::results::ScanResults;
+use crate::memory::dump::snapshot::{
+    take_blob, take_string, take_u32, take_u64, take_u8, write_blob, write_string, write_u32,
+    write_u64,
+};
+use crate::memory::scan::types::{FloatMatch, ScanType, ValueType};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::sync::Arc;
+
+/// "PVSS" as it appears at the very start of every saved scan session
+///
+pub const SCAN_SESSION_MAGIC: u32 = 0x5353_5650;
+
+/// Bumped whenever the layout changes, older sessions are refused rather than misread
+///
+const SCAN_SESSION_VERSION: u32 = 1;
+
+/// Extension scan sessions are saved with
+///
+pub const SCAN_SESSION_EXTENSION: &str = "pvscan";
+
+/// What a scan was run with, kept alongside what it found so the history reads as the steps that
+/// were taken
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanParams {
+    pub scan_type: ScanType,
+    pub value_type: ValueType,
+    /// Value as it was typed in, along with the upper bound of a between scan
+    pub value: String,
+    pub value_upper: String,
+    pub is_hex: bool,
+}
+
+impl ScanParams {
+    /// Short description of the step for the history list, e.g. Exact 100 (Four Bytes)
+    ///
+    pub fn describe(&self) -> String {
+        let value = match self.scan_type {
+            _ if !self.scan_type.needs_value() || self.value_type.is_group() => String::new(),
+            ScanType::Between => format!(" {} and {}", self.value, self.value_upper),
+            _ => format!(" {}", self.value),
+        };
+        format!(
+            "{}{} ({})",
+            self.scan_type.label(),
+            value,
+            self.value_type.label()
+        )
+    }
+}
+
+/// A single step of a scan along with what it left behind. Stored scans are too broad to list so
+/// they hold onto the store instead of results
+///
+#[derive(Clone, Debug)]
+pub struct ScanStep {
+    pub params: ScanParams,
+    /// Group the results were found with, used to show them
+    pub group: Option<ScanGroup>,
+    pub results: ScanResults,
+    pub store: Option<Arc<RegionStore>>,
+}
+
+impl ScanStep {
+    /// Number of values still in the running after this step
+    ///
+    pub fn match_count(&self) -> usize {
+        match &self.store {
+            Some(store) => store.match_count(),
+            None => self.results.len(),
+        }
+    }
+}
+
+/// Scan types are stored as their position within ScanType::ALL
+///
+fn write_scan_type(writer: &mut impl Write, scan_type: &ScanType) -> anyhow::Result<()> {
+    let idx = ScanType::ALL.iter().position(|other| other == scan_type);
+    write_u32(writer, idx.unwrap_or_default() as u32)
+}
+
+fn take_scan_type(reader: &mut impl Read) -> anyhow::Result<ScanType> {
+    let idx = take_u32(reader)?;
+    ScanType::ALL
+        .get(idx as usize)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Unknown scan type {idx}"))
+}
+
+/// Value types are stored by name so registered types can be found again
+///
+fn write_value_type(writer: &mut impl Write, value_type: &ValueType) -> anyhow::Result<()> {
+    write_string(writer, value_type.label())
+}
+
+fn take_value_type(reader: &mut impl Read) -> anyhow::Result<ValueType> {
+    let label = take_string(reader)?;
+    ValueType::from_label(&label).ok_or_else(|| anyhow::anyhow!("Unknown value type {label}"))
+}
+
+/// Float matches are stored as a tag followed by whatever setting they carry
+///
+fn write_float_match(writer: &mut impl Write, float_match: &FloatMatch) -> anyhow::Result<()> {
+    let (tag, setting) = match float_match {
+        FloatMatch::Exact => (0, 0),
+        FloatMatch::Rounded { decimals } => (1, *decimals as u64),
+        FloatMatch::Truncated { decimals } => (2, *decimals as u64),
+        FloatMatch::Epsilon(epsilon) => (3, epsilon.to_bits()),
+    };
+    writer.write_all(&[tag])?;
+    write_u64(writer, setting)
+}
+
+fn take_float_match(reader: &mut impl Read) -> anyhow::Result<FloatMatch> {
+    let tag = take_u8(reader)?;
+    let setting = take_u64(reader)?;
+    match tag {
+        0 => Ok(FloatMatch::Exact),
+        1 => Ok(FloatMatch::Rounded {
+            decimals: setting as i32,
+        }),
+        2 => Ok(FloatMatch::Truncated {
+            decimals: setting as i32,
+        }),
+        3 => Ok(FloatMatch::Epsilon(f64::from_bits(setting))),
+        tag => Err(anyhow::anyhow!("Unknown float match {tag}")),
+    }
+}
+
+fn write_step(writer: &mut impl Write, step: &ScanStep) -> anyhow::Result<()> {
+    write_scan_type(writer, &step.params.scan_type)?;
+    write_value_type(writer, &step.params.value_type)?;
+    write_string(writer, &step.params.value)?;
+    write_string(writer, &step.params.value_upper)?;
+    writer.write_all(&[step.params.is_hex as u8])?;
+
+    let members = step
+        .group
+        .as_ref()
+        .map(|group| group.members.as_slice())
+        .unwrap_or_default();
+    writer.write_all(&[step.group.is_some() as u8])?;
+    write_u32(writer, members.len() as u32)?;
+    for member in members {
+        write_u64(writer, member.offset as u64)?;
+        write_value_type(writer, &member.value_type)?;
+        write_blob(writer, &member.bytes)?;
+        write_float_match(writer, &member.float_match)?;
+    }
+
+    step.results.save(writer)
+}
+
+fn take_step(reader: &mut impl Read) -> anyhow::Result<ScanStep> {
+    let params = ScanParams {
+        scan_type: take_scan_type(reader)?,
+        value_type: take_value_type(reader)?,
+        value: take_string(reader)?,
+        value_upper: take_string(reader)?,
+        is_hex: take_u8(reader)? != 0,
+    };
+
+    let has_group = take_u8(reader)? != 0;
+    // The count comes from the file so members are only added as they're read
+    let member_count = take_u32(reader)?;
+    let members = (0..member_count)
+        .map(|_| {
+            Ok(GroupMember {
+                offset: take_u64(reader)? as usize,
+                value_type: take_value_type(reader)?,
+                bytes: take_blob(reader)?,
+                float_match: take_float_match(reader)?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let group = match has_group {
+        true => Some(ScanGroup::new(members)?),
+        false => None,
+    };
+
+    Ok(ScanStep {
+        params,
+        group,
+        results: ScanResults::load(reader)?,
+        store: None,
+    })
+}
+
+/// Write every step of a scan out so it can be picked back up later. Stored scans are copies of
+/// the whole target so they're left out, the hunt can only be resumed from steps with results
+///
+pub fn save_session(path: &str, history: &[ScanStep], current: &ScanStep) -> anyhow::Result<()> {
+    if current.store.is_some() {
+        return Err(anyhow::anyhow!(
+            "Narrow the unknown initial value scan down to a list of results before saving"
+        ));
+    }
+    let steps: Vec<&ScanStep> = history
+        .iter()
+        .filter(|step| step.store.is_none())
+        .chain(std::iter::once(current))
+        .collect();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_u32(&mut writer, SCAN_SESSION_MAGIC)?;
+    write_u32(&mut writer, SCAN_SESSION_VERSION)?;
+
+    write_u32(&mut writer, steps.len() as u32)?;
+    for step in steps {
+        write_step(&mut writer, step)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Load every step of a saved scan back in, the last one is where the hunt was left off
+///
+pub fn load_session(path: &str) -> anyhow::Result<Vec<ScanStep>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    if take_u32(&mut reader)? != SCAN_SESSION_MAGIC {
+        return Err(anyhow::anyhow!("{path} is not a scan session"));
+    }
+    let version = take_u32(&mut reader)?;
+    if version != SCAN_SESSION_VERSION {
+        return Err(anyhow::anyhow!(
+            "Scan session version {version} is not supported, expected {SCAN_SESSION_VERSION}"
+        ));
+    }
+
+    let step_count = take_u32(&mut reader)?;
+    (0..step_count).map(|_| take_step(&mut reader)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::dump::snapshot::SNAPSHOT_MAGIC;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("procvamp-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn step(scan_type: ScanType, value: &str, hits: &[(u64, u32)]) -> ScanStep {
+        let mut results = ScanResults::new(4);
+        for (address, value) in hits {
+            results.push(*address, &value.to_le_bytes());
+        }
+        ScanStep {
+            params: ScanParams {
+                scan_type,
+                value_type: ValueType::FourBytes,
+                value: value.to_string(),
+                value_upper: String::new(),
+                is_hex: false,
+            },
+            group: None,
+            results,
+            store: None,
+        }
+    }
+
+    #[test]
+    fn sessions_round_trip() {
+        let path = temp_path("round-trip.pvscan");
+        let first = step(ScanType::Exact, "100", &[(0x1000, 100), (0x2000, 100)]);
+        let mut current = step(ScanType::Decreased, "", &[(0x2000, 90)]);
+        current.params.value_type = ValueType::Group;
+        current.group = Some(
+            ScanGroup::new(vec![
+                GroupMember {
+                    offset: 4,
+                    value_type: ValueType::Float,
+                    bytes: 1.5f32.to_le_bytes().to_vec(),
+                    float_match: FloatMatch::Rounded { decimals: 1 },
+                },
+                GroupMember {
+                    offset: 0,
+                    value_type: ValueType::FourBytes,
+                    bytes: 90u32.to_le_bytes().to_vec(),
+                    float_match: FloatMatch::Exact,
+                },
+            ])
+            .unwrap(),
+        );
+
+        save_session(&path, std::slice::from_ref(&first), &current).unwrap();
+        let steps = load_session(&path);
+        let _ = std::fs::remove_file(&path);
+        let steps = steps.unwrap();
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].params, first.params);
+        assert_eq!(steps[0].group, None);
+        assert_eq!(steps[0].results.addresses(), [0x1000, 0x2000]);
+        assert_eq!(steps[1].params, current.params);
+        assert_eq!(steps[1].group, current.group);
+        assert_eq!(steps[1].results.addresses(), [0x2000]);
+        assert_eq!(steps[1].results.get(0).unwrap().bytes, 90u32.to_le_bytes());
+    }
+
+    #[test]
+    fn other_files_and_versions_are_refused() {
+        let path = temp_path("refused.pvscan");
+        save_session(&path, &[], &step(ScanType::Exact, "1", &[(0x1000, 1)])).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+
+        let mut wrong_magic = saved.clone();
+        wrong_magic[..4].copy_from_slice(&SNAPSHOT_MAGIC.to_le_bytes());
+        std::fs::write(&path, wrong_magic).unwrap();
+        let magic_error = load_session(&path).unwrap_err().to_string();
+
+        let mut wrong_version = saved.clone();
+        wrong_version[4..8].copy_from_slice(&(SCAN_SESSION_VERSION + 1).to_le_bytes());
+        std::fs::write(&path, wrong_version).unwrap();
+        let version_error = load_session(&path).unwrap_err().to_string();
+
+        // A group claiming billions of members with none behind it
+        let mut members = SCAN_SESSION_MAGIC.to_le_bytes().to_vec();
+        members.extend(SCAN_SESSION_VERSION.to_le_bytes());
+        members.extend(1u32.to_le_bytes());
+        write_scan_type(&mut members, &ScanType::Exact).unwrap();
+        write_value_type(&mut members, &ValueType::Group).unwrap();
+        write_string(&mut members, "").unwrap();
+        write_string(&mut members, "").unwrap();
+        members.extend([0, 1]);
+        members.extend(u32::MAX.to_le_bytes());
+        std::fs::write(&path, members).unwrap();
+        let members_result = load_session(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(magic_error.contains("is not a scan session"));
+        assert!(version_error.contains("is not supported"));
+        assert!(members_result.is_err());
+    }
+}
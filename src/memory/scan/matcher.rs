@@ -0,0 +1,123 @@
+use super::group::ScanGroup;
+use super::pattern::BytePattern;
+use super::results::ScanResults;
+use super::search::{find_group, find_pattern};
+use crate::memory::scan::compare::compare_with_previous;
+use crate::memory::scan::types::{FloatMatch, ScanType, ValueType};
+use crate::memory::source::source::MemorySource;
+
+/// Everything a scan compares memory against, bundled up so it can be handed to worker threads
+///
+#[derive(Debug, Clone)]
+pub struct ScanMatcher {
+    pub scan_type: ScanType,
+    pub value_type: ValueType,
+    pub value_bytes: Vec<u8>,
+    pub float_match: FloatMatch,
+    /// Set for array of bytes and string scans, these are matched through the pattern
+    pub pattern: Option<BytePattern>,
+    /// Set for exact group scans
+    pub group: Option<ScanGroup>,
+}
+
+impl ScanMatcher {
+    /// Number of bytes a match covers
+    ///
+    pub fn width(&self) -> usize {
+        match (&self.pattern, &self.group) {
+            (Some(pattern), _) => pattern.bytes.len(),
+            (_, Some(group)) => group.width(),
+            _ => self.value_type.size(),
+        }
+    }
+    /// Distance between offsets checked, fast scan only checks offsets aligned to the value (or a
+    /// single character for patterns, or the first value of a group)
+    ///
+    pub fn step(&self, fast_scan: bool) -> usize {
+        match (&self.pattern, &self.group) {
+            _ if !fast_scan => 1,
+            (Some(pattern), _) => pattern.unit,
+            (_, Some(group)) => group.alignment(),
+            _ => self.value_type.alignment(),
+        }
+    }
+    /// Check a freshly read value against an earlier result, patterns and groups are matched again
+    /// for exact scans while everything else goes through the usual comparison
+    ///
+    fn matches_previous(&self, memory_bytes: &[u8], previous_bytes: &[u8]) -> anyhow::Result<bool> {
+        match (&self.pattern, &self.group) {
+            (Some(pattern), _) if self.scan_type == ScanType::Exact => {
+                Ok(pattern.matches(memory_bytes))
+            }
+            (_, Some(group)) if self.scan_type == ScanType::Exact => {
+                Ok(group.matches(memory_bytes))
+            }
+            _ => compare_with_previous(
+                memory_bytes,
+                previous_bytes,
+                &self.value_bytes,
+                &self.scan_type,
+                &self.value_type,
+                &self.float_match,
+            ),
+        }
+    }
+    /// Check every offset within a block of memory read from base, how single values are searched
+    /// for is up to their type
+    ///
+    pub fn scan_bytes(
+        &self,
+        base: u64,
+        bytes: &[u8],
+        fast_scan: bool,
+    ) -> anyhow::Result<ScanResults> {
+        let width = self.width();
+        let step = self.step(fast_scan);
+        let mut results = ScanResults::new(width);
+        let mut hit =
+            |offset: usize| results.push(base + offset as u64, &bytes[offset..offset + width]);
+
+        match (&self.pattern, &self.group, self.value_type.scannable()) {
+            (Some(pattern), _, _) => find_pattern(bytes, pattern, step, hit),
+            (_, Some(group), _) => find_group(bytes, group, step, hit),
+            (_, _, Some(scannable)) => scannable.find(
+                bytes,
+                &self.value_bytes,
+                &self.scan_type,
+                &self.float_match,
+                step,
+                &mut hit,
+            )?,
+            (None, None, None) => {
+                return Err(anyhow::anyhow!(
+                    "{} values can't be compared as a single value",
+                    self.value_type.label()
+                ))
+            }
+        }
+
+        Ok(results)
+    }
+    /// Keep the earlier results whose current bytes still match, the values they had become their
+    /// previous ones
+    ///
+    pub fn filter(
+        &self,
+        source: &mut dyn MemorySource,
+        results: &ScanResults,
+    ) -> anyhow::Result<ScanResults> {
+        let mut next = ScanResults::new(results.width());
+
+        source.read_each(results.addresses(), results.width(), |idx, memory_bytes| {
+            let Some(result) = results.get(idx) else {
+                return Ok(());
+            };
+            if self.matches_previous(memory_bytes, result.bytes)? {
+                next.push_next(result.address, memory_bytes, result.bytes, result.first);
+            }
+            Ok(())
+        })?;
+
+        Ok(next)
+    }
+}
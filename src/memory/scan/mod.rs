@@ -0,0 +1,12 @@
+pub mod compare;
+pub mod group;
+pub mod history;
+pub mod matcher;
+pub mod pattern;
+pub mod region_filter;
+pub mod region_store;
+pub mod results;
+pub mod scan_job;
+pub mod scannable;
+pub mod search;
+pub mod types;
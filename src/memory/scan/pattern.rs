@@ -0,0 +1,223 @@
+use crate::memory::scan::types::ValueType;
+use thiserror::Error;
+
+/// Errors that can come out of parsing a pattern
+///
+#[derive(Error, Debug)]
+pub enum PatternErrors {
+    #[error("Pattern is empty")]
+    Empty,
+    #[error("'{byte}' is neither a hex byte nor a ?? wildcard")]
+    InvalidByte { byte: String },
+    #[error("Pattern is nothing but wildcards")]
+    OnlyWildcards,
+    #[error("{value_type:?} is not a pattern type")]
+    NotAPattern { value_type: ValueType },
+}
+
+/// A run of bytes to search for, wildcards can stand in for any byte and strings can be matched
+/// regardless of case
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytePattern {
+    pub bytes: Vec<u8>,
+    /// Which bytes have to match, wildcards are left false
+    pub mask: Vec<bool>,
+    pub case_insensitive: bool,
+    /// Size of a single character, 2 for UTF-16 so only whole characters are folded
+    pub unit: usize,
+}
+
+impl BytePattern {
+    /// Parse an array of bytes such as `48 8B 05 ?? ?? ?? ?? 48 85 C0`, a single ? works as a
+    /// wildcard too. A lone nibble is taken as the low half of a byte, anything longer than two
+    /// digits or with a wildcard nibble is refused
+    ///
+    pub fn from_aob(text: &str) -> anyhow::Result<Self> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+
+        for byte in text.split_whitespace() {
+            if byte == "??" || byte == "?" {
+                bytes.push(0);
+                mask.push(false);
+                continue;
+            }
+
+            // from_str_radix would take a leading sign too
+            let value = match byte.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+                true => u8::from_str_radix(byte, 16).ok(),
+                false => None,
+            };
+            let value = value.ok_or_else(|| PatternErrors::InvalidByte {
+                byte: byte.to_string(),
+            })?;
+            bytes.push(value);
+            mask.push(true);
+        }
+
+        if bytes.is_empty() {
+            return Err(PatternErrors::Empty.into());
+        }
+        if !mask.contains(&true) {
+            return Err(PatternErrors::OnlyWildcards.into());
+        }
+
+        Ok(Self {
+            bytes,
+            mask,
+            case_insensitive: false,
+            unit: 1,
+        })
+    }
+    /// Build a pattern out of a string, encoded as either ASCII or UTF-16
+    ///
+    pub fn from_string(text: &str, utf16: bool, case_insensitive: bool) -> anyhow::Result<Self> {
+        if text.is_empty() {
+            return Err(PatternErrors::Empty.into());
+        }
+
+        let bytes: Vec<u8> = if utf16 {
+            text.encode_utf16()
+                .flat_map(|unit| unit.to_le_bytes())
+                .collect()
+        } else {
+            text.bytes().collect()
+        };
+
+        Ok(Self {
+            mask: vec![true; bytes.len()],
+            bytes,
+            case_insensitive,
+            unit: if utf16 { 2 } else { 1 },
+        })
+    }
+    /// Build the right kind of pattern for a value type
+    ///
+    pub fn parse(
+        value_type: &ValueType,
+        text: &str,
+        case_insensitive: bool,
+    ) -> anyhow::Result<Self> {
+        match value_type {
+            ValueType::ArrayOfBytes => Self::from_aob(text),
+            ValueType::Ascii => Self::from_string(text, false, case_insensitive),
+            ValueType::Utf16 => Self::from_string(text, true, case_insensitive),
+            value_type => Err(PatternErrors::NotAPattern {
+                value_type: value_type.clone(),
+            })?,
+        }
+    }
+    /// Longest run of bytes that have to match along with where it starts, this is what gets
+    /// searched for before the rest of the pattern is checked
+    ///
+    pub fn anchor(&self) -> (usize, &[u8]) {
+        let mut best = (0, 0);
+        let mut start = 0;
+
+        for (idx, must_match) in self.mask.iter().enumerate() {
+            if !must_match {
+                start = idx + 1;
+            } else if idx + 1 - start > best.1 {
+                best = (start, idx + 1 - start);
+            }
+        }
+
+        (best.0, &self.bytes[best.0..best.0 + best.1])
+    }
+    /// Check whether some bytes start with the pattern. Case is only folded for ASCII letters,
+    /// which for UTF-16 means the high byte of the character has to be zero
+    ///
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        if bytes.len() < self.bytes.len() {
+            return false;
+        }
+
+        if !self.case_insensitive {
+            return self
+                .bytes
+                .iter()
+                .zip(self.mask.iter())
+                .zip(bytes.iter())
+                .all(|((expected, must_match), actual)| !must_match || expected == actual);
+        }
+
+        self.bytes
+            .chunks(self.unit)
+            .zip(bytes.chunks(self.unit))
+            .all(|(expected, actual)| {
+                let is_ascii = expected[1..].iter().all(|&byte| byte == 0);
+                if is_ascii && actual[1..] == expected[1..] {
+                    expected[0].eq_ignore_ascii_case(&actual[0])
+                } else {
+                    expected == actual
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_are_masked_out() {
+        let pattern =
+            BytePattern::parse(&ValueType::ArrayOfBytes, "48 8b ?? ? 05 C0", false).unwrap();
+        assert_eq!(pattern.bytes, [0x48, 0x8B, 0, 0, 0x05, 0xC0]);
+        assert_eq!(pattern.mask, [true, true, false, false, true, true]);
+        assert_eq!(pattern.anchor(), (0, &[0x48, 0x8B][..]));
+
+        assert!(pattern.matches(&[0x48, 0x8B, 0x12, 0x34, 0x05, 0xC0, 0xFF]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x12, 0x34, 0x05, 0xC1]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x12, 0x34, 0x05]));
+
+        // The longest run of fixed bytes is searched for first
+        let pattern = BytePattern::from_aob("E8 ?? ?? ?? ?? 48 85 C0 ?? 90").unwrap();
+        assert_eq!(pattern.anchor(), (5, &[0x48, 0x85, 0xC0][..]));
+    }
+
+    #[test]
+    fn malformed_bytes_are_refused() {
+        // A lone nibble is a whole byte
+        assert_eq!(BytePattern::from_aob("4 F").unwrap().bytes, [0x04, 0x0F]);
+
+        for text in ["488B", "4?", "+1", "GG", "0x48"] {
+            assert!(
+                BytePattern::from_aob(text).is_err(),
+                "{text} should have been refused"
+            );
+        }
+        assert!(BytePattern::from_aob("").is_err());
+        assert!(BytePattern::from_aob("?? ??").is_err());
+        assert!(BytePattern::parse(&ValueType::FourBytes, "48", false).is_err());
+    }
+
+    #[test]
+    fn strings_fold_ascii_case() {
+        let ascii = BytePattern::parse(&ValueType::Ascii, "Player", true).unwrap();
+        assert!(ascii.matches(b"PLAYER"));
+        assert!(ascii.matches(b"player_one"));
+        assert!(!ascii.matches(b"Playe"));
+        assert!(!BytePattern::parse(&ValueType::Ascii, "Player", false)
+            .unwrap()
+            .matches(b"PLAYER"));
+
+        let utf16 = |text: &str| -> Vec<u8> {
+            text.encode_utf16()
+                .flat_map(|unit| unit.to_le_bytes())
+                .collect()
+        };
+        let wide = BytePattern::parse(&ValueType::Utf16, "Héro", true).unwrap();
+        assert_eq!(wide.unit, 2);
+        assert!(wide.matches(&utf16("HéRO")));
+        // Only ASCII letters are folded, anything else has to match exactly
+        assert!(!wide.matches(&utf16("hÉRO")));
+
+        // Only whole characters are folded, a byte that happens to be a letter within a wider
+        // character isn't
+        let letter = BytePattern::parse(&ValueType::Utf16, "a", true).unwrap();
+        assert!(letter.matches(&[b'A', 0]));
+        assert!(!letter.matches(&[b'A', 0x01]));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::memory::process::types::{
+    MemoryRegion, ModuleInfo, MEM_COMMIT, MEM_IMAGE, MEM_MAPPED, MEM_PRIVATE,
+};
+use crate::memory::source::source::MemorySource;
+
+/// Whether a region has to have a protection flag, can't have it, or doesn't matter
+///
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum FlagFilter {
+    #[default]
+    Either,
+    Required,
+    Excluded,
+}
+
+impl FlagFilter {
+    /// Every option in the order they're offered up
+    ///
+    pub const ALL: [FlagFilter; 3] = [
+        FlagFilter::Either,
+        FlagFilter::Required,
+        FlagFilter::Excluded,
+    ];
+
+    /// Name shown within combo boxes
+    ///
+    pub fn label(&self) -> &'static str {
+        match self {
+            FlagFilter::Either => "Either",
+            FlagFilter::Required => "Yes",
+            FlagFilter::Excluded => "No",
+        }
+    }
+    /// Check a region's flag against the filter
+    ///
+    pub fn allows(&self, set: bool) -> bool {
+        match self {
+            FlagFilter::Either => true,
+            FlagFilter::Required => set,
+            FlagFilter::Excluded => !set,
+        }
+    }
+}
+
+/// Which parts of a target a scan covers, by default that's every readable region
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionFilter {
+    pub writable: FlagFilter,
+    pub executable: FlagFilter,
+    pub copy_on_write: FlagFilter,
+    pub image: bool,
+    pub private: bool,
+    pub mapped: bool,
+    /// Only scan within this module
+    pub module: Option<ModuleInfo>,
+    /// Start of the range to scan
+    pub start: u64,
+    /// End of the range to scan, this address itself isn't scanned
+    pub end: u64,
+}
+
+impl Default for RegionFilter {
+    fn default() -> Self {
+        Self {
+            writable: FlagFilter::Either,
+            executable: FlagFilter::Either,
+            copy_on_write: FlagFilter::Either,
+            image: true,
+            private: true,
+            mapped: true,
+            module: None,
+            start: 0,
+            end: u64::MAX,
+        }
+    }
+}
+
+impl RegionFilter {
+    /// Check a region's type against the filter, regions a backend doesn't give a type are kept
+    ///
+    fn allows_type(&self, region_type: u32) -> bool {
+        match region_type {
+            MEM_IMAGE => self.image,
+            MEM_PRIVATE => self.private,
+            MEM_MAPPED => self.mapped,
+            _ => true,
+        }
+    }
+    /// The part of a region that should be scanned, regions are cut down to the address range and
+    /// module and start on the alignment values are scanned at. None if none of it should be
+    ///
+    pub fn clip(&self, region: &MemoryRegion, alignment: usize) -> Option<MemoryRegion> {
+        if region.state != MEM_COMMIT
+            || !region.is_readable()
+            || !self.writable.allows(region.is_writable())
+            || !self.executable.allows(region.is_executable())
+            || !self.copy_on_write.allows(region.is_copy_on_write())
+            || !self.allows_type(region.region_type)
+        {
+            return None;
+        }
+
+        let (mut start, mut end) = (self.start, self.end);
+        if let Some(module) = &self.module {
+            start = start.max(module.base_address);
+            end = end.min(module.base_address + module.size);
+        }
+
+        let base_address = region
+            .base_address
+            .max(start)
+            .next_multiple_of(alignment as u64);
+        let region_end = (region.base_address + region.region_size).min(end);
+        if base_address >= region_end {
+            return None;
+        }
+
+        Some(MemoryRegion {
+            base_address,
+            region_size: region_end - base_address,
+            ..*region
+        })
+    }
+    /// Every region of a target the filter lets through, already clipped
+    ///
+    pub fn regions(
+        &self,
+        source: &mut dyn MemorySource,
+        alignment: usize,
+    ) -> anyhow::Result<Vec<MemoryRegion>> {
+        Ok(source
+            .regions()?
+            .iter()
+            .filter_map(|region| self.clip(region, alignment))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::process::types::{
+        PAGE_EXECUTE_READ, PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
+    };
+    use crate::memory::source::mock::MockTarget;
+
+    /// An image with code, read only data and copy on write data, a heap, a mapped file and a
+    /// guard page nothing can read
+    ///
+    fn target() -> MockTarget {
+        MockTarget::new("game")
+            .with_module("game", 0x40_0000, vec![0; 0x1000], PAGE_EXECUTE_READ)
+            .with_typed_region(0x40_1000, vec![0; 0x1000], PAGE_READONLY, MEM_IMAGE)
+            .with_typed_region(0x40_2000, vec![0; 0x1000], PAGE_WRITECOPY, MEM_IMAGE)
+            .with_region(0x100_0000, vec![0; 0x2000], PAGE_READWRITE)
+            .with_typed_region(0x200_0000, vec![0; 0x1000], PAGE_READONLY, MEM_MAPPED)
+            .with_region(0x300_0000, vec![0; 0x1000], PAGE_NOACCESS)
+    }
+
+    fn bases(filter: &RegionFilter) -> Vec<u64> {
+        filter
+            .regions(&mut target(), 1)
+            .unwrap()
+            .iter()
+            .map(|region| region.base_address)
+            .collect()
+    }
+
+    #[test]
+    fn every_readable_region_is_scanned_by_default() {
+        assert_eq!(
+            bases(&RegionFilter::default()),
+            [0x40_0000, 0x40_1000, 0x40_2000, 0x100_0000, 0x200_0000]
+        );
+    }
+
+    #[test]
+    fn protection_flags_filter_regions() {
+        let writable = RegionFilter {
+            writable: FlagFilter::Required,
+            ..Default::default()
+        };
+        assert_eq!(bases(&writable), [0x40_2000, 0x100_0000]);
+
+        let read_only_data = RegionFilter {
+            writable: FlagFilter::Excluded,
+            executable: FlagFilter::Excluded,
+            ..Default::default()
+        };
+        assert_eq!(bases(&read_only_data), [0x40_1000, 0x200_0000]);
+
+        let not_copy_on_write = RegionFilter {
+            writable: FlagFilter::Required,
+            copy_on_write: FlagFilter::Excluded,
+            ..Default::default()
+        };
+        assert_eq!(bases(&not_copy_on_write), [0x100_0000]);
+    }
+
+    #[test]
+    fn region_types_filter_regions() {
+        let heap_only = RegionFilter {
+            image: false,
+            mapped: false,
+            ..Default::default()
+        };
+        assert_eq!(bases(&heap_only), [0x100_0000]);
+
+        let no_heap = RegionFilter {
+            private: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            bases(&no_heap),
+            [0x40_0000, 0x40_1000, 0x40_2000, 0x200_0000]
+        );
+    }
+
+    #[test]
+    fn modules_and_ranges_clip_regions() {
+        // The module only covers its first page, the image regions past it are left out
+        let module = RegionFilter {
+            module: target().modules().unwrap().first().cloned(),
+            ..Default::default()
+        };
+        assert_eq!(bases(&module), [0x40_0000]);
+
+        // Ranges cut regions down, the end itself isn't scanned
+        let range = RegionFilter {
+            start: 0x40_1800,
+            end: 0x100_1000,
+            ..Default::default()
+        };
+        let regions = range.regions(&mut target(), 1).unwrap();
+        let spans: Vec<(u64, u64)> = regions
+            .iter()
+            .map(|region| (region.base_address, region.region_size))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (0x40_1800, 0x800),
+                (0x40_2000, 0x1000),
+                (0x100_0000, 0x1000)
+            ]
+        );
+
+        // Regions start on the alignment values are scanned at
+        let unaligned = RegionFilter {
+            start: 0x100_0001,
+            ..Default::default()
+        };
+        let regions = unaligned.regions(&mut target(), 4).unwrap();
+        assert_eq!(regions[0].base_address, 0x100_0004);
+        assert_eq!(regions[0].region_size, 0x1FFC);
+
+        let empty = RegionFilter {
+            start: 0x500_0000,
+            ..Default::default()
+        };
+        assert!(bases(&empty).is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+use super::results::ScanResults;
+use super::scan_job::ScanProgress;
+use crate::memory::process::types::MemoryRegion;
+use crate::memory::scan::compare::compare_with_previous;
+use crate::memory::scan::types::{FloatMatch, ScanType, ValueType};
+use crate::memory::source::source::{read_shared_paged, SharedSource};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How much compressed data is held in memory before the rest is spilled out to disk
+///
+pub const MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Every store spills to its own file, this keeps their names apart
+///
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Where a compressed blob ended up
+///
+#[derive(Debug)]
+enum Blob {
+    Memory(Vec<u8>),
+    Disk { offset: u64, length: usize },
+}
+
+/// A single region as of the last scan, along with which of its slots still match. Slots are
+/// every value sized (or with fast scan off, every byte) offset into the region
+///
+#[derive(Debug)]
+pub struct StoredRegion {
+    pub base_address: u64,
+    pub size: usize,
+    pub match_count: usize,
+    bytes: Blob,
+    matches: Blob,
+}
+
+/// A copy of every scannable region of a target, this is what an unknown initial value scan hands
+/// back instead of a result list since every address is a result. Regions are compressed and once
+/// MEMORY_LIMIT is hit the rest are spilled to a temporary file so huge targets don't have to fit
+/// in memory
+///
+#[derive(Debug)]
+pub struct RegionStore {
+    pub value_type: ValueType,
+    /// Distance between slots
+    pub step: usize,
+    pub regions: Vec<StoredRegion>,
+    memory_used: usize,
+    spill: Option<(PathBuf, File)>,
+}
+
+impl RegionStore {
+    /// Create an empty store
+    ///
+    fn new(value_type: ValueType, step: usize) -> Self {
+        Self {
+            value_type,
+            step,
+            regions: Vec::new(),
+            memory_used: 0,
+            spill: None,
+        }
+    }
+    /// Number of slots within a region of a given size
+    ///
+    fn slot_count(&self, size: usize) -> usize {
+        match size.checked_sub(self.value_type.size()) {
+            Some(last) => last / self.step + 1,
+            None => 0,
+        }
+    }
+    /// Compress a blob and keep it in memory if there's room for it, otherwise add it onto the
+    /// spill file
+    ///
+    fn store(&mut self, bytes: &[u8]) -> anyhow::Result<Blob> {
+        let compressed = lz4_flex::compress_prepend_size(bytes);
+
+        if self.memory_used + compressed.len() <= MEMORY_LIMIT {
+            self.memory_used += compressed.len();
+            return Ok(Blob::Memory(compressed));
+        }
+
+        if self.spill.is_none() {
+            let path = std::env::temp_dir().join(format!(
+                "procvamp-{}-{}.scan",
+                std::process::id(),
+                SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let file = File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)?;
+            self.spill = Some((path, file));
+        }
+
+        let (_, file) = self.spill.as_mut().unwrap();
+        let offset = file.seek(SeekFrom::End(0))?;
+        file.write_all(&compressed)?;
+
+        Ok(Blob::Disk {
+            offset,
+            length: compressed.len(),
+        })
+    }
+    /// Pull a blob back out, wherever it ended up
+    ///
+    fn load(&self, blob: &Blob) -> anyhow::Result<Vec<u8>> {
+        match blob {
+            Blob::Memory(compressed) => Ok(lz4_flex::decompress_size_prepended(compressed)?),
+            Blob::Disk { offset, length } => {
+                // Reading through a shared reference to the file is enough to seek and read
+                let mut file: &File = self
+                    .spill
+                    .as_ref()
+                    .map(|(_, file)| file)
+                    .ok_or_else(|| anyhow::anyhow!("Scan store has nothing spilled to disk"))?;
+                let mut compressed = vec![0u8; *length];
+                file.seek(SeekFrom::Start(*offset))?;
+                file.read_exact(&mut compressed)?;
+                Ok(lz4_flex::decompress_size_prepended(&compressed)?)
+            }
+        }
+    }
+    /// Add a region to the store
+    ///
+    fn push(
+        &mut self,
+        base_address: u64,
+        bytes: &[u8],
+        matches: &[u8],
+        match_count: usize,
+    ) -> anyhow::Result<()> {
+        let stored_bytes = self.store(bytes)?;
+        let stored_matches = self.store(matches)?;
+
+        self.regions.push(StoredRegion {
+            base_address,
+            size: bytes.len(),
+            match_count,
+            bytes: stored_bytes,
+            matches: stored_matches,
+        });
+        Ok(())
+    }
+    /// Total number of slots still matching
+    ///
+    pub fn match_count(&self) -> usize {
+        self.regions.iter().map(|region| region.match_count).sum()
+    }
+    /// Take a copy of every region given, every slot starts off as a match since nothing is known
+    /// about the value yet. Runs in the background so the target is only held a chunk at a time
+    ///
+    pub fn capture(
+        target: &SharedSource,
+        value_type: ValueType,
+        step: usize,
+        regions: &[MemoryRegion],
+        progress: &ScanProgress,
+    ) -> anyhow::Result<Self> {
+        let mut store = Self::new(value_type, step);
+
+        for region in regions.iter() {
+            progress.check_cancelled()?;
+            let bytes = read_shared_paged(
+                target,
+                region.base_address as usize,
+                region.region_size as usize,
+            )?;
+            let slots = store.slot_count(bytes.len());
+            progress.advance(region.region_size, slots);
+            if slots == 0 {
+                continue;
+            }
+
+            let mut matches = vec![0xFFu8; slots.div_ceil(8)];
+            // Clear the bits past the last slot so counts stay right
+            if !slots.is_multiple_of(8) {
+                *matches.last_mut().unwrap() = (1u8 << (slots % 8)) - 1;
+            }
+
+            store.push(region.base_address, &bytes, &matches, slots)?;
+        }
+
+        Ok(store)
+    }
+    /// Compare every slot still matching against what it holds now, handing back a new store
+    /// holding the current values. Regions that can no longer be read are dropped
+    ///
+    pub fn next_scan(
+        &self,
+        target: &SharedSource,
+        scan_type: &ScanType,
+        value_bytes: &[u8],
+        float_match: &FloatMatch,
+        progress: &ScanProgress,
+    ) -> anyhow::Result<Self> {
+        let size = self.value_type.size();
+        let mut next = Self::new(self.value_type.clone(), self.step);
+
+        for region in self.regions.iter() {
+            if region.match_count == 0 {
+                continue;
+            }
+            progress.check_cancelled()?;
+            let current = read_shared_paged(target, region.base_address as usize, region.size);
+            let Ok(current) = current else {
+                progress.advance(region.size as u64, 0);
+                continue;
+            };
+            let previous = self.load(&region.bytes)?;
+            let mut matches = self.load(&region.matches)?;
+            let mut match_count = 0;
+
+            for slot in 0..self.slot_count(region.size) {
+                let bit = 1u8 << (slot % 8);
+                if matches[slot / 8] & bit == 0 {
+                    continue;
+                }
+
+                let offset = slot * self.step;
+                if compare_with_previous(
+                    &current[offset..offset + size],
+                    &previous[offset..offset + size],
+                    value_bytes,
+                    scan_type,
+                    &self.value_type,
+                    float_match,
+                )? {
+                    match_count += 1;
+                } else {
+                    matches[slot / 8] &= !bit;
+                }
+            }
+
+            progress.advance(region.size as u64, match_count);
+            if match_count > 0 {
+                next.push(region.base_address, &current, &matches, match_count)?;
+            }
+        }
+
+        Ok(next)
+    }
+    /// Turn every slot still matching into a result, only worth doing once a few scans have
+    /// narrowed things down
+    ///
+    pub fn results(&self) -> anyhow::Result<ScanResults> {
+        let size = self.value_type.size();
+        let mut results = ScanResults::new(size);
+
+        for region in self.regions.iter() {
+            let bytes = self.load(&region.bytes)?;
+            let matches = self.load(&region.matches)?;
+
+            for slot in 0..self.slot_count(region.size) {
+                if matches[slot / 8] & (1u8 << (slot % 8)) == 0 {
+                    continue;
+                }
+
+                let offset = slot * self.step;
+                results.push(
+                    region.base_address + offset as u64,
+                    &bytes[offset..offset + size],
+                );
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+/// Clean up after ourselves, spill files are only meaningful to the store that wrote them
+///
+impl Drop for RegionStore {
+    fn drop(&mut self) {
+        if let Some((path, file)) = self.spill.take() {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
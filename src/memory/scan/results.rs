@@ -0,0 +1,221 @@
+use crate::memory::dump::snapshot::{
+    take_blob, take_u32, take_u64, write_blob, write_u32, write_u64,
+};
+use std::io::{Read, Write};
+
+/// A single row of a result list, borrowed straight out of it
+///
+#[derive(Clone, Copy, Debug)]
+pub struct ScanResult<'a> {
+    pub address: u64,
+    /// Raw bytes of the value as of the last scan, next scans compare against these
+    pub bytes: &'a [u8],
+    /// Value as of the scan before the last one
+    pub previous: &'a [u8],
+    /// Value as of the first scan
+    pub first: &'a [u8],
+}
+
+/// Everything a scan found. Every value within a list is the same width so addresses and raw bytes
+/// are kept back to back rather than a handful of allocations per hit, values are only ever turned
+/// into text for the rows being shown
+///
+#[derive(Clone, Default, Debug)]
+pub struct ScanResults {
+    width: usize,
+    addresses: Vec<u64>,
+    bytes: Vec<u8>,
+    /// Both left empty until a next scan, until then every value is its own previous and first
+    previous: Vec<u8>,
+    first: Vec<u8>,
+}
+
+impl ScanResults {
+    /// Create an empty list of values of a given width
+    ///
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+    pub fn addresses(&self) -> &[u64] {
+        &self.addresses
+    }
+    /// Whether any next scan has been run over these yet
+    ///
+    fn has_history(&self) -> bool {
+        !self.is_empty() && self.previous.len() == self.bytes.len()
+    }
+    /// Copy the current values out as their own previous and first values, needed before lists
+    /// with and without history can be put together
+    ///
+    fn fill_history(&mut self) {
+        if self.previous.len() != self.bytes.len() {
+            self.previous = self.bytes.clone();
+            self.first = self.bytes.clone();
+        }
+    }
+    /// Add a value found by a first scan
+    ///
+    pub fn push(&mut self, address: u64, bytes: &[u8]) {
+        let bytes = &bytes[..self.width];
+        if self.has_history() {
+            self.previous.extend_from_slice(bytes);
+            self.first.extend_from_slice(bytes);
+        }
+        self.addresses.push(address);
+        self.bytes.extend_from_slice(bytes);
+    }
+    /// Add a value that's made it through a next scan, the value it had before becomes its
+    /// previous one
+    ///
+    pub fn push_next(&mut self, address: u64, bytes: &[u8], previous: &[u8], first: &[u8]) {
+        self.fill_history();
+        self.addresses.push(address);
+        self.bytes.extend_from_slice(&bytes[..self.width]);
+        self.previous.extend_from_slice(&previous[..self.width]);
+        self.first.extend_from_slice(&first[..self.width]);
+    }
+    /// Grab a single row
+    ///
+    pub fn get(&self, idx: usize) -> Option<ScanResult<'_>> {
+        let address = *self.addresses.get(idx)?;
+        let range = idx * self.width..(idx + 1) * self.width;
+        let bytes = &self.bytes[range.clone()];
+
+        let (previous, first) = match self.has_history() {
+            true => (&self.previous[range.clone()], &self.first[range]),
+            false => (bytes, bytes),
+        };
+
+        Some(ScanResult {
+            address,
+            bytes,
+            previous,
+            first,
+        })
+    }
+    /// Move every value from another list onto the end of this one
+    ///
+    pub fn append(&mut self, mut other: ScanResults) {
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+
+        if self.has_history() || other.has_history() {
+            self.fill_history();
+            other.fill_history();
+        }
+        self.addresses.append(&mut other.addresses);
+        self.bytes.append(&mut other.bytes);
+        self.previous.append(&mut other.previous);
+        self.first.append(&mut other.first);
+    }
+    /// Copy out a run of rows
+    ///
+    fn slice(&self, start: usize, end: usize) -> ScanResults {
+        let range = start * self.width..end * self.width;
+        let history = self.has_history();
+
+        ScanResults {
+            width: self.width,
+            addresses: self.addresses[start..end].to_vec(),
+            bytes: self.bytes[range.clone()].to_vec(),
+            previous: match history {
+                true => self.previous[range.clone()].to_vec(),
+                false => Vec::new(),
+            },
+            first: match history {
+                true => self.first[range].to_vec(),
+                false => Vec::new(),
+            },
+        }
+    }
+    /// Split the list up into runs of at most size rows
+    ///
+    pub fn chunks(&self, size: usize) -> Vec<ScanResults> {
+        (0..self.len())
+            .step_by(size.max(1))
+            .map(|start| self.slice(start, (start + size).min(self.len())))
+            .collect()
+    }
+    /// Put the rows in address order, workers hand back their results in whatever order they
+    /// finish
+    ///
+    pub fn sort(&mut self) {
+        if self.addresses.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return;
+        }
+
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_unstable_by_key(|&idx| self.addresses[idx]);
+
+        let width = self.width;
+        let gather = |values: &[u8]| -> Vec<u8> {
+            order
+                .iter()
+                .flat_map(|&idx| &values[idx * width..(idx + 1) * width])
+                .copied()
+                .collect()
+        };
+        let history = self.has_history();
+
+        self.bytes = gather(&self.bytes);
+        if history {
+            self.previous = gather(&self.previous);
+            self.first = gather(&self.first);
+        }
+        self.addresses = order.iter().map(|&idx| self.addresses[idx]).collect();
+    }
+    /// Write the list out, used to save scan sessions
+    ///
+    pub fn save(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        write_u32(writer, self.width as u32)?;
+        write_u32(writer, self.addresses.len() as u32)?;
+        for address in self.addresses.iter() {
+            write_u64(writer, *address)?;
+        }
+        write_blob(writer, &self.bytes)?;
+        write_blob(writer, &self.previous)?;
+        write_blob(writer, &self.first)
+    }
+    /// Read a list back in, making sure every value is the width it claims to be
+    ///
+    pub fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let width = take_u32(reader)? as usize;
+        let count = take_u32(reader)? as usize;
+        let addresses = (0..count)
+            .map(|_| take_u64(reader))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let bytes = take_blob(reader)?;
+        let previous = take_blob(reader)?;
+        let first = take_blob(reader)?;
+
+        let expected = count * width;
+        if bytes.len() != expected
+            || !(previous.is_empty() || previous.len() == expected)
+            || previous.len() != first.len()
+        {
+            return Err(anyhow::anyhow!("Saved scan results are corrupt"));
+        }
+
+        Ok(Self {
+            width,
+            addresses,
+            bytes,
+            previous,
+            first,
+        })
+    }
+}
@@ -0,0 +1,400 @@
+use super::matcher::ScanMatcher;
+use super::region_filter::RegionFilter;
+use super::region_store::RegionStore;
+use super::results::ScanResults;
+use crate::memory::scan::types::{FloatMatch, ScanType, ValueType};
+use crate::memory::source::source::{lock_source, read_shared_paged, SharedSource};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// How many earlier results each piece of a next scan covers
+///
+pub const RESULTS_PER_CHUNK: usize = 0x4000;
+
+/// How far along a scan is, shared between the workers and the tab showing it. Work is split into
+/// chunks, a region for first scans and a run of results for next scans
+///
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    pub total_bytes: u64,
+    pub total_chunks: usize,
+    bytes_done: AtomicU64,
+    chunks_done: AtomicUsize,
+    hits: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl ScanProgress {
+    pub fn bytes_done(&self) -> u64 {
+        self.bytes_done.load(Ordering::Relaxed)
+    }
+    pub fn chunks_done(&self) -> usize {
+        self.chunks_done.load(Ordering::Relaxed)
+    }
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+    /// Ask whatever's doing the work to stop
+    ///
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    /// Record more of the target having been gone through along with anything found within it,
+    /// for work that keeps track of its own progress
+    ///
+    pub fn advance(&self, bytes: u64, hits: usize) {
+        self.bytes_done.fetch_add(bytes, Ordering::Relaxed);
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+    }
+    /// Give up on work once it's been cancelled
+    ///
+    pub fn check_cancelled(&self) -> anyhow::Result<()> {
+        match self.is_cancelled() {
+            true => Err(anyhow::anyhow!("Cancelled")),
+            false => Ok(()),
+        }
+    }
+    /// Fraction of the bytes scanned so far, for progress bars
+    ///
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 1.0;
+        }
+        self.bytes_done() as f32 / self.total_bytes as f32
+    }
+}
+
+/// A scan running on worker threads, results are handed back a chunk at a time as they're found
+/// so they can be shown before the whole scan is done. Work that can't be split up (building a
+/// region store or a pointer map) runs on a single worker and hands back whatever it built once
+/// it's done. Dropping the job cancels it
+///
+pub struct ScanJob<T = ScanResults> {
+    pub progress: Arc<ScanProgress>,
+    /// Behind a lock so the job can be shared between clones of the tab
+    receiver: Mutex<Receiver<anyhow::Result<T>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> ScanJob<T> {
+    /// Run a single long piece of work covering total_bytes of the target, it's left to update
+    /// the progress itself and to give up once it sees it's been cancelled
+    ///
+    pub fn spawn_single<F>(target: SharedSource, total_bytes: u64, run: F) -> Self
+    where
+        F: FnOnce(&SharedSource, &ScanProgress) -> anyhow::Result<T> + Send + 'static,
+    {
+        let progress = Arc::new(ScanProgress {
+            total_bytes,
+            total_chunks: 1,
+            ..Default::default()
+        });
+        let (sender, receiver) = channel();
+
+        let worker = {
+            let progress = progress.clone();
+            std::thread::spawn(move || {
+                let result = run(&target, &progress);
+                progress.chunks_done.fetch_add(1, Ordering::Relaxed);
+                let _ = sender.send(result);
+            })
+        };
+
+        Self {
+            progress,
+            receiver: Mutex::new(receiver),
+            workers: vec![worker],
+        }
+    }
+    /// Ask the workers to stop, anything already found is still handed back
+    ///
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+    /// Every worker has stopped, check this before the last poll so nothing sent is missed
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.workers.iter().all(|worker| worker.is_finished())
+    }
+    /// Take whatever the workers have handed back since we last checked
+    ///
+    pub fn poll(&self) -> Vec<anyhow::Result<T>> {
+        self.receiver
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .try_iter()
+            .collect()
+    }
+}
+
+impl ScanJob {
+    /// Spread work across as many threads as we've got cores, each piece of work is paired with
+    /// the number of bytes it covers so progress can be tracked
+    ///
+    fn spawn<W, F>(target: SharedSource, mut work: Vec<(u64, W)>, run: F) -> Self
+    where
+        W: Send + 'static,
+        F: Fn(&SharedSource, W) -> anyhow::Result<ScanResults> + Send + Sync + 'static,
+    {
+        let progress = Arc::new(ScanProgress {
+            total_bytes: work.iter().map(|(bytes, _)| bytes).sum(),
+            total_chunks: work.len(),
+            ..Default::default()
+        });
+        let thread_count = std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(4)
+            .clamp(1, work.len().max(1));
+
+        // Work is popped off the end so flip it to hand out the lowest addresses first
+        work.reverse();
+        let queue = Arc::new(Mutex::new(work));
+        let run = Arc::new(run);
+        let (sender, receiver) = channel();
+
+        let workers = (0..thread_count)
+            .map(|_| {
+                let (target, queue, run, progress, sender) = (
+                    target.clone(),
+                    queue.clone(),
+                    run.clone(),
+                    progress.clone(),
+                    sender.clone(),
+                );
+
+                std::thread::spawn(move || {
+                    while !progress.is_cancelled() {
+                        let Some((bytes, item)) = queue.lock().unwrap().pop() else {
+                            break;
+                        };
+                        let result = run(&target, item);
+
+                        if let Ok(results) = &result {
+                            progress.hits.fetch_add(results.len(), Ordering::Relaxed);
+                        }
+                        progress.bytes_done.fetch_add(bytes, Ordering::Relaxed);
+                        progress.chunks_done.fetch_add(1, Ordering::Relaxed);
+
+                        if sender.send(result).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            progress,
+            receiver: Mutex::new(receiver),
+            workers,
+        }
+    }
+    /// Scan every region of a target the filter lets through, regions are read a chunk at a time
+    /// and compared once the target's been let go so workers aren't stuck waiting on each other
+    ///
+    pub fn first_scan(
+        target: SharedSource,
+        matcher: ScanMatcher,
+        fast_scan: bool,
+        filter: &RegionFilter,
+    ) -> anyhow::Result<Self> {
+        let regions: Vec<_> = filter
+            .regions(&mut *lock_source(&target), matcher.step(fast_scan))?
+            .into_iter()
+            .map(|region| (region.region_size, region))
+            .collect();
+
+        Ok(Self::spawn(target, regions, move |target, region| {
+            let bytes = read_shared_paged(
+                target,
+                region.base_address as usize,
+                region.region_size as usize,
+            )?;
+            matcher.scan_bytes(region.base_address, &bytes, fast_scan)
+        }))
+    }
+    /// Narrow down earlier results, these are split up into runs so they can be spread out too
+    ///
+    pub fn next_scan(target: SharedSource, matcher: ScanMatcher, results: &ScanResults) -> Self {
+        let work = results
+            .chunks(RESULTS_PER_CHUNK)
+            .into_iter()
+            .map(|chunk| ((chunk.len() * chunk.width()) as u64, chunk))
+            .collect();
+
+        Self::spawn(target, work, move |target, chunk| {
+            matcher.filter(&mut *lock_source(target), &chunk)
+        })
+    }
+}
+
+impl ScanJob<RegionStore> {
+    /// Take a copy of every region the filter lets through for an unknown initial value scan
+    ///
+    pub fn capture(
+        target: SharedSource,
+        value_type: ValueType,
+        fast_scan: bool,
+        filter: &RegionFilter,
+    ) -> anyhow::Result<Self> {
+        let step = if fast_scan { value_type.alignment() } else { 1 };
+        let regions = filter.regions(&mut *lock_source(&target), step)?;
+        let total_bytes = regions.iter().map(|region| region.region_size).sum();
+
+        Ok(Self::spawn_single(
+            target,
+            total_bytes,
+            move |target, progress| {
+                RegionStore::capture(target, value_type, step, &regions, progress)
+            },
+        ))
+    }
+    /// Narrow a stored scan down, only regions with something still matching are read again
+    ///
+    pub fn stored_next_scan(
+        target: SharedSource,
+        store: Arc<RegionStore>,
+        scan_type: ScanType,
+        value_bytes: Vec<u8>,
+        float_match: FloatMatch,
+    ) -> Self {
+        let total_bytes = store
+            .regions
+            .iter()
+            .filter(|region| region.match_count > 0)
+            .map(|region| region.size as u64)
+            .sum();
+
+        Self::spawn_single(target, total_bytes, move |target, progress| {
+            store.next_scan(target, &scan_type, &value_bytes, &float_match, progress)
+        })
+    }
+}
+
+/// Workers hold onto the target so make sure they don't outlive the tab that started them
+///
+impl<T> Drop for ScanJob<T> {
+    fn drop(&mut self) {
+        self.progress.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::process::types::PAGE_READWRITE;
+    use crate::memory::source::mock::MockTarget;
+    use crate::memory::source::source::share_source;
+
+    /// Two regions with 1337 in three places, the first region is big enough to be read in
+    /// more than one chunk
+    ///
+    fn target() -> SharedSource {
+        let mut first = vec![0u8; 0x12000];
+        let mut second = vec![0u8; 0x100];
+        first[0x10..0x14].copy_from_slice(&1337u32.to_le_bytes());
+        first[0x11004..0x11008].copy_from_slice(&1337u32.to_le_bytes());
+        second[0x20..0x24].copy_from_slice(&1337u32.to_le_bytes());
+
+        share_source(
+            MockTarget::new("game")
+                .with_region(0x10000, first, PAGE_READWRITE)
+                .with_region(0x40000, second, PAGE_READWRITE),
+        )
+    }
+
+    fn matcher(value: u32) -> ScanMatcher {
+        ScanMatcher {
+            scan_type: ScanType::Exact,
+            value_type: ValueType::FourBytes,
+            value_bytes: value.to_le_bytes().to_vec(),
+            float_match: FloatMatch::Exact,
+            pattern: None,
+            group: None,
+        }
+    }
+
+    /// Wait for a job to finish and hand back everything it found
+    ///
+    fn finish<T: Send + 'static>(job: ScanJob<T>) -> Vec<T> {
+        let mut found = Vec::new();
+        loop {
+            let finished = job.is_finished();
+            found.extend(job.poll().into_iter().map(|result| result.unwrap()));
+            if finished {
+                return found;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    fn collect(job: ScanJob) -> ScanResults {
+        let mut results = ScanResults::default();
+        for batch in finish(job) {
+            results.append(batch);
+        }
+        results.sort();
+        results
+    }
+
+    #[test]
+    fn first_scan_finds_every_match() {
+        let job =
+            ScanJob::first_scan(target(), matcher(1337), true, &RegionFilter::default()).unwrap();
+
+        assert_eq!(collect(job).addresses(), [0x10010, 0x21004, 0x40020]);
+    }
+
+    #[test]
+    fn next_scan_drops_changed_values() {
+        let target = target();
+        let job = ScanJob::first_scan(
+            target.clone(),
+            matcher(1337),
+            true,
+            &RegionFilter::default(),
+        )
+        .unwrap();
+        let results = collect(job);
+
+        lock_source(&target)
+            .write_bytes(0x21004, &7u32.to_le_bytes())
+            .unwrap();
+        let next = collect(ScanJob::next_scan(target, matcher(1337), &results));
+
+        assert_eq!(next.addresses(), [0x10010, 0x40020]);
+    }
+
+    #[test]
+    fn stored_scans_narrow_down() {
+        let target = target();
+        let job = ScanJob::capture(
+            target.clone(),
+            ValueType::FourBytes,
+            true,
+            &RegionFilter::default(),
+        )
+        .unwrap();
+        let store = finish(job).pop().unwrap();
+        assert_eq!(store.match_count(), (0x12000 + 0x100) / 4);
+
+        lock_source(&target)
+            .write_bytes(0x40020, &7u32.to_le_bytes())
+            .unwrap();
+        let job = ScanJob::stored_next_scan(
+            target,
+            Arc::new(store),
+            ScanType::Changed,
+            vec![0u8; 4],
+            FloatMatch::Exact,
+        );
+        let store = finish(job).pop().unwrap();
+
+        assert_eq!(store.results().unwrap().addresses(), [0x40020]);
+    }
+}
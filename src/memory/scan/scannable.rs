@@ -0,0 +1,658 @@
+use super::search::{find_exact, find_typed, Primitive};
+use crate::memory::scan::compare::{compare_values, extract_value};
+use crate::memory::scan::types::{FloatMatch, ScanNumber, ScanType, ValueType};
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+/// Errors that can come out of putting a struct type together
+///
+#[derive(Error, Debug)]
+pub enum StructErrors {
+    #[error("Struct '{name}' has no fields")]
+    Empty { name: String },
+    #[error("{value_type:?} can't be a field of a struct")]
+    InvalidField { value_type: ValueType },
+    #[error("Fields at +0x{first:X} and +0x{second:X} overlap")]
+    Overlapping { first: usize, second: usize },
+    #[error("Expected {expected} values separated by commas, got {found}")]
+    WrongValueCount { expected: usize, found: usize },
+}
+
+/// Everything the scanner needs to know about a type of value. Built in numbers and any types
+/// registered while running, such as structs, are all scanned through this so adding a type only
+/// takes an implementation of it
+///
+pub trait Scannable: Send + Sync + Debug {
+    /// Name shown within combo boxes, registered types are told apart by it
+    ///
+    fn label(&self) -> &str;
+    /// Number of bytes a value takes up
+    ///
+    fn size(&self) -> usize;
+    /// Fast scan only checks offsets that are a multiple of this
+    ///
+    fn alignment(&self) -> usize {
+        self.size()
+    }
+    /// Whether values can be put in order, only ordered types can be scanned for being bigger,
+    /// smaller, between, increased and so on
+    ///
+    fn is_ordered(&self) -> bool {
+        false
+    }
+    /// Whether values are matched loosely rather than byte for byte
+    ///
+    fn is_float(&self) -> bool {
+        false
+    }
+    /// Parse a value typed in into the bytes it'd have within the target
+    ///
+    fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>>;
+    /// Turn a value back into text
+    ///
+    fn format(&self, bytes: &[u8]) -> String;
+    /// Decode a value into a number so it can be put in order, None for types that can't be
+    ///
+    fn decode(&self, _bytes: &[u8]) -> Option<ScanNumber> {
+        None
+    }
+    /// Compare a value against the one typed in, types that can't be put in order are only ever
+    /// compared byte for byte
+    ///
+    fn compare(
+        &self,
+        memory_bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: &ScanType,
+        float_match: &FloatMatch,
+    ) -> anyhow::Result<bool> {
+        let size = self.size();
+        let (Some(memory_val), Some(target_val)) =
+            (self.decode(memory_bytes), self.decode(value_bytes))
+        else {
+            return match scan_type {
+                ScanType::Exact => Ok(memory_bytes[..size] == value_bytes[..size]),
+                scan_type => Err(anyhow::anyhow!(
+                    "{} values can't be compared with {:?}",
+                    self.label(),
+                    scan_type
+                )),
+            };
+        };
+
+        match scan_type {
+            ScanType::Exact => match (memory_val, target_val) {
+                (ScanNumber::Float(memory_val), ScanNumber::Float(target_val)) => {
+                    Ok(float_match.matches(memory_val, target_val))
+                }
+                _ => Ok(memory_bytes[..size] == value_bytes[..size]),
+            },
+            ScanType::BiggerThan => Ok(memory_val > target_val),
+            ScanType::SmallerThan => Ok(memory_val < target_val),
+            ScanType::Between if value_bytes.len() >= size * 2 => {
+                let upper_val = self.decode(&value_bytes[size..]);
+                Ok(upper_val
+                    .is_some_and(|upper_val| memory_val >= target_val && memory_val <= upper_val))
+            }
+            scan_type => Err(anyhow::anyhow!(
+                "{:?} can't be used to compare against a value",
+                scan_type
+            )),
+        }
+    }
+    /// Compare a value against the one it had last scan moved up or down by the value typed in,
+    /// for increased by and decreased by. Only ordered types can be, floats are matched loosely
+    /// and anything else is added byte for byte so it wraps around at the size of the type like
+    /// the target would
+    ///
+    fn compare_change(
+        &self,
+        memory_bytes: &[u8],
+        previous_bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: &ScanType,
+        float_match: &FloatMatch,
+    ) -> anyhow::Result<bool> {
+        let increase = match scan_type {
+            ScanType::IncreasedBy if self.is_ordered() => true,
+            ScanType::DecreasedBy if self.is_ordered() => false,
+            scan_type => {
+                return Err(anyhow::anyhow!(
+                    "{} values can't be {:?}",
+                    self.label(),
+                    scan_type
+                ))
+            }
+        };
+
+        let size = self.size();
+        match (
+            self.decode(memory_bytes),
+            self.decode(previous_bytes),
+            self.decode(value_bytes),
+        ) {
+            (
+                Some(ScanNumber::Float(memory_val)),
+                Some(ScanNumber::Float(previous_val)),
+                Some(ScanNumber::Float(target_val)),
+            ) => {
+                let expected = if increase {
+                    previous_val + target_val
+                } else {
+                    previous_val - target_val
+                };
+                Ok(float_match.matches(memory_val, expected))
+            }
+            _ => Ok(memory_bytes[..size]
+                == wrapping_change(&previous_bytes[..size], &value_bytes[..size], increase)),
+        }
+    }
+    /// Hand every offset aligned to step where a value matches over to visit, by default each
+    /// offset is compared on its own
+    ///
+    fn find(
+        &self,
+        bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: &ScanType,
+        float_match: &FloatMatch,
+        step: usize,
+        visit: &mut dyn FnMut(usize),
+    ) -> anyhow::Result<()> {
+        let size = self.size();
+        let Some(last) = bytes.len().checked_sub(size) else {
+            return Ok(());
+        };
+
+        for offset in (0..=last).step_by(step) {
+            if self.compare(
+                &bytes[offset..offset + size],
+                value_bytes,
+                scan_type,
+                float_match,
+            )? {
+                visit(offset);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add or subtract two little endian values of the same width a byte at a time, carrying between
+/// bytes and dropping whatever carries out of the top
+///
+fn wrapping_change(previous: &[u8], value: &[u8], increase: bool) -> Vec<u8> {
+    let mut carry = 0i16;
+    previous
+        .iter()
+        .zip(value)
+        .map(|(&previous, &value)| {
+            let sum = match increase {
+                true => previous as i16 + value as i16 + carry,
+                false => previous as i16 - value as i16 - carry,
+            };
+            carry = match increase {
+                true => sum >> 8,
+                false => (sum < 0) as i16,
+            };
+            sum as u8
+        })
+        .collect()
+}
+
+/// A plain little endian number
+///
+#[derive(Debug)]
+pub struct Number<T> {
+    label: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Number<T> {
+    pub const fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Scannable for Number<T>
+where
+    T: Primitive + Display + FromStr + Debug,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    fn label(&self) -> &str {
+        self.label
+    }
+    fn size(&self) -> usize {
+        T::SIZE
+    }
+    fn is_ordered(&self) -> bool {
+        true
+    }
+    fn is_float(&self) -> bool {
+        T::FLOAT
+    }
+    /// Hex input is taken as the raw bits of the value, floats included
+    ///
+    fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>> {
+        let text = text.trim();
+
+        if is_hex {
+            let bits = u64::from_str_radix(text.trim_start_matches("0x"), 16)?;
+            if T::SIZE < 8 && bits >> (T::SIZE * 8) != 0 {
+                return Err(anyhow::anyhow!(
+                    "0x{:X} doesn't fit within a {}",
+                    bits,
+                    self.label
+                ));
+            }
+            return Ok(bits.to_le_bytes()[..T::SIZE].to_vec());
+        }
+
+        Ok(text.parse::<T>()?.to_bytes())
+    }
+    fn format(&self, bytes: &[u8]) -> String {
+        T::read(bytes).to_string()
+    }
+    fn decode(&self, bytes: &[u8]) -> Option<ScanNumber> {
+        Some(T::read(bytes).to_number())
+    }
+    /// Exact integers are searched for byte for byte, everything else is compared as a T a block
+    /// of offsets at a time
+    ///
+    fn find(
+        &self,
+        bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: &ScanType,
+        float_match: &FloatMatch,
+        step: usize,
+        visit: &mut dyn FnMut(usize),
+    ) -> anyhow::Result<()> {
+        if value_bytes.len() < T::SIZE {
+            return Err(anyhow::anyhow!("No {} value to scan for", self.label));
+        }
+        let target = T::read(value_bytes);
+        let float_match = *float_match;
+
+        match scan_type {
+            ScanType::Exact if !T::FLOAT => find_exact(bytes, &value_bytes[..T::SIZE], step, visit),
+            ScanType::Exact => find_typed(
+                bytes,
+                step,
+                |value: T| float_match.matches(value.to_f64(), target.to_f64()),
+                visit,
+            ),
+            ScanType::BiggerThan => find_typed(bytes, step, |value: T| value > target, visit),
+            ScanType::SmallerThan => find_typed(bytes, step, |value: T| value < target, visit),
+            ScanType::Between => {
+                if value_bytes.len() < T::SIZE * 2 {
+                    return Err(anyhow::anyhow!(
+                        "Value between needs both a lower and an upper {}",
+                        self.label
+                    ));
+                }
+                let upper = T::read(&value_bytes[T::SIZE..]);
+                find_typed(
+                    bytes,
+                    step,
+                    |value: T| (value >= target) & (value <= upper),
+                    visit,
+                )
+            }
+            scan_type => {
+                return Err(anyhow::anyhow!(
+                    "{:?} can't be used for a first scan",
+                    scan_type
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The built in numbers
+///
+pub static BYTE: Number<u8> = Number::new("Byte");
+pub static TWO_BYTES: Number<u16> = Number::new("Two Bytes");
+pub static FOUR_BYTES: Number<u32> = Number::new("Four Bytes");
+pub static EIGHT_BYTES: Number<u64> = Number::new("Eight Bytes");
+pub static SIGNED_BYTE: Number<i8> = Number::new("Signed Byte");
+pub static SIGNED_TWO_BYTES: Number<i16> = Number::new("Signed Two Bytes");
+pub static SIGNED_FOUR_BYTES: Number<i32> = Number::new("Signed Four Bytes");
+pub static SIGNED_EIGHT_BYTES: Number<i64> = Number::new("Signed Eight Bytes");
+pub static FLOAT: Number<f32> = Number::new("Float");
+pub static DOUBLE: Number<f64> = Number::new("Double");
+
+/// A fixed length run of bytes, such as a buffer within a struct. Text arrays are typed in and
+/// shown as the string they hold, anything else as hex bytes
+///
+#[derive(Debug)]
+pub struct ByteArray {
+    label: String,
+    length: usize,
+    text: bool,
+}
+
+impl ByteArray {
+    /// Longest run of bytes shown before the rest is cut off
+    ///
+    const MAX_SHOWN: usize = 64;
+
+    pub fn new(label: &str, length: usize, text: bool) -> Self {
+        Self {
+            label: label.to_string(),
+            length,
+            text,
+        }
+    }
+}
+
+impl Scannable for ByteArray {
+    fn label(&self) -> &str {
+        &self.label
+    }
+    fn size(&self) -> usize {
+        self.length
+    }
+    fn alignment(&self) -> usize {
+        1
+    }
+    /// Values shorter than the array are padded out with zeros
+    ///
+    fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = match self.text && !is_hex {
+            true => text.as_bytes().to_vec(),
+            false => text
+                .split_whitespace()
+                .map(|byte| u8::from_str_radix(byte, 16))
+                .collect::<Result<Vec<u8>, _>>()?,
+        };
+        if bytes.len() > self.length {
+            return Err(anyhow::anyhow!(
+                "{} bytes doesn't fit within a {}",
+                bytes.len(),
+                self.label
+            ));
+        }
+        bytes.resize(self.length, 0);
+        Ok(bytes)
+    }
+    fn format(&self, bytes: &[u8]) -> String {
+        let bytes = &bytes[..bytes.len().min(self.length)];
+        let shown = &bytes[..bytes.len().min(Self::MAX_SHOWN)];
+        let more = match bytes.len() > shown.len() {
+            true => "...",
+            false => "",
+        };
+
+        match self.text {
+            true => {
+                let length = shown.iter().position(|&byte| byte == 0);
+                let more = if length.is_some() { "" } else { more };
+                let text = String::from_utf8_lossy(&shown[..length.unwrap_or(shown.len())]);
+                format!("\"{text}{more}\"")
+            }
+            false => {
+                let hex = shown
+                    .iter()
+                    .map(|byte| format!("{byte:02X}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{hex}{more}")
+            }
+        }
+    }
+}
+
+/// A named value at an offset within a struct
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructField {
+    pub name: String,
+    pub offset: usize,
+    pub value_type: ValueType,
+}
+
+/// A user defined struct made up of values at fixed offsets. Values are typed in as each of their
+/// fields separated by commas and only the fields are compared, whatever padding sits between
+/// them can be anything
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructType {
+    pub name: String,
+    pub size: usize,
+    /// Kept in offset order
+    pub fields: Vec<StructField>,
+}
+
+impl StructType {
+    /// Put a struct together, fields can't be patterns or groups and can't overlap. The size is
+    /// bumped up to fit every field if it's too small
+    ///
+    pub fn new(name: &str, size: usize, mut fields: Vec<StructField>) -> anyhow::Result<Self> {
+        if fields.is_empty() {
+            return Err(StructErrors::Empty {
+                name: name.to_string(),
+            }
+            .into());
+        }
+        if let Some(field) = fields
+            .iter()
+            .find(|field| field.value_type.scannable().is_none())
+        {
+            return Err(StructErrors::InvalidField {
+                value_type: field.value_type.clone(),
+            }
+            .into());
+        }
+
+        fields.sort_by_key(|field| field.offset);
+        for pair in fields.windows(2) {
+            if pair[0].offset + pair[0].value_type.size() > pair[1].offset {
+                return Err(StructErrors::Overlapping {
+                    first: pair[0].offset,
+                    second: pair[1].offset,
+                }
+                .into());
+            }
+        }
+
+        let end = fields
+            .iter()
+            .map(|field| field.offset + field.value_type.size())
+            .max()
+            .unwrap_or(0);
+
+        Ok(Self {
+            name: name.to_string(),
+            size: size.max(end),
+            fields,
+        })
+    }
+    /// The bytes of a single field out of a whole value
+    ///
+    fn field_bytes<'a>(&self, field: &StructField, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[field.offset..field.offset + field.value_type.size()]
+    }
+}
+
+impl Scannable for StructType {
+    fn label(&self) -> &str {
+        &self.name
+    }
+    fn size(&self) -> usize {
+        self.size
+    }
+    /// Aligned like a C struct would be, to its most aligned field
+    ///
+    fn alignment(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| field.value_type.alignment())
+            .max()
+            .unwrap_or(1)
+    }
+    fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>> {
+        let values: Vec<&str> = text.split(',').collect();
+        if values.len() != self.fields.len() {
+            return Err(StructErrors::WrongValueCount {
+                expected: self.fields.len(),
+                found: values.len(),
+            }
+            .into());
+        }
+
+        let mut bytes = vec![0u8; self.size];
+        for (field, value) in self.fields.iter().zip(values) {
+            let field_bytes = field.value_type.parse(value, is_hex)?;
+            bytes[field.offset..field.offset + field_bytes.len()].copy_from_slice(&field_bytes);
+        }
+        Ok(bytes)
+    }
+    fn format(&self, bytes: &[u8]) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| extract_value(self.field_bytes(field, bytes), &field.value_type))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(", "))
+    }
+    fn compare(
+        &self,
+        memory_bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: &ScanType,
+        float_match: &FloatMatch,
+    ) -> anyhow::Result<bool> {
+        if *scan_type != ScanType::Exact {
+            return Err(anyhow::anyhow!(
+                "{} values can't be compared with {:?}",
+                self.name,
+                scan_type
+            ));
+        }
+
+        for field in self.fields.iter() {
+            let matched = compare_values(
+                self.field_bytes(field, memory_bytes),
+                self.field_bytes(field, value_bytes),
+                scan_type,
+                &field.value_type,
+                float_match,
+            )?;
+            if !matched {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// A registered type as it's held within a ValueType, told apart by name
+///
+#[derive(Clone, Debug)]
+pub struct CustomType(pub Arc<dyn Scannable>);
+
+impl PartialEq for CustomType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.label() == other.0.label()
+    }
+}
+
+impl Eq for CustomType {}
+
+/// Types registered on top of the built in ones
+///
+static REGISTERED: RwLock<Vec<Arc<dyn Scannable>>> = RwLock::new(Vec::new());
+
+/// Make a type available to scan for, one registered earlier under the same name is replaced
+///
+pub fn register_value_type(scannable: Arc<dyn Scannable>) -> ValueType {
+    let mut registered = REGISTERED
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    registered.retain(|other| other.label() != scannable.label());
+    registered.push(scannable.clone());
+
+    ValueType::Custom(CustomType(scannable))
+}
+
+/// Every type registered so far, in the order they were registered
+///
+pub fn registered_value_types() -> Vec<ValueType> {
+    REGISTERED
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .map(|scannable| ValueType::Custom(CustomType(scannable.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(
+        scannable: &dyn Scannable,
+        bytes: &[u8],
+        value_bytes: &[u8],
+        scan_type: ScanType,
+    ) -> anyhow::Result<Vec<usize>> {
+        let mut hits = Vec::new();
+        scannable.find(
+            bytes,
+            value_bytes,
+            &scan_type,
+            &FloatMatch::default(),
+            scannable.alignment(),
+            &mut |offset| hits.push(offset),
+        )?;
+        Ok(hits)
+    }
+
+    #[test]
+    fn between_includes_both_bounds() {
+        let bytes: Vec<u8> = [9u32, 10, 15, 20, 21]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let bounds = [
+            FOUR_BYTES.parse("10", false).unwrap(),
+            FOUR_BYTES.parse("20", false).unwrap(),
+        ]
+        .concat();
+
+        let hits = find(&FOUR_BYTES, &bytes, &bounds, ScanType::Between).unwrap();
+        assert_eq!(hits, [4, 8, 12]);
+
+        let float = |value: f32| value.to_le_bytes();
+        let bounds = [float(-1.5), float(2.5)].concat();
+        for (value, inside) in [(-1.5, true), (2.5, true), (2.51, false), (-1.6, false)] {
+            let matched = FLOAT
+                .compare(
+                    &float(value),
+                    &bounds,
+                    &ScanType::Between,
+                    &FloatMatch::Exact,
+                )
+                .unwrap();
+            assert_eq!(matched, inside, "{value}");
+        }
+    }
+
+    #[test]
+    fn missing_values_are_refused() {
+        let bytes = [0u8; 16];
+        let lower = 10u32.to_le_bytes();
+        assert!(find(&FOUR_BYTES, &bytes, &lower, ScanType::Between).is_err());
+        assert!(find(&FOUR_BYTES, &bytes, &[], ScanType::Exact).is_err());
+        assert!(find(&FOUR_BYTES, &bytes, &lower, ScanType::Exact).is_ok());
+    }
+}
@@ -0,0 +1,280 @@
+use super::group::ScanGroup;
+use super::pattern::BytePattern;
+use crate::memory::scan::types::ScanNumber;
+use memchr::memmem::Finder;
+
+/// How many offsets are compared before the hits among them are handed out
+///
+const BLOCK_SIZE: usize = 64;
+
+/// A number that can be read straight out of little endian bytes, lets typed scans be compiled
+/// once per type instead of matching on the type at every offset
+///
+pub trait Primitive: Copy + PartialOrd {
+    const SIZE: usize;
+    const FLOAT: bool;
+
+    fn read(bytes: &[u8]) -> Self;
+    fn to_f64(self) -> f64;
+    fn to_number(self) -> ScanNumber;
+    fn to_bytes(self) -> Vec<u8>;
+}
+
+macro_rules! impl_primitive {
+    ($number:ident as $wide:ty, $float:literal: $($ty:ty),*) => {
+        $(
+            impl Primitive for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+                const FLOAT: bool = $float;
+
+                fn read(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+                }
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+                fn to_number(self) -> ScanNumber {
+                    ScanNumber::$number(self as $wide)
+                }
+                fn to_bytes(self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(Unsigned as u64, false: u8, u16, u32, u64);
+impl_primitive!(Signed as i64, false: i8, i16, i32, i64);
+impl_primitive!(Float as f64, true: f32, f64);
+
+/// Find every offset aligned to step where needle sits, the search itself is SIMD accelerated
+/// and matches are allowed to overlap
+///
+pub fn find_exact(haystack: &[u8], needle: &[u8], step: usize, mut visit: impl FnMut(usize)) {
+    let finder = Finder::new(needle);
+    let mut pos = 0;
+
+    while pos < haystack.len() {
+        let Some(hit) = finder.find(&haystack[pos..]) else {
+            break;
+        };
+        let offset = pos + hit;
+
+        if offset.is_multiple_of(step) {
+            visit(offset);
+            pos = offset + 1;
+        } else {
+            pos = offset.next_multiple_of(step);
+        }
+    }
+}
+
+/// Read a value of type T at every offset aligned to step and hand out the ones the predicate
+/// holds for. Offsets are compared a block at a time into a bitmask without branching so the
+/// comparisons can be vectorized, only the hits are walked afterwards
+///
+pub fn find_typed<T: Primitive>(
+    bytes: &[u8],
+    step: usize,
+    predicate: impl Fn(T) -> bool,
+    mut visit: impl FnMut(usize),
+) {
+    let Some(last) = bytes.len().checked_sub(T::SIZE) else {
+        return;
+    };
+    let slots = last / step + 1;
+
+    for block_start in (0..slots).step_by(BLOCK_SIZE) {
+        let block_end = (block_start + BLOCK_SIZE).min(slots);
+        let mut mask = 0u64;
+
+        for slot in block_start..block_end {
+            let offset = slot * step;
+            let hit = predicate(T::read(&bytes[offset..offset + T::SIZE]));
+            mask |= (hit as u64) << (slot - block_start);
+        }
+
+        while mask != 0 {
+            visit((block_start + mask.trailing_zeros() as usize) * step);
+            mask &= mask - 1;
+        }
+    }
+}
+
+/// Find every offset aligned to step where a pattern matches. The longest run of fixed bytes is
+/// searched for first and the rest of the pattern is only checked around those, case folding
+/// can't be searched for that way so those fall back to checking each offset
+///
+pub fn find_pattern(
+    bytes: &[u8],
+    pattern: &BytePattern,
+    step: usize,
+    mut visit: impl FnMut(usize),
+) {
+    let width = pattern.bytes.len();
+    if bytes.len() < width {
+        return;
+    }
+
+    if pattern.case_insensitive {
+        for offset in (0..=bytes.len() - width).step_by(step) {
+            if pattern.matches(&bytes[offset..]) {
+                visit(offset);
+            }
+        }
+        return;
+    }
+
+    let (anchor_offset, anchor) = pattern.anchor();
+    find_exact(bytes, anchor, 1, |hit| {
+        let Some(offset) = hit.checked_sub(anchor_offset) else {
+            return;
+        };
+        if offset.is_multiple_of(step)
+            && offset + width <= bytes.len()
+            && pattern.matches(&bytes[offset..])
+        {
+            visit(offset);
+        }
+    });
+}
+
+/// Find every offset aligned to step where a group matches. The widest of its values that can be
+/// matched byte for byte is searched for first, groups made up of nothing but floats are checked
+/// at each offset
+///
+pub fn find_group(bytes: &[u8], group: &ScanGroup, step: usize, mut visit: impl FnMut(usize)) {
+    let width = group.width();
+    if bytes.len() < width {
+        return;
+    }
+
+    let Some(anchor) = group.anchor() else {
+        for offset in (0..=bytes.len() - width).step_by(step) {
+            if group.matches(&bytes[offset..]) {
+                visit(offset);
+            }
+        }
+        return;
+    };
+
+    find_exact(bytes, &anchor.bytes, 1, |hit| {
+        let Some(offset) = hit.checked_sub(anchor.offset) else {
+            return;
+        };
+        if offset.is_multiple_of(step)
+            && offset + width <= bytes.len()
+            && group.matches(&bytes[offset..])
+        {
+            visit(offset);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::scan::group::GroupMember;
+    use crate::memory::scan::types::{FloatMatch, ValueType};
+
+    fn collect(find: impl FnOnce(&mut dyn FnMut(usize))) -> Vec<usize> {
+        let mut hits = Vec::new();
+        find(&mut |offset| hits.push(offset));
+        hits
+    }
+
+    /// Every offset aligned to step that the predicate holds for, checked one at a time
+    ///
+    fn naive<T: Primitive>(bytes: &[u8], step: usize, predicate: impl Fn(T) -> bool) -> Vec<usize> {
+        (0..=bytes.len() - T::SIZE)
+            .step_by(step)
+            .filter(|&offset| predicate(T::read(&bytes[offset..])))
+            .collect()
+    }
+
+    #[test]
+    fn exact_hits_are_only_kept_when_aligned() {
+        let mut bytes = vec![0u8; 0x40];
+        for offset in [0x3, 0x8, 0x11, 0x3C] {
+            bytes[offset..offset + 4].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
+        }
+        let needle = 0xDEADBEEFu32.to_le_bytes();
+
+        // Full step scans find everything, fast scans only the aligned hits including the one
+        // ending right at the end of the region
+        let full = collect(|visit| find_exact(&bytes, &needle, 1, visit));
+        assert_eq!(full, [0x3, 0x8, 0x11, 0x3C]);
+        let fast = collect(|visit| find_exact(&bytes, &needle, 4, visit));
+        assert_eq!(fast, [0x8, 0x3C]);
+
+        // An unaligned hit just before an aligned one doesn't hide it
+        let mut bytes = vec![0u8; 0x10];
+        bytes[0x1..0x4].copy_from_slice(&[0xAA, 0xAA, 0xAA]);
+        let hits = collect(|visit| find_exact(&bytes, &[0xAA, 0xAA], 2, visit));
+        assert_eq!(hits, [0x2]);
+        let hits = collect(|visit| find_exact(&bytes, &[0xAA, 0xAA], 1, visit));
+        assert_eq!(hits, [0x1, 0x2]);
+    }
+
+    #[test]
+    fn exact_hits_can_overlap() {
+        let bytes = [0u8; 8];
+        let hits = collect(|visit| find_exact(&bytes, &[0, 0, 0, 0], 1, visit));
+        assert_eq!(hits, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn typed_scans_match_a_slot_at_a_time() {
+        // Enough slots to span a few blocks and end part way through one
+        let bytes: Vec<u8> = (0..0x203u32).map(|idx| (idx * 7 % 256) as u8).collect();
+
+        for step in [1, 2, 4] {
+            let hits =
+                collect(|visit| find_typed(&bytes, step, |value: u32| value > 0x8000_0000, visit));
+            assert_eq!(hits, naive(&bytes, step, |value: u32| value > 0x8000_0000));
+            let hits =
+                collect(|visit| find_typed(&bytes, step, |value: u16| value < 0x1000, visit));
+            assert_eq!(hits, naive(&bytes, step, |value: u16| value < 0x1000));
+        }
+
+        // The last value that fits within the region is still checked
+        let mut bytes = vec![0u8; 0x83];
+        bytes[0x7F..].copy_from_slice(&1u32.to_le_bytes());
+        let hits = collect(|visit| find_typed(&bytes, 1, |value: u32| value == 1, visit));
+        assert_eq!(hits, [0x7F]);
+        let hits = collect(|visit| find_typed(&bytes[..2], 1, |value: u32| value == 1, visit));
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn patterns_and_groups_are_found_around_their_anchor() {
+        let mut bytes = vec![0u8; 0x20];
+        bytes[0x6..0xA].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10]);
+        bytes[0x1C..].copy_from_slice(&[0x48, 0x8B, 0x05, 0x20]);
+
+        let pattern = BytePattern::parse(&ValueType::ArrayOfBytes, "48 8B 05 ??", false).unwrap();
+        let hits = collect(|visit| find_pattern(&bytes, &pattern, 1, visit));
+        assert_eq!(hits, [0x6, 0x1C]);
+        let hits = collect(|visit| find_pattern(&bytes, &pattern, 4, visit));
+        assert_eq!(hits, [0x1C]);
+
+        let group = ScanGroup::new(vec![
+            GroupMember {
+                offset: 0,
+                value_type: ValueType::Byte,
+                bytes: vec![0x48],
+                float_match: FloatMatch::Exact,
+            },
+            GroupMember {
+                offset: 2,
+                value_type: ValueType::TwoBytes,
+                bytes: 0x2005u16.to_le_bytes().to_vec(),
+                float_match: FloatMatch::Exact,
+            },
+        ])
+        .unwrap();
+        let hits = collect(|visit| find_group(&bytes, &group, 1, visit));
+        assert_eq!(hits, [0x1C]);
+    }
+}
@@ -0,0 +1,291 @@
+use super::pattern::BytePattern;
+use super::scannable::{
+    registered_value_types, CustomType, Scannable, BYTE, DOUBLE, EIGHT_BYTES, FLOAT, FOUR_BYTES,
+    SIGNED_BYTE, SIGNED_EIGHT_BYTES, SIGNED_FOUR_BYTES, SIGNED_TWO_BYTES, TWO_BYTES,
+};
+
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub enum ScanType {
+    #[default]
+    Exact,
+    BiggerThan,
+    SmallerThan,
+    /// Inclusive on both ends, the value bytes hold the lower bound followed by the upper one
+    Between,
+    UnknownInitial,
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+    IncreasedBy,
+    DecreasedBy,
+}
+
+impl ScanType {
+    /// Every type in the order they're offered up
+    ///
+    pub const ALL: [ScanType; 11] = [
+        ScanType::Exact,
+        ScanType::BiggerThan,
+        ScanType::SmallerThan,
+        ScanType::Between,
+        ScanType::UnknownInitial,
+        ScanType::Changed,
+        ScanType::Unchanged,
+        ScanType::Increased,
+        ScanType::Decreased,
+        ScanType::IncreasedBy,
+        ScanType::DecreasedBy,
+    ];
+
+    /// Scans which compare against what was found last time, these can only be used for a next
+    /// scan
+    ///
+    pub fn needs_previous(&self) -> bool {
+        matches!(
+            self,
+            ScanType::Changed
+                | ScanType::Unchanged
+                | ScanType::Increased
+                | ScanType::Decreased
+                | ScanType::IncreasedBy
+                | ScanType::DecreasedBy
+        )
+    }
+    /// Name shown within combo boxes
+    ///
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScanType::Exact => "Exact",
+            ScanType::BiggerThan => "Bigger Than",
+            ScanType::SmallerThan => "Smaller Than",
+            ScanType::Between => "Value Between",
+            ScanType::UnknownInitial => "Unknown Initial Value",
+            ScanType::Changed => "Changed",
+            ScanType::Unchanged => "Unchanged",
+            ScanType::Increased => "Increased",
+            ScanType::Decreased => "Decreased",
+            ScanType::IncreasedBy => "Increased By",
+            ScanType::DecreasedBy => "Decreased By",
+        }
+    }
+    /// Scans which compare against the value that's been typed in
+    ///
+    pub fn needs_value(&self) -> bool {
+        !matches!(
+            self,
+            ScanType::UnknownInitial
+                | ScanType::Changed
+                | ScanType::Unchanged
+                | ScanType::Increased
+                | ScanType::Decreased
+        )
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    Byte,
+    TwoBytes,
+    #[default]
+    FourBytes,
+    EightBytes,
+    SignedByte,
+    SignedTwoBytes,
+    SignedFourBytes,
+    SignedEightBytes,
+    Float,
+    Double,
+    ArrayOfBytes,
+    Ascii,
+    Utf16,
+    /// Several values at offsets from each other, defined separately
+    Group,
+    /// A type registered while running, such as a struct
+    Custom(CustomType),
+}
+
+/// A value decoded out of its bytes so it can be compared the way its type should be, only values
+/// of the same type are ever compared against each other
+///
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum ScanNumber {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+impl ValueType {
+    /// Every built in type in the order they're offered up
+    ///
+    pub const ALL: [ValueType; 14] = [
+        ValueType::Byte,
+        ValueType::TwoBytes,
+        ValueType::FourBytes,
+        ValueType::EightBytes,
+        ValueType::SignedByte,
+        ValueType::SignedTwoBytes,
+        ValueType::SignedFourBytes,
+        ValueType::SignedEightBytes,
+        ValueType::Float,
+        ValueType::Double,
+        ValueType::ArrayOfBytes,
+        ValueType::Ascii,
+        ValueType::Utf16,
+        ValueType::Group,
+    ];
+
+    /// Every built in type followed by every registered one
+    ///
+    pub fn all() -> Vec<ValueType> {
+        let mut all = ValueType::ALL.to_vec();
+        all.extend(registered_value_types());
+        all
+    }
+    /// Find a type by the name it's shown with
+    ///
+    pub fn from_label(label: &str) -> Option<ValueType> {
+        ValueType::all()
+            .into_iter()
+            .find(|value_type| value_type.label() == label)
+    }
+    /// How values of this type are parsed, compared and shown. Patterns and groups are matched
+    /// through BytePattern and ScanGroup instead so they have none
+    ///
+    pub fn scannable(&self) -> Option<&dyn Scannable> {
+        Some(match self {
+            ValueType::Byte => &BYTE,
+            ValueType::TwoBytes => &TWO_BYTES,
+            ValueType::FourBytes => &FOUR_BYTES,
+            ValueType::EightBytes => &EIGHT_BYTES,
+            ValueType::SignedByte => &SIGNED_BYTE,
+            ValueType::SignedTwoBytes => &SIGNED_TWO_BYTES,
+            ValueType::SignedFourBytes => &SIGNED_FOUR_BYTES,
+            ValueType::SignedEightBytes => &SIGNED_EIGHT_BYTES,
+            ValueType::Float => &FLOAT,
+            ValueType::Double => &DOUBLE,
+            ValueType::Custom(custom) => custom.0.as_ref(),
+            ValueType::ArrayOfBytes | ValueType::Ascii | ValueType::Utf16 | ValueType::Group => {
+                return None
+            }
+        })
+    }
+    /// Size in bytes of a value of this type, patterns vary in length so for those it's the size
+    /// of a single character
+    ///
+    pub fn size(&self) -> usize {
+        match (self, self.scannable()) {
+            (_, Some(scannable)) => scannable.size(),
+            (ValueType::Utf16, None) => 2,
+            (_, None) => 1,
+        }
+    }
+    /// Fast scan only checks offsets that are a multiple of this
+    ///
+    pub fn alignment(&self) -> usize {
+        self.scannable()
+            .map_or(self.size(), |scannable| scannable.alignment())
+    }
+    /// Name shown within combo boxes
+    ///
+    pub fn label(&self) -> &str {
+        match self {
+            ValueType::ArrayOfBytes => "Array of Bytes",
+            ValueType::Ascii => "String",
+            ValueType::Utf16 => "UTF-16 String",
+            ValueType::Group => "Group",
+            _ => self.scannable().map_or("", |scannable| scannable.label()),
+        }
+    }
+    /// Is this a run of bytes rather than a single number
+    ///
+    pub fn is_pattern(&self) -> bool {
+        matches!(
+            self,
+            ValueType::ArrayOfBytes | ValueType::Ascii | ValueType::Utf16
+        )
+    }
+    /// Is this a group of values
+    ///
+    pub fn is_group(&self) -> bool {
+        *self == ValueType::Group
+    }
+    /// Is this a floating point type
+    ///
+    pub fn is_float(&self) -> bool {
+        self.scannable()
+            .is_some_and(|scannable| scannable.is_float())
+    }
+    /// Can values of this type be put in order
+    ///
+    pub fn is_ordered(&self) -> bool {
+        self.scannable()
+            .is_some_and(|scannable| scannable.is_ordered())
+    }
+    /// Decode a value of this type out of its little endian bytes, anything that isn't a number
+    /// has no numeric value and is only ever compared byte for byte
+    ///
+    pub fn decode(&self, bytes: &[u8]) -> ScanNumber {
+        self.scannable()
+            .and_then(|scannable| scannable.decode(bytes))
+            .unwrap_or(ScanNumber::Unsigned(0))
+    }
+    /// Parse a value typed in into the bytes it'd have within the target
+    ///
+    pub fn parse(&self, text: &str, is_hex: bool) -> anyhow::Result<Vec<u8>> {
+        if self.is_pattern() {
+            return Ok(BytePattern::parse(self, text, false)?.bytes);
+        }
+
+        match self.scannable() {
+            Some(scannable) => scannable.parse(text, is_hex),
+            None => Err(anyhow::anyhow!("Groups are entered a value at a time")),
+        }
+    }
+}
+
+/// How float values are matched against the value typed in, the same float is rarely stored bit
+/// for bit so rounding to what was typed in is the default
+///
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub enum FloatRounding {
+    #[default]
+    Rounded,
+    Truncated,
+    Epsilon,
+    Exact,
+}
+
+/// A float rounding mode along with everything needed to apply it
+///
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub enum FloatMatch {
+    #[default]
+    Exact,
+    Rounded {
+        decimals: i32,
+    },
+    Truncated {
+        decimals: i32,
+    },
+    Epsilon(f64),
+}
+
+impl FloatMatch {
+    /// Check a float against the value it's expected to be
+    ///
+    pub fn matches(&self, actual: f64, expected: f64) -> bool {
+        match self {
+            FloatMatch::Exact => actual == expected,
+            FloatMatch::Rounded { decimals } => {
+                let scale = 10f64.powi(*decimals);
+                (actual * scale).round() == (expected * scale).round()
+            }
+            FloatMatch::Truncated { decimals } => {
+                let scale = 10f64.powi(*decimals);
+                (actual * scale).trunc() == (expected * scale).trunc()
+            }
+            FloatMatch::Epsilon(epsilon) => (actual - expected).abs() <= *epsilon,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::source::{MemorySource, SourceErrors};
+use crate::memory::process::types::{MemoryRegion, ModuleInfo, MEM_COMMIT, MEM_IMAGE, MEM_PRIVATE};
+use crate::memory::process::ProcessErrors;
+
+/// An in-memory target built entirely out of byte buffers along with a made up region and module
+/// list, lets us drive the scanner, disassembler and imports lookups without a live process
+///
+#[derive(Debug, Clone, Default)]
+pub struct MockTarget {
+    name: String,
+    regions: Vec<(MemoryRegion, Vec<u8>)>,
+    modules: Vec<ModuleInfo>,
+}
+
+impl MockTarget {
+    /// Create an empty target, regions and modules are added on with the with_* functions
+    ///
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+    /// Add a private region of memory backed by the given bytes
+    ///
+    pub fn with_region(self, base_address: u64, bytes: Vec<u8>, protect: u32) -> Self {
+        self.with_typed_region(base_address, bytes, protect, MEM_PRIVATE)
+    }
+    /// Add a region of memory with an explicit MEM_* type
+    ///
+    pub fn with_typed_region(
+        mut self,
+        base_address: u64,
+        bytes: Vec<u8>,
+        protect: u32,
+        region_type: u32,
+    ) -> Self {
+        let region = MemoryRegion {
+            base_address,
+            region_size: bytes.len() as u64,
+            protect,
+            state: MEM_COMMIT,
+            region_type,
+        };
+        self.regions.push((region, bytes));
+        self.regions.sort_by_key(|(region, _)| region.base_address);
+        self
+    }
+    /// Add a module, its bytes are mapped in as an image region at the module's base
+    ///
+    pub fn with_module(
+        mut self,
+        name: &str,
+        base_address: u64,
+        bytes: Vec<u8>,
+        protect: u32,
+    ) -> Self {
+        self.modules.push(ModuleInfo {
+            name: name.to_string(),
+            path: name.to_string(),
+            base_address,
+            size: bytes.len() as u64,
+        });
+        self.with_typed_region(base_address, bytes, protect, MEM_IMAGE)
+    }
+    /// Find the region and offset within it that an address falls into
+    ///
+    fn locate(&self, address: u64) -> Option<(usize, usize)> {
+        self.regions
+            .iter()
+            .position(|(region, _)| region.contains(address))
+            .map(|idx| (idx, (address - self.regions[idx].0.base_address) as usize))
+    }
+}
+
+impl MemorySource for MockTarget {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    /// Reads may span neighbouring regions but every byte has to be backed, same as a failed
+    /// ReadProcessMemory on a live process
+    ///
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::with_capacity(size);
+
+        while buffer.len() < size {
+            let current = address as u64 + buffer.len() as u64;
+            let (idx, offset) = self
+                .locate(current)
+                .ok_or(SourceErrors::Unmapped { address: current })?;
+            let bytes = &self.regions[idx].1;
+            let to_copy = std::cmp::min(size - buffer.len(), bytes.len() - offset);
+            buffer.extend_from_slice(&bytes[offset..offset + to_copy]);
+        }
+
+        Ok(buffer)
+    }
+    fn write_bytes(&mut self, address: usize, bytes: &[u8]) -> anyhow::Result<()> {
+        let mut written = 0;
+
+        while written < bytes.len() {
+            let current = address as u64 + written as u64;
+            let (idx, offset) = self
+                .locate(current)
+                .ok_or(SourceErrors::Unmapped { address: current })?;
+            let region_bytes = &mut self.regions[idx].1;
+            let to_copy = std::cmp::min(bytes.len() - written, region_bytes.len() - offset);
+            region_bytes[offset..offset + to_copy]
+                .copy_from_slice(&bytes[written..written + to_copy]);
+            written += to_copy;
+        }
+
+        Ok(())
+    }
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+        Ok(self.regions.iter().map(|(region, _)| *region).collect())
+    }
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+        Ok(self.modules.clone())
+    }
+    fn base(&mut self) -> anyhow::Result<u64> {
+        self.modules
+            .iter()
+            .find(|module| module.name == self.name)
+            .map(|module| module.base_address)
+            .ok_or_else(|| {
+                ProcessErrors::ModuleNotFound {
+                    module_name: self.name.clone(),
+                }
+                .into()
+            })
+    }
+}
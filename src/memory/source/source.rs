@@ -0,0 +1,230 @@
+use crate::memory::process::types::{MemoryRegion, ModuleInfo, ThreadInfo};
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+
+/// Errors any source can hand back regardless of what's behind it
+///
+#[derive(Error, Debug)]
+pub enum SourceErrors {
+    #[error("Target '{target_name}' is read only")]
+    ReadOnly { target_name: String },
+    #[error("Address 0x{address:X} is not mapped")]
+    Unmapped { address: u64 },
+}
+
+/// Anything we can analyse, whether that's a live process or something built up from buffers,
+/// every view talks to its target through this rather than a concrete process so the same code
+/// paths can be driven without a running target
+///
+pub trait MemorySource: Send {
+    /// Name of the target, for processes this is also the name of the main module
+    ///
+    fn name(&self) -> String;
+    /// Read bytes from the target at the given location
+    ///
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>>;
+    /// Write an array of bytes to the target at the given location
+    ///
+    fn write_bytes(&mut self, address: usize, bytes: &[u8]) -> anyhow::Result<()>;
+    /// Every region of memory the target has
+    ///
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>>;
+    /// Every module loaded within the target
+    ///
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>>;
+    /// Base address of the main module
+    ///
+    fn base(&mut self) -> anyhow::Result<u64>;
+    /// Every thread within the target, not every source knows about threads
+    ///
+    fn threads(&mut self) -> anyhow::Result<Vec<ThreadInfo>> {
+        Ok(Vec::new())
+    }
+    /// Whether writes are refused, things like dumps are only ever analysed
+    ///
+    fn is_read_only(&self) -> bool {
+        false
+    }
+    /// Bitness code within the target should be decoded as, either 16, 32 or 64
+    ///
+    fn bitness(&self) -> u32 {
+        64
+    }
+    /// Whether the target ran on Windows, this decides how wide a C long is. Live processes run
+    /// on whatever we're running on
+    ///
+    fn is_windows(&self) -> bool {
+        cfg!(windows)
+    }
+    /// Read partitions of large allocations, any page that fails to read is filled with zeros
+    ///
+    fn read_bytes_paged(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let step_size: usize = std::cmp::min(size, 0x1000);
+        let mut buffer = Vec::with_capacity(size);
+
+        for i in (0..size).step_by(step_size) {
+            let remaining = size - i;
+            let size_to_read = std::cmp::min(step_size, remaining);
+
+            match self.read_bytes(address + i, size_to_read) {
+                Ok(page_data) => {
+                    buffer.extend_from_slice(&page_data);
+                }
+                Err(_) => {
+                    // If a page fails, fill with zeros and continue
+                    buffer.extend_from_slice(&vec![0u8; size_to_read]);
+                }
+            }
+        }
+
+        Ok(buffer)
+    }
+    /// Read a pointer from the target, these are as wide as the target's bitness
+    ///
+    fn read_pointer(&mut self, address: usize) -> anyhow::Result<u64> {
+        self.read_pointer_sized(address, self.bitness() as usize / 8)
+    }
+    /// Read a pointer of a given width, for things like images which don't have to match the
+    /// bitness of the target they're loaded into
+    ///
+    fn read_pointer_sized(&mut self, address: usize, size: usize) -> anyhow::Result<u64> {
+        let bytes = self.read_bytes(address, size)?;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0u64, |value, &byte| (value << 8) | byte as u64))
+    }
+}
+
+/// How views hold onto their target, every tab shares the same one so opening a tab no longer
+/// means cloning (and later closing) the underlying handle
+///
+pub type SharedSource = Arc<Mutex<dyn MemorySource>>;
+
+/// Wrap a source up so it can be handed out to views
+///
+pub fn share_source<S: MemorySource + 'static>(source: S) -> SharedSource {
+    Arc::new(Mutex::new(source))
+}
+
+/// Lock a shared source, a poisoned lock only means a view panicked while holding it so there's
+/// no reason for every other view to follow suit
+///
+pub fn lock_source(source: &SharedSource) -> MutexGuard<'_, dyn MemorySource + 'static> {
+    source
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// How much of a shared source is read while holding onto it before letting others have a turn
+///
+pub const SHARED_READ_CHUNK: usize = 0x10000;
+
+/// Read a large range of a shared source, the source is only held for a chunk at a time so
+/// background work reading whole regions doesn't leave every other thread (the UI included)
+/// waiting on it. Like read_bytes_paged, pages that fail to read are filled with zeros
+///
+pub fn read_shared_paged(
+    source: &SharedSource,
+    address: usize,
+    size: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(size);
+
+    for offset in (0..size).step_by(SHARED_READ_CHUNK) {
+        let chunk_size = SHARED_READ_CHUNK.min(size - offset);
+        buffer.extend(lock_source(source).read_bytes_paged(address + offset, chunk_size)?);
+    }
+
+    Ok(buffer)
+}
+
+/// Read a large range of a shared source a chunk at a time like read_shared_paged, except pages
+/// that fail to read are left out. Every run of readable bytes is handed back along with where it
+/// starts, nothing is lost to a chunk holding a single bad page since those are read page by page
+///
+pub fn read_shared_runs(source: &SharedSource, address: u64, size: usize) -> Vec<(u64, Vec<u8>)> {
+    const PAGE_SIZE: usize = 0x1000;
+    let mut runs: Vec<(u64, Vec<u8>)> = Vec::new();
+
+    for offset in (0..size).step_by(SHARED_READ_CHUNK) {
+        let chunk_size = SHARED_READ_CHUNK.min(size - offset);
+        let chunk_address = address + offset as u64;
+        let mut source = lock_source(source);
+
+        let pages = match source.read_bytes(chunk_address as usize, chunk_size) {
+            Ok(bytes) => vec![(chunk_address, bytes)],
+            Err(_) => (0..chunk_size)
+                .step_by(PAGE_SIZE)
+                .filter_map(|page_offset| {
+                    let page_address = chunk_address + page_offset as u64;
+                    let page_size = PAGE_SIZE.min(chunk_size - page_offset);
+                    let bytes = source.read_bytes(page_address as usize, page_size).ok()?;
+                    Some((page_address, bytes))
+                })
+                .collect(),
+        };
+
+        for (page_address, bytes) in pages {
+            match runs.last_mut() {
+                Some((start, run)) if *start + run.len() as u64 == page_address => {
+                    run.extend(bytes)
+                }
+                _ => runs.push((page_address, bytes)),
+            }
+        }
+    }
+
+    runs
+}
+
+impl dyn MemorySource + '_ {
+    /// Read the current value at each address and hand it out along with the index of the
+    /// address, any that can no longer be read are skipped
+    ///
+    pub fn read_each(
+        &mut self,
+        addresses: &[u64],
+        width: usize,
+        mut visit: impl FnMut(usize, &[u8]) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let page_size = 0x1000u64;
+        // Results sit close together so read a page at a time rather than value by value
+        let mut page: Option<(u64, Vec<u8>)> = None;
+
+        for (idx, &address) in addresses.iter().enumerate() {
+            let in_page = |page: &Option<(u64, Vec<u8>)>| {
+                page.as_ref().is_some_and(|(base, bytes)| {
+                    address >= *base && address + width as u64 <= base + bytes.len() as u64
+                })
+            };
+            if !in_page(&page) {
+                let base = address & !(page_size - 1);
+                page = self
+                    .read_bytes(base as usize, (page_size * 2) as usize)
+                    .or_else(|_| self.read_bytes(base as usize, page_size as usize))
+                    .ok()
+                    .map(|bytes| (base, bytes));
+            }
+
+            // Values straddling the end of what we could read are read on their own
+            match &page {
+                Some((base, bytes)) if in_page(&page) => {
+                    let offset = (address - base) as usize;
+                    visit(idx, &bytes[offset..offset + width])?;
+                }
+                _ => {
+                    if let Ok(bytes) = self.read_bytes(address as usize, width) {
+                        visit(idx, &bytes)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use super::pe::{get_image_headers, get_pe_from_path, get_sections, ImageHeaders};
+use crate::memory::dump::dump::file_name_of;
+use crate::memory::process::types::*;
+use crate::memory::source::source::{MemorySource, SourceErrors};
+use exe::Buffer;
+
+/// Round a size up to the next multiple of an alignment, images with a broken alignment of 0 are
+/// left as is
+///
+fn align_up(value: u64, alignment: u64) -> u64 {
+    if alignment == 0 {
+        return value;
+    }
+    value.div_ceil(alignment) * alignment
+}
+
+/// A PE file from disk mapped the way the loader would map it at its preferred image base, this
+/// lets every view work on a binary without ever running it
+///
+#[derive(Clone)]
+pub struct ImageTarget {
+    name: String,
+    path: String,
+    headers: ImageHeaders,
+    image: Vec<u8>,
+    regions: Vec<MemoryRegion>,
+}
+
+impl ImageTarget {
+    /// Read a PE from disk and map its headers and sections, anything past a section's raw data is
+    /// left zeroed same as the loader would leave it
+    ///
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let pe = get_pe_from_path(path.to_string())?;
+        let headers = get_image_headers(&pe)?;
+        let sections = get_sections(&pe)?;
+        let alignment = headers.section_alignment as u64;
+
+        let mut image = vec![0u8; headers.size_of_image as usize];
+        let mut regions = Vec::with_capacity(sections.len() + 1);
+
+        // Copy over the headers, they're always mapped read only at the very start of the image
+        let header_size = std::cmp::min(headers.size_of_headers as usize, image.len());
+        let header_bytes = pe.read(0, std::cmp::min(header_size, pe.len()))?;
+        image[..header_bytes.len()].copy_from_slice(header_bytes);
+        regions.push(MemoryRegion {
+            base_address: headers.image_base,
+            region_size: align_up(header_size as u64, alignment),
+            protect: PAGE_READONLY,
+            state: MEM_COMMIT,
+            region_type: MEM_IMAGE,
+        });
+
+        for section in sections.iter() {
+            let start = section.virtual_address as usize;
+            if start >= image.len() {
+                continue;
+            }
+
+            // Raw data can be larger than the virtual size thanks to file alignment, only copy
+            // whatever actually fits within the section and the file
+            let virtual_size = match section.virtual_size {
+                0 => section.raw_size,
+                size => size,
+            } as usize;
+            let to_copy = std::cmp::min(section.raw_size as usize, virtual_size)
+                .min(image.len() - start)
+                .min(pe.len().saturating_sub(section.raw_offset as usize));
+            if to_copy != 0 {
+                let raw = pe.read(section.raw_offset as usize, to_copy)?;
+                image[start..start + to_copy].copy_from_slice(raw);
+            }
+
+            regions.push(MemoryRegion {
+                base_address: headers.image_base + start as u64,
+                region_size: std::cmp::min(
+                    align_up(virtual_size as u64, alignment),
+                    (image.len() - start) as u64,
+                ),
+                protect: section.protection(),
+                state: MEM_COMMIT,
+                region_type: MEM_IMAGE,
+            });
+        }
+
+        Ok(Self {
+            name: file_name_of(path),
+            path: path.to_string(),
+            headers,
+            image,
+            regions,
+        })
+    }
+}
+
+impl MemorySource for ImageTarget {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    /// The whole image is mapped contiguously so any read within it succeeds, gaps between
+    /// sections just read back as zeros
+    ///
+    fn read_bytes(&mut self, address: usize, size: usize) -> anyhow::Result<Vec<u8>> {
+        let image_base = self.headers.image_base as usize;
+        let offset = address
+            .checked_sub(image_base)
+            .filter(|offset| offset + size <= self.image.len())
+            .ok_or(SourceErrors::Unmapped {
+                address: address as u64,
+            })?;
+
+        Ok(self.image[offset..offset + size].to_vec())
+    }
+    fn write_bytes(&mut self, _address: usize, _bytes: &[u8]) -> anyhow::Result<()> {
+        Err(SourceErrors::ReadOnly {
+            target_name: self.name.clone(),
+        })?
+    }
+    fn regions(&mut self) -> anyhow::Result<Vec<MemoryRegion>> {
+        Ok(self.regions.clone())
+    }
+    fn modules(&mut self) -> anyhow::Result<Vec<ModuleInfo>> {
+        Ok(vec![ModuleInfo {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            base_address: self.headers.image_base,
+            size: self.image.len() as u64,
+        }])
+    }
+    fn base(&mut self) -> anyhow::Result<u64> {
+        Ok(self.headers.image_base)
+    }
+    fn is_read_only(&self) -> bool {
+        true
+    }
+    fn bitness(&self) -> u32 {
+        if self.headers.is_64 {
+            64
+        } else {
+            32
+        }
+    }
+    fn is_windows(&self) -> bool {
+        true
+    }
+}
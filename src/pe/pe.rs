@@ -1,10 +1,15 @@
+use crate::memory::process::types::*;
+use crate::memory::source::source::MemorySource;
 use exe::headers::ImageDirectoryEntry;
+use exe::headers::ImageExportDirectory;
 use exe::headers::ImageImportDescriptor;
-use exe::types::{CCharString, ImportDirectory};
+use exe::types::NTHeaders;
+use exe::types::{CCharString, ImportDirectory, ThunkData};
 use exe::Buffer;
 use exe::VecPE;
 use exe::PE;
 use exe::RVA;
+use std::collections::HashMap;
 
 /// Structure representing a runtime function entry in the exception directory
 ///
@@ -15,12 +20,116 @@ pub struct RuntimeFunction {
     pub unwind_info: u32,
 }
 
+/// The handful of header fields we show and map images with, pulled out of whichever optional
+/// header the image has
+///
+#[derive(Debug, Clone, Default)]
+pub struct ImageHeaders {
+    pub machine: u16,
+    pub is_64: bool,
+    pub image_base: u64,
+    pub entry_point: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub section_alignment: u32,
+    pub number_of_sections: u16,
+    pub time_date_stamp: u32,
+}
+
+/// A single section of an image
+///
+#[derive(Debug, Clone, Default)]
+pub struct SectionInfo {
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub raw_offset: u32,
+    pub raw_size: u32,
+    pub characteristics: u32,
+}
+
+impl SectionInfo {
+    /// Map the section's memory characteristics onto the PAGE_* protection the loader would give it
+    ///
+    pub fn protection(&self) -> u32 {
+        let execute = self.characteristics & 0x2000_0000 != 0;
+        let read = self.characteristics & 0x4000_0000 != 0;
+        let write = self.characteristics & 0x8000_0000 != 0;
+
+        match (read, write, execute) {
+            (false, false, false) => PAGE_NOACCESS,
+            (true, false, false) => PAGE_READONLY,
+            (_, true, false) => PAGE_READWRITE,
+            (false, false, true) => PAGE_EXECUTE,
+            (true, false, true) => PAGE_EXECUTE_READ,
+            (_, true, true) => PAGE_EXECUTE_READWRITE,
+        }
+    }
+}
+
 /// Get a VecPE from an image path provided
 ///
 pub fn get_pe_from_path(path: String) -> anyhow::Result<VecPE> {
     Ok(VecPE::from_disk_file(path)?)
 }
 
+/// Get a VecPE from a module already mapped within a target, this is laid out as it is in memory
+/// rather than on disk
+///
+pub fn get_pe_from_source(
+    source: &mut dyn MemorySource,
+    module: &ModuleInfo,
+) -> anyhow::Result<VecPE> {
+    let image = source.read_bytes_paged(module.base_address as usize, module.size as usize)?;
+    Ok(VecPE::from_memory_data(image))
+}
+
+/// Get the headers of an image
+///
+pub fn get_image_headers(image: &VecPE) -> anyhow::Result<ImageHeaders> {
+    Ok(match image.get_valid_nt_headers()? {
+        NTHeaders::NTHeaders32(headers) => ImageHeaders {
+            machine: headers.file_header.machine,
+            is_64: false,
+            image_base: headers.optional_header.image_base as u64,
+            entry_point: headers.optional_header.address_of_entry_point.0,
+            size_of_image: headers.optional_header.size_of_image,
+            size_of_headers: headers.optional_header.size_of_headers,
+            section_alignment: headers.optional_header.section_alignment,
+            number_of_sections: headers.file_header.number_of_sections,
+            time_date_stamp: headers.file_header.time_date_stamp,
+        },
+        NTHeaders::NTHeaders64(headers) => ImageHeaders {
+            machine: headers.file_header.machine,
+            is_64: true,
+            image_base: headers.optional_header.image_base,
+            entry_point: headers.optional_header.address_of_entry_point.0,
+            size_of_image: headers.optional_header.size_of_image,
+            size_of_headers: headers.optional_header.size_of_headers,
+            section_alignment: headers.optional_header.section_alignment,
+            number_of_sections: headers.file_header.number_of_sections,
+            time_date_stamp: headers.file_header.time_date_stamp,
+        },
+    })
+}
+
+/// Get every section within an image
+///
+pub fn get_sections(image: &VecPE) -> anyhow::Result<Vec<SectionInfo>> {
+    Ok(image
+        .get_section_table()?
+        .iter()
+        .map(|section| SectionInfo {
+            name: section.name.as_str().unwrap_or_default().to_string(),
+            virtual_address: section.virtual_address.0,
+            virtual_size: section.virtual_size,
+            raw_offset: section.pointer_to_raw_data.0,
+            raw_size: section.size_of_raw_data,
+            characteristics: section.characteristics.bits(),
+        })
+        .collect())
+}
+
 /// Get imports from a specific module in an image
 ///
 pub fn get_imports<'a>(image: &'a VecPE) -> anyhow::Result<ImportDirectory<'a>> {
@@ -51,6 +160,66 @@ pub fn get_imports_descriptor_from_name(
         .ok_or_else(|| anyhow::anyhow!("Module {module_name} not found"))
 }
 
+/// Get the name of an import whether it's imported by name or ordinal
+///
+pub fn get_import_name(import: &exe::ImportData) -> String {
+    match import {
+        exe::ImportData::Ordinal(ord) => ord.to_string(),
+        exe::ImportData::ImportByName(name) => name.to_string(),
+    }
+}
+
+/// Find the IAT entry of an imported function and read what it's been resolved to within the
+/// target, returns both the entry address and the function address
+///
+pub fn get_iat_entry(
+    source: &mut dyn MemorySource,
+    image: &VecPE,
+    module_name: &str,
+    function_name: &str,
+) -> anyhow::Result<(u64, u64)> {
+    let descriptor = get_imports_descriptor_from_name(image.clone(), module_name.to_string())?;
+
+    // Get the index of the imported function, the IAT is laid out in the same order
+    let idx = descriptor
+        .get_imports(image)?
+        .iter()
+        .position(|import| get_import_name(import) == function_name)
+        .ok_or_else(|| anyhow::anyhow!("Function {function_name} not found in {module_name}"))?;
+
+    // Make sure the thunk actually exists before we go reading it
+    if idx >= descriptor.get_first_thunk(image)?.len() {
+        return Err(anyhow::anyhow!("No thunk found for {function_name}"));
+    }
+
+    // Entries are as wide as a pointer within the image, not within the target
+    let pointer_size = match get_image_headers(image)?.is_64 {
+        true => 8,
+        false => 4,
+    };
+    let iat_entry_address =
+        source.base()? + descriptor.first_thunk.0 as u64 + (idx * pointer_size) as u64;
+    let func_address = source.read_pointer_sized(iat_entry_address as usize, pointer_size)?;
+
+    Ok((iat_entry_address, func_address))
+}
+
+/// Get every function an image exports along with its RVA, forwarded exports live within some
+/// other module so they're left out
+///
+pub fn get_exports(image: &VecPE) -> anyhow::Result<HashMap<String, u32>> {
+    let export_directory = ImageExportDirectory::parse(image)?;
+
+    Ok(export_directory
+        .get_export_map(image)?
+        .into_iter()
+        .filter_map(|(name, thunk)| match thunk {
+            ThunkData::Function(rva) => Some((name.to_string(), rva.0)),
+            _ => None,
+        })
+        .collect())
+}
+
 /// Get all functions within exception directory
 ///
 pub fn get_functions(image: &VecPE) -> anyhow::Result<Vec<RuntimeFunction>> {
@@ -123,3 +292,153 @@ pub fn get_functions(image: &VecPE) -> anyhow::Result<Vec<RuntimeFunction>> {
 
     Ok(functions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::source::mock::MockTarget;
+
+    const BASE: u64 = 0x40_0000;
+
+    /// A mapped image with a single .idata section importing CreateFileW and ExitProcess from
+    /// kernel32.dll and exporting Attach along with Detach forwarded onto kernel32, thunks are as
+    /// wide as the image's pointers
+    ///
+    fn image(is_64: bool) -> Vec<u8> {
+        let mut image = vec![0u8; 0x2000];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            image[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        let (thunk_size, optional_size) = match is_64 {
+            true => (8, 0xF0u16),
+            false => (4, 0xE0u16),
+        };
+
+        // DOS and file headers
+        put(0, b"MZ");
+        put(0x3C, &0x80u32.to_le_bytes());
+        put(0x80, b"PE\0\0");
+        put(
+            0x84,
+            &(if is_64 { 0x8664u16 } else { 0x14Cu16 }).to_le_bytes(),
+        );
+        put(0x86, &1u16.to_le_bytes());
+        put(0x94, &optional_size.to_le_bytes());
+
+        // Optional header, the data directories follow the fields that differ in size
+        let optional = 0x98;
+        let directories = match is_64 {
+            true => {
+                put(optional, &0x20Bu16.to_le_bytes());
+                put(optional + 24, &BASE.to_le_bytes());
+                put(optional + 108, &16u32.to_le_bytes());
+                optional + 112
+            }
+            false => {
+                put(optional, &0x10Bu16.to_le_bytes());
+                put(optional + 28, &(BASE as u32).to_le_bytes());
+                put(optional + 92, &16u32.to_le_bytes());
+                optional + 96
+            }
+        };
+        put(optional + 32, &0x1000u32.to_le_bytes());
+        put(optional + 36, &0x200u32.to_le_bytes());
+        put(optional + 56, &0x2000u32.to_le_bytes());
+        put(optional + 60, &0x400u32.to_le_bytes());
+        put(directories, &0x1400u32.to_le_bytes());
+        put(directories + 4, &0x100u32.to_le_bytes());
+        put(directories + 8, &0x1000u32.to_le_bytes());
+        put(directories + 12, &0x28u32.to_le_bytes());
+
+        // Section table
+        let section = optional + optional_size as usize;
+        put(section, b".idata\0\0");
+        for (offset, value) in [(8, 0x1000u32), (12, 0x1000), (16, 0x1000), (20, 0x1000)] {
+            put(section + offset, &value.to_le_bytes());
+        }
+        put(section + 36, &0xC000_0040u32.to_le_bytes());
+
+        // Import descriptor, lookup table, address table and names
+        put(0x1000, &0x1100u32.to_le_bytes());
+        put(0x100C, &0x1200u32.to_le_bytes());
+        put(0x1010, &0x1180u32.to_le_bytes());
+        let thunks = |values: [u64; 2]| {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes()[..thunk_size].to_vec())
+                .collect::<Vec<u8>>()
+        };
+        put(0x1100, &thunks([0x1300, 0x1310]));
+        put(0x1180, &thunks([0x7000_1000, 0x7000_2000]));
+        put(0x1200, b"kernel32.dll\0");
+        put(0x1302, b"CreateFileW\0");
+        put(0x1312, b"ExitProcess\0");
+
+        // Export directory, functions, names and ordinals. Detach points back within the export
+        // directory at the name of what it's forwarded onto
+        put(0x1400 + 12, &0x1480u32.to_le_bytes());
+        put(0x1400 + 16, &1u32.to_le_bytes());
+        put(0x1400 + 20, &2u32.to_le_bytes());
+        put(0x1400 + 24, &2u32.to_le_bytes());
+        put(0x1400 + 28, &0x1440u32.to_le_bytes());
+        put(0x1400 + 32, &0x1450u32.to_le_bytes());
+        put(0x1400 + 36, &0x1460u32.to_le_bytes());
+        put(0x1440, &0x1800u32.to_le_bytes());
+        put(0x1444, &0x14A0u32.to_le_bytes());
+        put(0x1450, &0x1490u32.to_le_bytes());
+        put(0x1454, &0x1498u32.to_le_bytes());
+        put(0x1462, &1u16.to_le_bytes());
+        put(0x1480, b"game.exe\0");
+        put(0x1490, b"Attach\0");
+        put(0x1498, b"Detach\0");
+        put(0x14A0, b"kernel32.Sleep\0");
+
+        image
+    }
+
+    fn iat_entry(is_64: bool, function_name: &str) -> anyhow::Result<(u64, u64)> {
+        let mut target =
+            MockTarget::new("game.exe").with_module("game.exe", BASE, image(is_64), PAGE_READWRITE);
+        let module = target.modules()?.remove(0);
+        let pe = get_pe_from_source(&mut target, &module)?;
+
+        get_iat_entry(&mut target, &pe, "KERNEL32.dll", function_name)
+    }
+
+    #[test]
+    fn iat_entries_are_pointer_sized_in_64_bit_images() {
+        assert_eq!(
+            iat_entry(true, "ExitProcess").unwrap(),
+            (BASE + 0x1188, 0x7000_2000)
+        );
+    }
+
+    #[test]
+    fn iat_entries_are_pointer_sized_in_32_bit_images() {
+        assert_eq!(
+            iat_entry(false, "CreateFileW").unwrap(),
+            (BASE + 0x1180, 0x7000_1000)
+        );
+        assert_eq!(
+            iat_entry(false, "ExitProcess").unwrap(),
+            (BASE + 0x1184, 0x7000_2000)
+        );
+    }
+
+    #[test]
+    fn missing_imports_are_errors() {
+        assert!(iat_entry(true, "Sleep").is_err());
+    }
+
+    #[test]
+    fn forwarded_exports_are_left_out() {
+        let mut target =
+            MockTarget::new("game.exe").with_module("game.exe", BASE, image(true), PAGE_READWRITE);
+        let module = target.modules().unwrap().remove(0);
+        let pe = get_pe_from_source(&mut target, &module).unwrap();
+
+        let exports = get_exports(&pe).unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports.get("Attach"), Some(&0x1800));
+    }
+}